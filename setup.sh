@@ -10,19 +10,42 @@ for origin in "${ORIGINS[@]}"; do
     origins_yaml="$origins_yaml\n    - $origin"
 done
 
+sponsors_yaml=""
+IFS=',' read -ra SPONSORS <<< "${SPONSOR_ACCOUNT_PRIVATE_KEYS:-$SPONSOR_ACCOUNT_PRIVATE_KEY}"
+for sponsor in "${SPONSORS[@]}"; do
+    sponsors_yaml="$sponsors_yaml\n    - $sponsor"
+done
+
+# Chain id and testnet flag of the network profile unless set explicitly, the chain id is
+# checked against the node on startup and the flag enables testnet-only actions like minting
+case "${NETWORK:-default}" in
+    mainnet) default_chain_id=1; default_testnet=false ;;
+    testnet) default_chain_id=2; default_testnet=true ;;
+    *) default_chain_id=208; default_testnet=true ;;
+esac
 
 # Generate the config.yaml dynamically
 cat <<EOF > /secrets/config/config.yaml
-aptos_base_url: ${APTOS_BASE_URL}
-contract_address: ${CONTRACT_ADDRESS}
-decibel_url: ${DECIBEL_URL}
+network: ${NETWORK:-default}
+networks:
+  ${NETWORK:-default}:
+    node_url: ${APTOS_BASE_URL}
+    decibel_url: ${DECIBEL_URL}
+    contract_address: ${CONTRACT_ADDRESS}
+    explorer_txn_url: ${EXPLORER_TXN_URL:-https://explorer.aptoslabs.com/txn/{hash}?network=decibel}
+    chain_id: ${CHAIN_ID:-$default_chain_id}
+    testnet: ${TESTNET:-$default_testnet}
 terminal_url: ${TERMINAL_URL}
 server_config:
   port: ${PORT}
+  metrics_port: ${METRICS_PORT:-9090}
   allowed_origins: ${origins_yaml}
+  public_url: ${PUBLIC_URL:-null}
 jwt_config:
   secret: ${JWT_SECRET}
   expires_in: 1d
+api_key_config:
+  secret: ${API_KEY_SECRET}
 db_config: 
   url: ${DATABASE_URL}
   pool_size: 10
@@ -34,7 +57,11 @@ bot_config:
   token: ${BOT_TOKEN}
   username: ${BOT_USERNAME}
 admin_config:
-  sponsor_private_key: ${SPONSOR_ACCOUNT_PRIVATE_KEY}
+  sponsor_private_keys: ${sponsors_yaml}
+  admin_chat_ids: [${ADMIN_CHAT_IDS}]
+  gas_sponsorship:
+    daily_quota: ${GAS_DAILY_QUOTA:-5000000}
+    low_balance_threshold: ${SPONSOR_LOW_BALANCE_THRESHOLD:-1000000000}
 EOF
 
 # Run the Rust binary
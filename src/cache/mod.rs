@@ -1,9 +1,15 @@
 use bigdecimal::BigDecimal;
 use moka::future::Cache as MokaCache;
 use serde::{Deserialize, Serialize};
-use std::{hash::Hash, time::Duration};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use utoipa::ToSchema;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Market {
     pub market_addr: String,
     pub market_name: String,
@@ -16,33 +22,55 @@ pub struct Market {
     pub max_open_interest: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct AssetContext {
     pub market: String,
+    #[schema(value_type = String)]
     pub volume_24h: BigDecimal,
+    #[schema(value_type = String)]
     pub open_interest: BigDecimal,
+    #[schema(value_type = String)]
     pub mark_price: BigDecimal,
+    #[schema(value_type = String)]
     pub mid_price: BigDecimal,
+    #[schema(value_type = String)]
     pub oracle_price: BigDecimal,
+    #[schema(value_type = String)]
     pub previous_day_price: BigDecimal,
+    #[schema(value_type = String)]
     pub price_change_pct_24h: BigDecimal,
+    #[schema(value_type = Vec<String>)]
     pub price_history: Vec<BigDecimal>,
 }
 
+pub const DATASET_MARKETS: &str = "markets";
+pub const DATASET_ASSET_CONTEXTS: &str = "asset_contexts";
+
+/// A dataset older than this is stale, a few missed refreshes of the market indexer
+const MARKETS_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+const ASSET_CONTEXTS_MAX_AGE: Duration = Duration::from_secs(60);
+
 #[async_trait::async_trait]
 pub trait ICache: Send + Sync + 'static {
+    /// Every dataset was set recently enough to be served
     fn is_healthy(&self) -> bool;
 
+    /// Time since each dataset was last set, `None` for datasets never set
+    fn dataset_ages(&self) -> Vec<(&'static str, Option<Duration>)>;
+
+    async fn get_markets(&self) -> Vec<Market>;
     async fn get_market(&self, market_name: &str) -> Option<Market>;
     async fn get_markets_ilike(&self, market_name: &str) -> Vec<Market>;
     async fn set_markets(&self, markets: Vec<Market>);
 
     async fn set_asset_contexts(&self, asset_contexts: Vec<AssetContext>);
+    async fn get_asset_contexts(&self) -> Vec<AssetContext>;
     async fn get_asset_context(&self, market: &str) -> Option<AssetContext>;
 }
 pub struct Cache {
     markets: MokaCache<String, Vec<Market>>,
     asset_contexts: MokaCache<String, Vec<AssetContext>>,
+    refreshed_at: Mutex<HashMap<&'static str, Instant>>,
 }
 
 impl Cache {
@@ -64,14 +92,46 @@ impl Cache {
         Self {
             markets,
             asset_contexts,
+            refreshed_at: Mutex::new(HashMap::new()),
         }
     }
+
+    fn mark_refreshed(&self, dataset: &'static str) {
+        self.refreshed_at
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(dataset, Instant::now());
+    }
 }
 
 #[async_trait::async_trait]
 impl ICache for Cache {
     fn is_healthy(&self) -> bool {
-        true
+        self.dataset_ages().into_iter().all(|(dataset, age)| {
+            let max_age = match dataset {
+                DATASET_MARKETS => MARKETS_MAX_AGE,
+                _ => ASSET_CONTEXTS_MAX_AGE,
+            };
+            age.is_some_and(|age| age <= max_age)
+        })
+    }
+
+    fn dataset_ages(&self) -> Vec<(&'static str, Option<Duration>)> {
+        let refreshed_at = self
+            .refreshed_at
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        [DATASET_MARKETS, DATASET_ASSET_CONTEXTS]
+            .into_iter()
+            .map(|dataset| {
+                let age = refreshed_at.get(dataset).map(Instant::elapsed);
+                (dataset, age)
+            })
+            .collect()
+    }
+
+    async fn get_markets(&self) -> Vec<Market> {
+        self.markets.get("markets").await.unwrap_or_default()
     }
 
     async fn get_market(&self, market_name: &str) -> Option<Market> {
@@ -101,12 +161,21 @@ impl ICache for Cache {
 
     async fn set_markets(&self, markets: Vec<Market>) {
         self.markets.insert("markets".to_string(), markets).await;
+        self.mark_refreshed(DATASET_MARKETS);
     }
 
     async fn set_asset_contexts(&self, asset_contexts: Vec<AssetContext>) {
         self.asset_contexts
             .insert("asset_contexts".to_string(), asset_contexts)
             .await;
+        self.mark_refreshed(DATASET_ASSET_CONTEXTS);
+    }
+
+    async fn get_asset_contexts(&self) -> Vec<AssetContext> {
+        self.asset_contexts
+            .get("asset_contexts")
+            .await
+            .unwrap_or_default()
     }
 
     async fn get_asset_context(&self, market: &str) -> Option<AssetContext> {
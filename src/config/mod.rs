@@ -1,4 +1,4 @@
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{collections::HashMap, fs::File, io::Read, path::PathBuf};
 
 use anyhow::{Context, Ok};
 use clap::Parser;
@@ -13,23 +13,60 @@ pub struct Cli {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
-    pub aptos_base_url: String,
-    pub contract_address: String,
-    pub decibel_url: String,
+    /// Name of the profile in `networks` the bot runs against
+    pub network: String,
+    pub networks: HashMap<String, NetworkProfile>,
     pub terminal_url: String,
     pub server_config: ServerConfig,
     pub jwt_config: JWTConfig,
+    pub api_key_config: ApiKeyConfig,
     pub db_config: DbConfig,
-    pub turnkey_config: TurnkeyConfig,
+    #[serde(default)]
+    pub turnkey_config: Option<TurnkeyConfig>,
+    #[serde(default)]
+    pub signer_config: SignerConfig,
     pub bot_config: BotConfig,
     pub admin_config: AdminConfig,
+    #[serde(default)]
+    pub signing_policy: SigningPolicyConfig,
     pub stream_config: StreamConfig,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkProfile {
+    pub node_url: String,
+    pub decibel_url: String,
+    pub contract_address: String,
+    /// Explorer link for a transaction, `{hash}` is replaced with the txn hash
+    pub explorer_txn_url: String,
+    pub chain_id: u8,
+    /// Enables functions only meant for test networks, such as the faucet
+    #[serde(default)]
+    pub testnet: bool,
+}
+
+impl NetworkProfile {
+    pub fn txn_url(&self, txn_hash: &str) -> String {
+        self.explorer_txn_url.replace("{hash}", txn_hash)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
     pub port: u16,
+    /// Port of the Prometheus scrape endpoint, kept off the public port
+    #[serde(default = "ServerConfig::default_metrics_port")]
+    pub metrics_port: u16,
     pub allowed_origins: Vec<String>,
+    /// Base URL the server is reachable at, one-time key export links are disabled without it
+    #[serde(default)]
+    pub public_url: Option<String>,
+}
+
+impl ServerConfig {
+    pub const fn default_metrics_port() -> u16 {
+        9090
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,6 +75,34 @@ pub struct JWTConfig {
     pub expires_in: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyConfig {
+    /// Encrypts the secrets of API keys at rest. Changing it invalidates every key.
+    pub secret: String,
+    /// Requests per minute a new key may make
+    #[serde(default = "ApiKeyConfig::default_rate_limit_per_minute")]
+    pub rate_limit_per_minute: u32,
+    #[serde(default = "ApiKeyConfig::default_max_keys_per_user")]
+    pub max_keys_per_user: usize,
+    /// How far the timestamp of a signed request may be from the server time
+    #[serde(default = "ApiKeyConfig::default_signature_ttl_secs")]
+    pub signature_ttl_secs: i64,
+}
+
+impl ApiKeyConfig {
+    pub const fn default_rate_limit_per_minute() -> u32 {
+        60
+    }
+
+    pub const fn default_max_keys_per_user() -> usize {
+        5
+    }
+
+    pub const fn default_signature_ttl_secs() -> i64 {
+        30
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DbConfig {
     pub url: String,
@@ -58,15 +123,162 @@ pub struct TurnkeyConfig {
     pub api_public_key: String,
 }
 
+/// Backend holding the keys of user wallets
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum SignerConfig {
+    #[default]
+    Turnkey,
+    /// Hex encoded Ed25519 keys kept in memory
+    Local {
+        #[serde(default)]
+        private_keys: Vec<String>,
+    },
+    /// Password encrypted key files in `path`
+    Keystore { path: String, password: String },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotConfig {
     pub token: String,
     pub username: String,
+    /// Bot API server, `https://api.telegram.org` unless set
+    #[serde(default)]
+    pub api_url: Option<String>,
+}
+
+impl BotConfig {
+    pub fn bot(&self) -> teloxide::Bot {
+        let bot = teloxide::Bot::new(&self.token);
+        match &self.api_url {
+            Some(api_url) => {
+                bot.set_api_url(url::Url::parse(api_url).expect("Invalid bot_config.api_url"))
+            }
+            None => bot,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdminConfig {
-    pub sponsor_private_key: String,
+    /// Fee payers of sponsored txns, used round-robin. Older configs set a single
+    /// `sponsor_private_key`.
+    #[serde(alias = "sponsor_private_key", deserialize_with = "one_or_many")]
+    pub sponsor_private_keys: Vec<String>,
+    /// Telegram chats alerted when a sponsor runs low on APT
+    #[serde(default)]
+    pub admin_chat_ids: Vec<i64>,
+    #[serde(default)]
+    pub gas_sponsorship: GasSponsorshipConfig,
+}
+
+fn one_or_many<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Result::Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(value) => vec![value],
+        OneOrMany::Many(values) => values,
+    })
+}
+
+/// Amounts are in octas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasSponsorshipConfig {
+    /// Gas fees sponsored per user per UTC day, users pay their own gas past it
+    #[serde(default = "GasSponsorshipConfig::default_daily_quota")]
+    pub daily_quota: u64,
+    /// Sponsor APT balance below which admins are alerted
+    #[serde(default = "GasSponsorshipConfig::default_low_balance_threshold")]
+    pub low_balance_threshold: u64,
+    #[serde(default = "GasSponsorshipConfig::default_check_interval_secs")]
+    pub check_interval_secs: u64,
+}
+
+impl GasSponsorshipConfig {
+    pub const fn default_daily_quota() -> u64 {
+        5_000_000
+    }
+
+    pub const fn default_low_balance_threshold() -> u64 {
+        1_000_000_000
+    }
+
+    pub const fn default_check_interval_secs() -> u64 {
+        300
+    }
+}
+
+impl Default for GasSponsorshipConfig {
+    fn default() -> Self {
+        Self {
+            daily_quota: Self::default_daily_quota(),
+            low_balance_threshold: Self::default_low_balance_threshold(),
+            check_interval_secs: Self::default_check_interval_secs(),
+        }
+    }
+}
+
+/// Entry functions the server is allowed to sign and sponsor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningPolicyConfig {
+    pub allowed_functions: Vec<AllowedFunction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowedFunction {
+    /// `<address>::<module>::<function>`, `contract` standing for the network's contract
+    /// address and `*` for every function of the module
+    pub function: String,
+    #[serde(default)]
+    pub arg_limits: Vec<ArgLimit>,
+    #[serde(default)]
+    pub testnet_only: bool,
+}
+
+/// Upper bound on a `u64` argument, e.g. the amount of a transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArgLimit {
+    /// Position of the argument in the entry function
+    pub index: usize,
+    pub max: u64,
+}
+
+impl Default for SigningPolicyConfig {
+    fn default() -> Self {
+        Self {
+            allowed_functions: vec![
+                AllowedFunction {
+                    function: "contract::dex_accounts::*".to_string(),
+                    arg_limits: vec![],
+                    testnet_only: false,
+                },
+                AllowedFunction {
+                    function: "0x1::primary_fungible_store::transfer".to_string(),
+                    arg_limits: vec![ArgLimit {
+                        index: 2,
+                        max: 100_000_000_000,
+                    }],
+                    testnet_only: false,
+                },
+                AllowedFunction {
+                    function: "contract::usdc::mint".to_string(),
+                    arg_limits: vec![ArgLimit {
+                        index: 1,
+                        max: 1_000_000_000,
+                    }],
+                    testnet_only: true,
+                },
+            ],
+        }
+    }
 }
 
 impl Config {
@@ -83,9 +295,22 @@ impl Config {
 
         let config =
             serde_yaml::from_str::<Config>(&contents).context("Failed to parse yaml file")?;
+        if !config.networks.contains_key(&config.network) {
+            anyhow::bail!("Network profile `{}` is not defined", config.network);
+        }
+        if config.api_key_config.secret.is_empty()
+            || config.api_key_config.secret == config.jwt_config.secret
+        {
+            anyhow::bail!("api_key_config.secret must be set and differ from the JWT secret");
+        }
 
         Ok(config)
     }
+
+    /// Active network profile, its presence is checked in `load`
+    pub fn network(&self) -> &NetworkProfile {
+        &self.networks[&self.network]
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -95,4 +320,13 @@ pub struct StreamConfig {
     pub ending_version: Option<i64>,
     pub auth_token: String,
     pub request_name_header: String,
+    /// `/ready` reports degraded when the indexer is more versions behind the chain head
+    #[serde(default = "StreamConfig::default_max_lag_versions")]
+    pub max_lag_versions: i64,
+}
+
+impl StreamConfig {
+    pub const fn default_max_lag_versions() -> i64 {
+        100_000
+    }
 }
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS unique_user_subaccount;
+
+ALTER TABLE subaccounts
+    DROP COLUMN IF EXISTS created_at;
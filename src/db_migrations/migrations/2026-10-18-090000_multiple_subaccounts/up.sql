@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE subaccounts
+    ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT NOW ();
+
+CREATE UNIQUE INDEX unique_user_subaccount ON subaccounts (user_id, address);
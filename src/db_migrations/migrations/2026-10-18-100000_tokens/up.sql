@@ -0,0 +1,33 @@
+-- Your SQL goes here
+-- tokens differ per network, `chain_id` is the one of the network profile
+CREATE TABLE tokens(
+    id UUID PRIMARY KEY NOT NULL DEFAULT gen_random_uuid (),
+    name VARCHAR NOT NULL,
+    symbol VARCHAR NOT NULL,
+    address VARCHAR(66) NOT NULL,
+    decimals INT NOT NULL,
+    is_collateral BOOLEAN NOT NULL DEFAULT(FALSE),
+    chain_id INT NOT NULL
+);
+
+CREATE UNIQUE INDEX unique_token_symbol ON tokens (chain_id, symbol);
+
+CREATE UNIQUE INDEX unique_collateral_token ON tokens (chain_id)
+WHERE is_collateral;
+
+INSERT INTO tokens (name, symbol, address, decimals, is_collateral, chain_id)
+VALUES (
+    'USD Coin',
+    'USDC',
+    '0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02',
+    6,
+    TRUE,
+    208
+), (
+    'USD Coin',
+    'USDC',
+    '0xbae207659db88bea0cbead6da0ed00aac12edcdda169e591cd41c94180b46f3b',
+    6,
+    TRUE,
+    1
+);
@@ -0,0 +1,29 @@
+-- Your SQL goes here
+-- txns spending what an earlier txn brings in wait for it to commit, `depends_on`, and are
+-- only signed then. `sponsored_gas_fee` is the simulated gas fee counted against the user's
+-- daily quota when the txn was signed, NULL when the sender paid, corrected once it commits.
+-- A worker claims the rows it works on until `locked_until`, so running several instances
+-- never submits or re-signs a txn twice
+CREATE TABLE transaction_outbox(
+    id UUID PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL,
+    kind VARCHAR(50) NOT NULL,
+    summary VARCHAR NOT NULL,
+    payload BYTEA NOT NULL,
+    signed_txn BYTEA,
+    txn_hash VARCHAR(66),
+    status VARCHAR(20) NOT NULL DEFAULT('pending'),
+    attempts INT NOT NULL DEFAULT(0),
+    error VARCHAR,
+    expires_at TIMESTAMP,
+    chat_id BIGINT,
+    message_id INT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    depends_on UUID REFERENCES transaction_outbox (id),
+    sponsored_gas_fee BIGINT,
+    locked_until TIMESTAMP
+);
+
+CREATE INDEX transaction_outbox_open ON transaction_outbox (created_at)
+WHERE status IN ('waiting', 'pending', 'submitted');
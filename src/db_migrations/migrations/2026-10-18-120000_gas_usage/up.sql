@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE gas_usage(
+    user_id UUID NOT NULL,
+    day DATE NOT NULL,
+    gas_fee BIGINT NOT NULL DEFAULT(0),
+    txn_count INT NOT NULL DEFAULT(0),
+    PRIMARY KEY (user_id, day)
+);
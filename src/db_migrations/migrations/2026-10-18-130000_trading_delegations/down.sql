@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS trading_delegations;
+
+ALTER TABLE users DROP COLUMN IF EXISTS custody_mode;
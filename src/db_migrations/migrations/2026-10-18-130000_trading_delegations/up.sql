@@ -0,0 +1,22 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN custody_mode VARCHAR(20) NOT NULL DEFAULT('custodial');
+
+-- delegations change once their txn is seen on chain, each txn counts once
+CREATE TABLE trading_delegations(
+    id UUID PRIMARY KEY NOT NULL DEFAULT gen_random_uuid (),
+    user_id UUID NOT NULL,
+    owner_address VARCHAR(66) NOT NULL,
+    delegate_address VARCHAR(66) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    revoked_at TIMESTAMP,
+    txn_hash VARCHAR(66),
+    revoke_txn_hash VARCHAR(66)
+);
+
+CREATE UNIQUE INDEX unique_active_delegation ON trading_delegations (user_id)
+WHERE
+    revoked_at IS NULL;
+
+CREATE UNIQUE INDEX unique_delegation_txn ON trading_delegations (txn_hash);
+
+CREATE UNIQUE INDEX unique_revoke_delegation_txn ON trading_delegations (revoke_txn_hash);
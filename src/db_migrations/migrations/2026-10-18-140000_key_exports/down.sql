@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS key_export_links;
+
+DROP TABLE IF EXISTS key_exports;
+
+ALTER TABLE users DROP COLUMN IF EXISTS pin_hash;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN pin_hash VARCHAR;
+
+CREATE TABLE key_exports(
+    id UUID PRIMARY KEY NOT NULL DEFAULT gen_random_uuid (),
+    user_id UUID NOT NULL,
+    delivery VARCHAR(20) NOT NULL,
+    outcome VARCHAR(20) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX key_exports_user_id_created_at ON key_exports (user_id, created_at);
+
+CREATE TABLE key_export_links(
+    token_hash VARCHAR(64) PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+CREATE TABLE auth_nonces(
+    nonce VARCHAR(64) PRIMARY KEY NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
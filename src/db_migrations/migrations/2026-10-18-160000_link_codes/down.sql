@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS link_codes;
+
+ALTER TABLE users DROP COLUMN IF EXISTS merged_into;
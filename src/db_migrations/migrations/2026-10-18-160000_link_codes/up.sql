@@ -0,0 +1,10 @@
+-- Your SQL goes here
+ALTER TABLE users ADD COLUMN merged_into UUID;
+
+CREATE TABLE link_codes(
+    code_hash VARCHAR(64) PRIMARY KEY NOT NULL,
+    user_id UUID NOT NULL,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
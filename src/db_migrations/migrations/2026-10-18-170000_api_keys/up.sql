@@ -0,0 +1,18 @@
+-- Your SQL goes here
+-- Requests are signed with an HMAC of the key secret, so the server needs the secret itself
+-- and a hash can't verify them. It is stored AES-256-GCM encrypted with
+-- `api_key_config.secret`, which never touches the database.
+CREATE TABLE api_keys(
+    id UUID PRIMARY KEY NOT NULL DEFAULT gen_random_uuid (),
+    user_id UUID NOT NULL,
+    key_id VARCHAR(40) UNIQUE NOT NULL,
+    encrypted_secret VARCHAR(256) NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    scope VARCHAR(20) NOT NULL,
+    rate_limit_per_minute INT4 NOT NULL,
+    last_used_at TIMESTAMP,
+    revoked_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX api_keys_user_id ON api_keys (user_id);
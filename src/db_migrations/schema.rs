@@ -1,5 +1,77 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    api_keys (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 40]
+        key_id -> Varchar,
+        #[max_length = 256]
+        encrypted_secret -> Varchar,
+        #[max_length = 64]
+        name -> Varchar,
+        #[max_length = 20]
+        scope -> Varchar,
+        rate_limit_per_minute -> Int4,
+        last_used_at -> Nullable<Timestamp>,
+        revoked_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    auth_nonces (nonce) {
+        #[max_length = 64]
+        nonce -> Varchar,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    gas_usage (user_id, day) {
+        user_id -> Uuid,
+        day -> Date,
+        gas_fee -> Int8,
+        txn_count -> Int4,
+    }
+}
+
+diesel::table! {
+    key_export_links (token_hash) {
+        #[max_length = 64]
+        token_hash -> Varchar,
+        user_id -> Uuid,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    key_exports (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 20]
+        delivery -> Varchar,
+        #[max_length = 20]
+        outcome -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    link_codes (code_hash) {
+        #[max_length = 64]
+        code_hash -> Varchar,
+        user_id -> Uuid,
+        expires_at -> Timestamp,
+        used_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     processor_status (processor) {
         #[max_length = 50]
@@ -17,6 +89,63 @@ diesel::table! {
         #[max_length = 66]
         address -> Varchar,
         is_primary -> Nullable<Bool>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tokens (id) {
+        id -> Uuid,
+        name -> Varchar,
+        symbol -> Varchar,
+        #[max_length = 66]
+        address -> Varchar,
+        decimals -> Int4,
+        is_collateral -> Bool,
+        chain_id -> Int4,
+    }
+}
+
+diesel::table! {
+    trading_delegations (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 66]
+        owner_address -> Varchar,
+        #[max_length = 66]
+        delegate_address -> Varchar,
+        created_at -> Timestamp,
+        revoked_at -> Nullable<Timestamp>,
+        #[max_length = 66]
+        txn_hash -> Nullable<Varchar>,
+        #[max_length = 66]
+        revoke_txn_hash -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    transaction_outbox (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        #[max_length = 50]
+        kind -> Varchar,
+        summary -> Varchar,
+        payload -> Bytea,
+        signed_txn -> Nullable<Bytea>,
+        #[max_length = 66]
+        txn_hash -> Nullable<Varchar>,
+        #[max_length = 20]
+        status -> Varchar,
+        attempts -> Int4,
+        error -> Nullable<Varchar>,
+        expires_at -> Nullable<Timestamp>,
+        chat_id -> Nullable<Int8>,
+        message_id -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        depends_on -> Nullable<Uuid>,
+        sponsored_gas_fee -> Nullable<Int8>,
+        locked_until -> Nullable<Timestamp>,
     }
 }
 
@@ -33,7 +162,24 @@ diesel::table! {
         wallet_id -> Varchar,
         slippage -> Int8,
         degen_mode -> Bool,
+        #[max_length = 20]
+        custody_mode -> Varchar,
+        pin_hash -> Nullable<Varchar>,
+        merged_into -> Nullable<Uuid>,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(processor_status, subaccounts, users,);
+diesel::allow_tables_to_appear_in_same_query!(
+    api_keys,
+    auth_nonces,
+    gas_usage,
+    key_export_links,
+    key_exports,
+    link_codes,
+    processor_status,
+    subaccounts,
+    tokens,
+    trading_delegations,
+    transaction_outbox,
+    users,
+);
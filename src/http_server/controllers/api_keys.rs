@@ -0,0 +1,113 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+};
+use uuid::Uuid;
+
+use crate::{
+    http_server::{
+        controllers::{InternalState, me::USER_TAG},
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::{
+        api::{
+            requests::create_api_key::CreateApiKey,
+            responses::api_key::{ApiKeyResponse, CreatedApiKeyResponse},
+        },
+        db::{api_keys::ApiKey, users::User},
+    },
+    utils::{api_keys::create_api_key, database_connection::get_db_connection},
+};
+
+#[utoipa::path(
+    post,
+    path = "/me/api-keys",
+    tag = USER_TAG,
+    request_body = CreateApiKey,
+    responses(
+        (status = 200, description = "Returns the new key with its secret, the secret is not shown again", body = CreatedApiKeyResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn create_key(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    api_key: Option<Extension<ApiKey>>,
+    Json(req): Json<CreateApiKey>,
+) -> ApiResult<Json<CreatedApiKeyResponse>> {
+    reject_api_key(api_key)?;
+    let mut conn = get_db_connection(&state.pool).await?;
+    let (api_key, secret) =
+        create_api_key(&state.config, db_user.id, &req.name, &req.scope, &mut conn)
+            .await
+            .map_err(ApiError::bad_request)?;
+    tracing::info!("{} created API key {}", db_user.id, api_key.key_id);
+    Ok(Json(CreatedApiKeyResponse {
+        api_key: api_key.into(),
+        secret,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/api-keys",
+    tag = USER_TAG,
+    responses(
+        (status = 200, description = "Returns the active API keys", body = Vec<ApiKeyResponse>)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn list_keys(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    api_key: Option<Extension<ApiKey>>,
+) -> ApiResult<Json<Vec<ApiKeyResponse>>> {
+    reject_api_key(api_key)?;
+    let mut conn = get_db_connection(&state.pool).await?;
+    let api_keys = ApiKey::get_by_user_id(db_user.id, &mut conn).await?;
+    Ok(Json(
+        api_keys.into_iter().map(ApiKeyResponse::from).collect(),
+    ))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/me/api-keys/{id}",
+    tag = USER_TAG,
+    params(
+        ("id" = Uuid, Path, description = "Id of the key")
+    ),
+    responses(
+        (status = 200, description = "Revokes the key, requests signed with it are rejected"),
+        (status = 404, description = "Key not found or already revoked")
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn revoke_key(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    api_key: Option<Extension<ApiKey>>,
+    Path(id): Path<Uuid>,
+) -> ApiResult<()> {
+    reject_api_key(api_key)?;
+    let mut conn = get_db_connection(&state.pool).await?;
+    if !ApiKey::revoke(id, db_user.id, &mut conn).await? {
+        return Err(ApiError::not_found("API key not found"));
+    }
+    tracing::info!("{} revoked API key {}", db_user.id, id);
+    Ok(())
+}
+
+/// Keys are managed with a JWT, so a leaked key can't mint or revoke others
+fn reject_api_key(api_key: Option<Extension<ApiKey>>) -> ApiResult<()> {
+    match api_key {
+        Some(_) => Err(ApiError::forbidden("API keys can't manage API keys")),
+        None => Ok(()),
+    }
+}
@@ -1,144 +1,121 @@
 use aptos_indexer_processor_sdk::utils::convert::standardize_address;
-use axum::{
-    Json,
-    extract::State,
-    response::{IntoResponse, Response},
-};
+use axum::{Json, extract::State};
 use chrono::{Duration, Utc};
 use jsonwebtoken::{EncodingKey, Header};
 use uuid::Uuid;
 
 use crate::{
     http_server::{
+        HttpServer,
         controllers::InternalState,
         middlewares::authentication::Claims,
-        utils::err_handler::{response_400_with_const, response_429_with_unhandled_err},
+        utils::{
+            api_error::{ApiError, ApiResult},
+            sign_in::{SignInMessage, sign_in_domain},
+            telegram_init_data::verify_init_data,
+        },
     },
     models::{
-        api::{requests::connect_wallet::ConnectWallet, responses::auth::AuthResponse},
-        db::users::User,
+        api::{
+            requests::{connect_wallet::ConnectWallet, tg_verify::TgVerify},
+            responses::auth::{AuthResponse, NonceResponse},
+        },
+        db::{
+            auth_nonces::NewAuthNonce,
+            users::{CUSTODY_CUSTODIAL, User},
+        },
+    },
+    schema::{auth_nonces, users},
+    signer::account_address,
+    utils::{
+        database_connection::get_db_connection, database_utils::DbPoolConnection,
+        db_execution::execute_with_better_error,
     },
-    schema::users,
-    utils::{database_connection::get_db_connection, db_execution::execute_with_better_error},
 };
 use aptos_crypto::{Signature, ValidCryptoMaterialStringExt, ed25519::*};
 
-pub const ADMIN_TAG: &str = "admin";
-// #[utoipa::path(
-//     get,
-//     path = "/auth/tg-verify",
-//     tag = ADMIN_TAG,
-//     responses(
-//         (status = 200, description = "Returns auth token", body = [AuthResponse])
-//     ),
-//     security(
-//         ("BearerAuth" = [])
-//     )
-// )]
-// pub async fn tg_verify(
-//     State(state): InternalState,
-//     Extension(db_user): Extension<User>
-// ) -> Response<String> {
-
-//     Response::builder()
-//         .status(StatusCode::OK)
-//         .body("OK".into())
-//         .unwrap()
-// }
+/// How long a sign-in nonce can be used
+const NONCE_TTL_SECS: i64 = 300;
 
 pub const AUTH_TAG: &str = "auth";
 #[utoipa::path(
     get,
+    path = "/auth/nonce",
+    tag = AUTH_TAG,
+    responses(
+        (status = 200, description = "Returns a nonce for a Sign-In-With-Aptos message", body = NonceResponse)
+    )
+)]
+pub async fn nonce(State(state): InternalState) -> ApiResult<Json<NonceResponse>> {
+    let domain = sign_in_domain(&state.config.terminal_url)?;
+    let mut conn = get_db_connection(&state.pool).await?;
+
+    let nonce = hex::encode(rand::random::<[u8; 16]>());
+    let expires_at = Utc::now() + Duration::seconds(NONCE_TTL_SECS);
+    let query = diesel::insert_into(auth_nonces::table).values(NewAuthNonce {
+        nonce: nonce.clone(),
+        expires_at: expires_at.naive_utc(),
+    });
+    execute_with_better_error(&mut conn, vec![query]).await?;
+
+    Ok(Json(NonceResponse {
+        domain,
+        nonce,
+        expires_at,
+    }))
+}
+
+#[utoipa::path(
+    post,
     path = "/auth/connect-wallet",
     tag = AUTH_TAG,
+    request_body = ConnectWallet,
     responses(
-        (status = 200, description = "Returns auth token", body = [AuthResponse])
+        (status = 200, description = "Returns auth token", body = AuthResponse)
     )
 )]
 pub async fn connect_wallet(
     State(state): InternalState,
     Json(req): Json<ConnectWallet>,
-) -> Response {
-    let mut conn = match get_db_connection(&state.pool).await {
-        Ok(conn) => conn,
-        Err(_) => {
-            return response_429_with_unhandled_err(anyhow::anyhow!(
-                "Failed to get database connection"
-            ));
-        }
-    };
+) -> ApiResult<Json<AuthResponse>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    let address = verify_sign_in(&state, &req, &mut conn).await?;
+    let db_user = get_or_create_connected_user(&state, &address, &mut conn).await?;
+    auth_response(&state, db_user.id)
+}
 
-    let maybe_existing_user =
-        match User::get_by_connected_address(req.address.clone(), &mut conn).await {
-            Ok(maybe_existing_user) => maybe_existing_user,
-            Err(_) => {
-                return response_429_with_unhandled_err(anyhow::anyhow!(
-                    "Failed to execute get user query"
-                ));
-            }
-        };
-
-    let db_user = if let Some(existing_user) = maybe_existing_user {
-        existing_user
-    } else {
-        // let public_key_bytes = req.public_key.as_bytes();
-        // let signature_bytes = req.signature.as_bytes();
-        let public_key = match Ed25519PublicKey::from_encoded_string(&req.public_key) {
-            Ok(public_key) => public_key,
-            Err(_) => {
-                return response_429_with_unhandled_err(anyhow::anyhow!(
-                    "Failed to get public key from bytes"
-                ));
-            }
-        };
-
-        let signature = match Ed25519Signature::from_encoded_string(&req.signature) {
-            Ok(signature) => signature,
-            Err(_) => {
-                return response_429_with_unhandled_err(anyhow::anyhow!(
-                    "Failed to get signature from bytes"
-                ));
-            }
-        };
-        if let Err(_) = signature.verify_arbitrary_msg(req.message.as_bytes(), &public_key) {
-            return response_429_with_unhandled_err(anyhow::anyhow!(
-                "Signature verification failed"
-            ));
-        };
-        let wallet_name: String = format!("apt-{}", &req.address);
-        let (wallet_id, address, public_key) = match state
-            .aptos_client
-            .create_new_wallet_on_turnkey(&wallet_name)
-            .await
-        {
-            Ok((wallet_id, address, public_key)) => (wallet_id, address, public_key),
-            Err(_) => {
-                return response_429_with_unhandled_err(anyhow::anyhow!("Failed to create wallet"));
-            }
-        };
-        let new_user = User {
-            id: Uuid::new_v4(),
-            address: standardize_address(&address),
-            connected_wallet: Some(req.address.clone()),
-            public_key: public_key,
-            slippage: 20,
-            tg_id: None,
-            tg_username: None,
-            wallet_id,
-            degen_mode: false,
-        };
-        let query = diesel::insert_into(users::table)
-            .values(new_user.clone())
-            .on_conflict(users::connected_wallet)
-            .do_nothing();
-        match execute_with_better_error(&mut conn, vec![query]).await {
-            Ok(_) => {}
-            Err(_) => {
-                return response_429_with_unhandled_err(anyhow::anyhow!("Failed to execute query"));
-            }
-        }
-        new_user
-    };
+#[utoipa::path(
+    post,
+    path = "/auth/tg-verify",
+    tag = AUTH_TAG,
+    request_body = TgVerify,
+    responses(
+        (status = 200, description = "Returns auth token", body = AuthResponse)
+    )
+)]
+pub async fn tg_verify(
+    State(state): InternalState,
+    Json(req): Json<TgVerify>,
+) -> ApiResult<Json<AuthResponse>> {
+    let tg_id = verify_init_data(&req.init_data, &state.config.bot_config.token)
+        .map_err(|err| ApiError::unauthorized(format!("Invalid init data: {}", err)))?;
+    let mut conn = get_db_connection(&state.pool).await?;
+
+    match User::get_by_telegram_id(tg_id, &mut conn).await? {
+        Some(db_user) => auth_response(&state, db_user.id),
+        None => Err(ApiError::unauthorized(
+            "Wallet not created. Type /start in the bot",
+        )),
+    }
+}
+
+fn auth_response(state: &HttpServer, user_id: Uuid) -> ApiResult<Json<AuthResponse>> {
+    let token = issue_token(state, user_id)?;
+    Ok(Json(AuthResponse { token }))
+}
+
+/// JWT of the user, valid for `jwt_config.expires_in`
+pub fn issue_token(state: &HttpServer, user_id: Uuid) -> jsonwebtoken::errors::Result<String> {
     let (value_str, unit) = state
         .config
         .jwt_config
@@ -156,19 +133,91 @@ pub async fn connect_wallet(
     };
 
     let claims = Claims {
-        id: db_user.id.to_string(),
+        id: user_id.to_string(),
         exp: (Utc::now().timestamp() + duration.num_seconds()) as usize,
         iat: Utc::now().timestamp() as usize,
     };
 
-    let token_result = jsonwebtoken::encode(
+    jsonwebtoken::encode(
         &Header::default(),
         &claims,
         &EncodingKey::from_secret(state.config.jwt_config.secret.as_ref()),
-    );
+    )
+}
+
+/// Fails unless `req.message` is a valid Sign-In-With-Aptos message for this server and
+/// `req.address`, signed by `req.public_key` which derives `req.address`. The nonce of the
+/// message is consumed, so a signature is accepted once. Returns the address in its standard
+/// form, which connected wallets are stored and looked up by.
+pub async fn verify_sign_in(
+    state: &HttpServer,
+    req: &ConnectWallet,
+    conn: &mut DbPoolConnection<'_>,
+) -> ApiResult<String> {
+    let message = SignInMessage::parse(&req.message)
+        .map_err(|err| ApiError::bad_request(format!("Invalid sign-in message: {}", err)))?;
+    let domain = sign_in_domain(&state.config.terminal_url)?;
+    message
+        .validate(&domain, &req.address)
+        .map_err(ApiError::unauthorized)?;
+
+    let public_key = Ed25519PublicKey::from_encoded_string(&req.public_key)
+        .map_err(|_| ApiError::bad_request("Failed to get public key from bytes"))?;
+    if account_address(&public_key) != message.address {
+        return Err(ApiError::unauthorized(
+            "Public key does not belong to the address",
+        ));
+    }
+    let signature = Ed25519Signature::from_encoded_string(&req.signature)
+        .map_err(|_| ApiError::bad_request("Failed to get signature from bytes"))?;
+    if signature
+        .verify_arbitrary_msg(req.message.as_bytes(), &public_key)
+        .is_err()
+    {
+        return Err(ApiError::unauthorized("Signature verification failed"));
+    };
+
+    if !NewAuthNonce::consume(&message.nonce, conn).await? {
+        return Err(ApiError::unauthorized("Nonce is unknown, used or expired"));
+    }
+    Ok(message.address.to_standard_string())
+}
+
+/// User of the connected wallet at `address`, as returned by `verify_sign_in`, created with
+/// its own bot wallet on the first connect
+pub async fn get_or_create_connected_user(
+    state: &HttpServer,
+    address: &str,
+    conn: &mut DbPoolConnection<'_>,
+) -> ApiResult<User> {
+    if let Some(existing_user) = User::get_by_connected_address(address.to_string(), conn).await? {
+        return Ok(existing_user);
+    }
 
-    return match token_result {
-        Ok(token) => Json(AuthResponse { token }).into_response(),
-        Err(_) => response_400_with_const(),
+    let wallet_name: String = format!("apt-{}", address);
+    let (wallet_id, wallet_address, public_key) = state
+        .aptos_client
+        .create_new_wallet(&wallet_name)
+        .await
+        .map_err(ApiError::Upstream)?;
+    let new_user = User {
+        id: Uuid::new_v4(),
+        address: standardize_address(&wallet_address),
+        connected_wallet: Some(address.to_string()),
+        public_key: public_key,
+        slippage: 20,
+        tg_id: None,
+        tg_username: None,
+        wallet_id,
+        degen_mode: false,
+        custody_mode: CUSTODY_CUSTODIAL.to_string(),
+        pin_hash: None,
+        merged_into: None,
     };
+    let query = diesel::insert_into(users::table)
+        .values(new_user.clone())
+        .on_conflict(users::connected_wallet)
+        .do_nothing();
+    execute_with_better_error(conn, vec![query]).await?;
+    Ok(new_user)
 }
@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+use chrono::Utc;
+use reqwest::Client;
+
+use crate::{
+    cache::ICache,
+    http_server::{
+        controllers::InternalState,
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::api::requests::chart::ChartQuery,
+    utils::{
+        chart::{
+            DEFAULT_RANGE_MILLIS, INTERVALS, MAX_CANDLES, MAX_RANGE_MILLIS, parse_duration_millis,
+            render_candlestick_png,
+        },
+        decibel_api::fetch_candlesticks,
+    },
+};
+
+pub const CHART_TAG: &str = "chart";
+const DEFAULT_INTERVAL: &str = "1h";
+/// Link previews fetch the same chart many times, the last candle moves slowly enough
+const CHART_MAX_AGE_SECS: u64 = 60;
+
+#[utoipa::path(
+    get,
+    path = "/charts/{file}",
+    tag = CHART_TAG,
+    params(
+        ("file" = String, Path, description = "Market name or base asset followed by `.png`, such as `BTC.png`"),
+        ChartQuery
+    ),
+    responses(
+        (status = 200, description = "Returns the candlestick chart of the market as a PNG", content_type = "image/png", body = Vec<u8>),
+        (status = 400, description = "Unknown interval, invalid range or too many candles"),
+        (status = 404, description = "Market not found or no candles in the range")
+    )
+)]
+pub async fn get_chart(
+    State(state): InternalState,
+    Path(file): Path<String>,
+    Query(req): Query<ChartQuery>,
+) -> ApiResult<Response> {
+    let Some(name) = file.strip_suffix(".png") else {
+        return Err(ApiError::not_found("Chart not found"));
+    };
+    let interval = req.interval.unwrap_or(DEFAULT_INTERVAL.to_string());
+    if !INTERVALS.contains(&interval.as_str()) {
+        return Err(ApiError::bad_request(format!(
+            "Unknown interval {}, use one of {}",
+            interval,
+            INTERVALS.join(", ")
+        )));
+    }
+    let range = match req.range.as_deref() {
+        Some(range) => match parse_duration_millis(range) {
+            Some(range) if range <= MAX_RANGE_MILLIS => range,
+            _ => {
+                return Err(ApiError::bad_request(
+                    "Range must be like 24h or 7d, at most 90d",
+                ));
+            }
+        },
+        None => DEFAULT_RANGE_MILLIS,
+    };
+    let interval_millis = parse_duration_millis(&interval).unwrap_or(1);
+    if range / interval_millis > MAX_CANDLES {
+        return Err(ApiError::bad_request(format!(
+            "At most {} candles fit a chart, use a shorter range or a wider interval",
+            MAX_CANDLES
+        )));
+    }
+
+    let market = match state.cache.get_market(name).await {
+        Some(market) => Some(market),
+        None => state.cache.get_markets_ilike(name).await.into_iter().next(),
+    };
+    let market = market.ok_or_else(|| ApiError::not_found("Market not found"))?;
+
+    let end = Utc::now().timestamp_millis();
+    let candles = fetch_candlesticks(
+        &Client::new(),
+        &state.config.network().decibel_url,
+        &market.market_addr,
+        &interval,
+        end - range,
+        end,
+    )
+    .await
+    .map_err(ApiError::Upstream)?;
+    if candles.is_empty() {
+        return Err(ApiError::not_found("No candles in the range"));
+    }
+
+    let png = tokio::task::spawn_blocking(move || {
+        render_candlestick_png(&market.market_name, &interval, &candles)
+    })
+    .await??;
+
+    let cache_control = format!("public, max-age={}", CHART_MAX_AGE_SECS);
+    Ok((
+        [
+            (header::CONTENT_TYPE, HeaderValue::from_static("image/png")),
+            (
+                header::CACHE_CONTROL,
+                HeaderValue::from_str(&cache_control)
+                    .unwrap_or(HeaderValue::from_static("no-cache")),
+            ),
+        ],
+        png,
+    )
+        .into_response())
+}
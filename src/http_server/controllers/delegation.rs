@@ -0,0 +1,192 @@
+use std::str::FromStr;
+
+use aptos_crypto::HashValue;
+use aptos_sdk::types::{account_address::AccountAddress, transaction::TransactionPayload};
+use axum::{Json, extract::State};
+use diesel::{ExpressionMethods, QueryDsl};
+
+use crate::{
+    http_server::{
+        HttpServer,
+        controllers::{
+            InternalState,
+            auth::{AUTH_TAG, get_or_create_connected_user, verify_sign_in},
+        },
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::{
+        api::{
+            requests::delegation::DelegationRequest,
+            responses::delegation::{DelegationResponse, EntryFunctionCall},
+        },
+        db::{
+            trading_delegations::{NewTradingDelegation, TradingDelegation},
+            users::{CUSTODY_DELEGATED, User},
+        },
+    },
+    schema::{trading_delegations, users},
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::DbPoolConnection,
+        db_execution::execute_with_better_error,
+        decibel_transaction::{
+            delegate_trading_to, revoke_delegation as revoke_delegation_payload,
+        },
+    },
+};
+
+#[utoipa::path(
+    post,
+    path = "/auth/delegate",
+    tag = AUTH_TAG,
+    request_body = DelegationRequest,
+    responses(
+        (status = 200, description = "Returns the `delegate_trading_to` call for the connected wallet to sign, then activates the delegation once its `txn_hash` committed", body = DelegationResponse)
+    )
+)]
+pub async fn delegate_trading(
+    State(state): InternalState,
+    Json(req): Json<DelegationRequest>,
+) -> ApiResult<Json<DelegationResponse>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    let owner_address = verify_sign_in(&state, &req.wallet, &mut conn).await?;
+    let db_user = get_or_create_connected_user(&state, &owner_address, &mut conn).await?;
+
+    if let Some(delegation) = TradingDelegation::get_active(db_user.id, &mut conn).await? {
+        return Ok(Json(DelegationResponse {
+            delegate_address: delegation.delegate_address,
+            active: true,
+            pending_function: None,
+        }));
+    }
+
+    let contract_address = &state.config.network().contract_address;
+    let Some(txn_hash) = req.txn_hash else {
+        return Ok(Json(DelegationResponse {
+            pending_function: Some(EntryFunctionCall {
+                function: format!("{}::dex_accounts::delegate_trading_to", contract_address),
+                arguments: vec![db_user.address.clone()],
+            }),
+            delegate_address: db_user.address,
+            active: false,
+        }));
+    };
+
+    let expected = delegate_trading_to(contract_address, &db_user.address)?;
+    let txn_hash =
+        verify_owner_txn(&state, &owner_address, &txn_hash, &expected, &mut conn).await?;
+    let delegation = NewTradingDelegation {
+        user_id: db_user.id,
+        owner_address,
+        delegate_address: db_user.address.clone(),
+        txn_hash: Some(txn_hash),
+    };
+    let insert_query = diesel::insert_into(trading_delegations::table)
+        .values(delegation)
+        .on_conflict_do_nothing();
+    let update_query = diesel::update(users::table.find(db_user.id))
+        .set(users::custody_mode.eq(CUSTODY_DELEGATED));
+    execute_with_better_error(&mut conn, vec![insert_query]).await?;
+    execute_with_better_error(&mut conn, vec![update_query]).await?;
+
+    Ok(Json(DelegationResponse {
+        delegate_address: db_user.address,
+        active: true,
+        pending_function: None,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/revoke-delegation",
+    tag = AUTH_TAG,
+    request_body = DelegationRequest,
+    responses(
+        (status = 200, description = "Returns the `revoke_delegation` call for the connected wallet to sign, then stops trading for it once its `txn_hash` committed", body = DelegationResponse)
+    )
+)]
+pub async fn revoke_delegation(
+    State(state): InternalState,
+    Json(req): Json<DelegationRequest>,
+) -> ApiResult<Json<DelegationResponse>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    let owner_address = verify_sign_in(&state, &req.wallet, &mut conn).await?;
+    let db_user = User::get_by_connected_address(owner_address, &mut conn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("Wallet is not connected"))?;
+    let Some(delegation) = TradingDelegation::get_active(db_user.id, &mut conn).await? else {
+        return Ok(Json(DelegationResponse {
+            delegate_address: db_user.address,
+            active: false,
+            pending_function: None,
+        }));
+    };
+
+    let contract_address = &state.config.network().contract_address;
+    let Some(txn_hash) = req.txn_hash else {
+        return Ok(Json(DelegationResponse {
+            pending_function: Some(EntryFunctionCall {
+                function: format!("{}::dex_accounts::revoke_delegation", contract_address),
+                arguments: vec![delegation.delegate_address.clone()],
+            }),
+            delegate_address: delegation.delegate_address,
+            active: true,
+        }));
+    };
+
+    let expected = revoke_delegation_payload(contract_address, &delegation.delegate_address)?;
+    let txn_hash = verify_owner_txn(
+        &state,
+        &delegation.owner_address,
+        &txn_hash,
+        &expected,
+        &mut conn,
+    )
+    .await?;
+    // The user stays in delegated mode, so the bot wallet never trades for them again until
+    // they delegate anew
+    let query = diesel::update(trading_delegations::table.find(delegation.id)).set((
+        trading_delegations::revoked_at.eq(chrono::Utc::now().naive_utc()),
+        trading_delegations::revoke_txn_hash.eq(Some(txn_hash)),
+    ));
+    execute_with_better_error(&mut conn, vec![query]).await?;
+
+    Ok(Json(DelegationResponse {
+        delegate_address: delegation.delegate_address,
+        active: false,
+        pending_function: None,
+    }))
+}
+
+/// Checks `txn_hash` committed a call of `expected` sent by `owner_address` and was not used
+/// before, returning the normalized hash
+async fn verify_owner_txn(
+    state: &HttpServer,
+    owner_address: &str,
+    txn_hash: &str,
+    expected: &TransactionPayload,
+    conn: &mut DbPoolConnection<'_>,
+) -> ApiResult<String> {
+    let hash = HashValue::from_str(txn_hash.trim_start_matches("0x"))
+        .map_err(|_| ApiError::bad_request("Invalid txn hash"))?;
+    let txn_hash = format!("0x{}", hash.to_hex());
+    if TradingDelegation::is_txn_used(&txn_hash, conn).await? {
+        return Err(ApiError::bad_request("Txn was already used"));
+    }
+    let owner = AccountAddress::from_str(owner_address)?;
+
+    let txn = state
+        .aptos_client
+        .committed_transaction(&txn_hash)
+        .await
+        .map_err(ApiError::Upstream)?
+        .ok_or_else(|| {
+            ApiError::bad_request("Txn has not committed successfully yet, retry once it has")
+        })?;
+    if txn.sender() != owner || txn.payload() != expected {
+        return Err(ApiError::bad_request(
+            "Txn is not the requested call from the connected wallet",
+        ));
+    }
+    Ok(txn_hash)
+}
@@ -1,7 +1,149 @@
-use axum::http::{Response, StatusCode};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use axum::{
+    Json,
+    extract::State,
+    http::{Response, StatusCode},
+    response::IntoResponse,
+};
+use reqwest::Client;
+
+use crate::{
+    cache::ICache,
+    http_server::{HttpServer, controllers::InternalState},
+    models::api::responses::readiness::{ReadinessCheck, ReadinessResponse},
+    utils::{
+        database_connection::get_db_connection, decibel_api,
+        starting_version::get_latest_version_from_db,
+    },
+};
+
+/// Each check fails when it takes longer than this
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
 pub async fn check() -> Response<String> {
     Response::builder()
         .status(StatusCode::OK)
         .body("OK".into())
         .unwrap()
 }
+
+/// Whether the server can serve traffic: the DB, node and Decibel API are reachable, the cache
+/// is fresh and the indexer keeps up with the chain. Answers 503 when any check fails.
+pub async fn ready(State(state): InternalState) -> impl IntoResponse {
+    let (database, (node, indexer), decibel) = tokio::join!(
+        run_check("database", async {
+            get_db_connection(&state.pool).await?;
+            Ok(None)
+        }),
+        check_node_and_indexer(&state),
+        run_check("decibel", async {
+            decibel_api::ping(&Client::new(), &state.config.network().decibel_url).await?;
+            Ok(None)
+        }),
+    );
+    let cache = run_check("cache", check_cache(&state)).await;
+
+    let checks = vec![database, node, decibel, cache, indexer];
+    let ready = checks.iter().all(|check| check.ok);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(ReadinessResponse { ready, checks }))
+}
+
+/// The node check, and the indexer lag against the chain head the node reported
+async fn check_node_and_indexer(state: &HttpServer) -> (ReadinessCheck, ReadinessCheck) {
+    let started = Instant::now();
+    let ledger_info = tokio::time::timeout(CHECK_TIMEOUT, state.aptos_client.ledger_info())
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("Timed out")));
+    let expected_chain_id = state.config.network().chain_id;
+    let node = check_result(
+        "node",
+        started,
+        match &ledger_info {
+            Ok(ledger_info) if ledger_info.chain_id != expected_chain_id => Err(anyhow::anyhow!(
+                "Chain id {} does not match the expected {}",
+                ledger_info.chain_id,
+                expected_chain_id
+            )),
+            Ok(ledger_info) => Ok(Some(format!(
+                "Ledger version {}",
+                ledger_info.ledger_version
+            ))),
+            Err(err) => Err(anyhow::anyhow!("{err:#}")),
+        },
+    );
+
+    let indexer = run_check("indexer", async {
+        let ledger_info = ledger_info.context("Failed to get the chain head")?;
+        let Some(last_success_version) =
+            get_latest_version_from_db(&state.config.stream_config, Arc::clone(&state.pool))
+                .await?
+        else {
+            return Ok(Some("No version processed yet".to_string()));
+        };
+        let lag = (ledger_info.ledger_version as i64 - last_success_version).max(0);
+        let max_lag = state.config.stream_config.max_lag_versions;
+        if lag > max_lag {
+            anyhow::bail!("Lagging {} versions behind, more than {}", lag, max_lag);
+        }
+        Ok(Some(format!("Lagging {} versions behind", lag)))
+    })
+    .await;
+    (node, indexer)
+}
+
+async fn check_cache(state: &HttpServer) -> anyhow::Result<Option<String>> {
+    if state.cache.is_healthy() {
+        return Ok(None);
+    }
+    let ages = state
+        .cache
+        .dataset_ages()
+        .into_iter()
+        .map(|(dataset, age)| match age {
+            Some(age) => format!("{} refreshed {}s ago", dataset, age.as_secs()),
+            None => format!("{} never loaded", dataset),
+        })
+        .collect::<Vec<_>>();
+    anyhow::bail!("Stale: {}", ages.join(", "))
+}
+
+async fn run_check(
+    name: &str,
+    check: impl Future<Output = anyhow::Result<Option<String>>>,
+) -> ReadinessCheck {
+    let started = Instant::now();
+    let result = tokio::time::timeout(CHECK_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| Err(anyhow::anyhow!("Timed out")));
+    check_result(name, started, result)
+}
+
+fn check_result(
+    name: &str,
+    started: Instant,
+    result: anyhow::Result<Option<String>>,
+) -> ReadinessCheck {
+    let (ok, detail) = match result {
+        Ok(detail) => (true, detail),
+        Err(err) => {
+            tracing::warn!("Readiness check {} failed: {:#}", name, err);
+            (false, Some(format!("{:#}", err)))
+        }
+    };
+    ReadinessCheck {
+        name: name.to_string(),
+        ok,
+        detail,
+        latency_ms: started.elapsed().as_millis() as u64,
+    }
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{Path, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    http_server::{
+        controllers::InternalState,
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::db::{
+        key_exports::{KeyExport, NewKeyExportLink, OUTCOME_LINK_OPENED},
+        users::User,
+    },
+    utils::{
+        database_connection::get_db_connection,
+        key_export::{KeyDelivery, hash_link_token},
+    },
+};
+
+pub const KEY_EXPORT_TAG: &str = "key-export";
+
+/// Link previews and scanners only GET the link, so it must not give the key away yet
+const CONFIRM_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><meta name="robots" content="noindex"><title>Export private key</title></head>
+<body>
+<p>Anyone with your private key controls your wallet. Only continue on your own device.</p>
+<form method="post"><button type="submit">Show my private key</button></form>
+</body>
+</html>"#;
+
+#[utoipa::path(
+    get,
+    path = "/key-export/{token}",
+    tag = KEY_EXPORT_TAG,
+    params(
+        ("token" = String, Path, description = "One-time token sent by the bot")
+    ),
+    responses(
+        (status = 200, description = "Returns a page asking to confirm the export, the link stays unused", body = String)
+    )
+)]
+pub async fn confirm_page(Path(_token): Path<String>) -> Response {
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/html; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-store"),
+            (header::REFERRER_POLICY, "no-referrer"),
+        ],
+        CONFIRM_PAGE,
+    )
+        .into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/key-export/{token}",
+    tag = KEY_EXPORT_TAG,
+    params(
+        ("token" = String, Path, description = "One-time token sent by the bot")
+    ),
+    responses(
+        (status = 200, description = "Uses up the link and returns the private key as plain text", body = String),
+        (status = 404, description = "Link is unknown, used or expired")
+    )
+)]
+pub async fn open_link(
+    State(state): InternalState,
+    Path(token): Path<String>,
+) -> ApiResult<Response> {
+    let mut conn = get_db_connection(&state.pool).await?;
+
+    let Some(user_id) = NewKeyExportLink::redeem(&hash_link_token(&token), &mut conn).await? else {
+        // opened in a browser, so plain text rather than JSON
+        return Ok((StatusCode::NOT_FOUND, "Link expired or already used").into_response());
+    };
+    let db_user = User::get_by_id(user_id, &mut conn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("User not found"))?;
+    let private_key = state
+        .aptos_client
+        .export_private_key(&db_user.address)
+        .await
+        .map_err(ApiError::Upstream)?;
+    if let Err(err) = KeyExport::record(
+        db_user.id,
+        &KeyDelivery::Link.to_string(),
+        OUTCOME_LINK_OPENED,
+        &mut conn,
+    )
+    .await
+    {
+        tracing::error!("Failed to record key export of {}: {:?}", db_user.id, err);
+    }
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/plain; charset=utf-8"),
+            (header::CACHE_CONTROL, "no-store"),
+            (header::REFERRER_POLICY, "no-referrer"),
+        ],
+        private_key,
+    )
+        .into_response())
+}
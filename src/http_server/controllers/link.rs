@@ -0,0 +1,102 @@
+use axum::{Extension, Json, extract::State};
+
+use crate::{
+    http_server::{
+        HttpServer,
+        controllers::{
+            InternalState,
+            auth::{AUTH_TAG, issue_token, verify_sign_in},
+            me::USER_TAG,
+        },
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::{
+        api::{
+            requests::link_telegram::{LinkTelegram, LinkTelegramWithWallet},
+            responses::link::LinkResponse,
+        },
+        db::{link_codes::NewLinkCode, users::User},
+    },
+    utils::{
+        account_link::{hash_link_code, link_wallet},
+        database_connection::get_db_connection,
+        database_utils::DbPoolConnection,
+    },
+};
+
+#[utoipa::path(
+    post,
+    path = "/me/link-telegram",
+    tag = USER_TAG,
+    request_body = LinkTelegram,
+    responses(
+        (status = 200, description = "Returns a token of the linked user", body = LinkResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn link_telegram(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    Json(req): Json<LinkTelegram>,
+) -> ApiResult<Json<LinkResponse>> {
+    let Some(wallet_address) = db_user.connected_wallet.clone() else {
+        return Err(ApiError::bad_request("Connect a wallet first"));
+    };
+    let mut conn = get_db_connection(&state.pool).await?;
+    redeem(&state, &req.code, &wallet_address, Some(db_user), &mut conn).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/auth/link-telegram",
+    tag = AUTH_TAG,
+    request_body = LinkTelegramWithWallet,
+    responses(
+        (status = 200, description = "Returns a token of the linked user", body = LinkResponse)
+    )
+)]
+pub async fn link_telegram_with_wallet(
+    State(state): InternalState,
+    Json(req): Json<LinkTelegramWithWallet>,
+) -> ApiResult<Json<LinkResponse>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    let wallet_address = verify_sign_in(&state, &req.wallet, &mut conn).await?;
+    let wallet_user = User::get_by_connected_address(wallet_address.clone(), &mut conn).await?;
+    redeem(&state, &req.code, &wallet_address, wallet_user, &mut conn).await
+}
+
+async fn redeem(
+    state: &HttpServer,
+    code: &str,
+    wallet_address: &str,
+    wallet_user: Option<User>,
+    conn: &mut DbPoolConnection<'_>,
+) -> ApiResult<Json<LinkResponse>> {
+    let code_hash = hash_link_code(code);
+    let user_id = NewLinkCode::get_user_id(&code_hash, conn)
+        .await?
+        .ok_or_else(|| ApiError::bad_request("Code is unknown, used or expired"))?;
+    let tg_user = User::get_by_id(user_id, conn)
+        .await?
+        .ok_or_else(|| ApiError::not_found("User of the code not found"))?;
+
+    // the code is only used up together with the link itself
+    let linked = link_wallet(
+        &state.trading,
+        &code_hash,
+        tg_user,
+        wallet_address,
+        wallet_user,
+        conn,
+    )
+    .await
+    .map_err(ApiError::bad_request)?;
+    let token = issue_token(state, linked.user.id)?;
+    Ok(Json(LinkResponse {
+        token,
+        user_id: linked.user.id,
+        redelegate: linked.redelegate,
+    }))
+}
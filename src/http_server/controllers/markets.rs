@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::Response,
+};
+
+use crate::{
+    cache::{AssetContext, ICache, Market},
+    http_server::{
+        controllers::InternalState,
+        utils::{
+            api_error::{ApiError, ApiResult},
+            cached_json::cached_json,
+        },
+    },
+};
+
+pub const MARKET_TAG: &str = "market";
+/// Markets change rarely, the indexer refreshes them every few minutes
+const MARKETS_MAX_AGE_SECS: u64 = 60;
+/// Prices move on every indexer refresh
+const ASSET_CONTEXTS_MAX_AGE_SECS: u64 = 5;
+
+#[utoipa::path(
+    get,
+    path = "/markets",
+    tag = MARKET_TAG,
+    responses(
+        (status = 200, description = "Returns every market", body = Vec<Market>),
+        (status = 304, description = "Markets did not change since the ETag in `If-None-Match`")
+    )
+)]
+pub async fn list_markets(State(state): InternalState, headers: HeaderMap) -> Response {
+    let markets = state.cache.get_markets().await;
+    cached_json(&headers, &markets, MARKETS_MAX_AGE_SECS)
+}
+
+#[utoipa::path(
+    get,
+    path = "/markets/{name}",
+    tag = MARKET_TAG,
+    params(
+        ("name" = String, Path, description = "Market name with its slash URL encoded, such as `BTC%2FUSD`, or its base asset, such as `BTC`")
+    ),
+    responses(
+        (status = 200, description = "Returns the market", body = Market),
+        (status = 304, description = "Market did not change since the ETag in `If-None-Match`"),
+        (status = 404, description = "Market not found")
+    )
+)]
+pub async fn get_market(
+    State(state): InternalState,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> ApiResult<Response> {
+    let market = match state.cache.get_market(&name).await {
+        Some(market) => Some(market),
+        None => state
+            .cache
+            .get_markets_ilike(&name)
+            .await
+            .into_iter()
+            .next(),
+    };
+    match market {
+        Some(market) => Ok(cached_json(&headers, &market, MARKETS_MAX_AGE_SECS)),
+        None => Err(ApiError::not_found("Market not found")),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/asset-contexts",
+    tag = MARKET_TAG,
+    responses(
+        (status = 200, description = "Returns prices, volume and open interest of every market", body = Vec<AssetContext>),
+        (status = 304, description = "Asset contexts did not change since the ETag in `If-None-Match`")
+    )
+)]
+pub async fn list_asset_contexts(State(state): InternalState, headers: HeaderMap) -> Response {
+    let asset_contexts = state.cache.get_asset_contexts().await;
+    cached_json(&headers, &asset_contexts, ASSET_CONTEXTS_MAX_AGE_SECS)
+}
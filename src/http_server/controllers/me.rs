@@ -0,0 +1,91 @@
+use axum::{Extension, Json, extract::State};
+use diesel::QueryDsl;
+
+use crate::{
+    http_server::{
+        controllers::InternalState,
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::{
+        api::{requests::update_settings::UpdateSettings, responses::me::MeResponse},
+        db::{
+            subaccounts::SubAccount,
+            users::{User, UserSettings},
+        },
+    },
+    schema::users,
+    utils::{
+        database_connection::get_db_connection, database_utils::DbPoolConnection,
+        db_execution::execute_with_better_error,
+    },
+};
+
+pub const USER_TAG: &str = "user";
+#[utoipa::path(
+    get,
+    path = "/me",
+    tag = USER_TAG,
+    responses(
+        (status = 200, description = "Returns the authenticated user", body = MeResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn get_me(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+) -> ApiResult<Json<MeResponse>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    me_response(db_user, &mut conn).await
+}
+
+#[utoipa::path(
+    patch,
+    path = "/me/settings",
+    tag = USER_TAG,
+    request_body = UpdateSettings,
+    responses(
+        (status = 200, description = "Returns the updated user", body = MeResponse)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub async fn update_settings(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    Json(req): Json<UpdateSettings>,
+) -> ApiResult<Json<MeResponse>> {
+    if req.slippage.is_none() && req.degen_mode.is_none() {
+        return Err(ApiError::bad_request("Nothing to update"));
+    }
+    if req
+        .slippage
+        .is_some_and(|slippage| slippage <= 0 || slippage >= 100)
+    {
+        return Err(ApiError::bad_request("Slippage must be between 0 to 100"));
+    }
+    let mut conn = get_db_connection(&state.pool).await?;
+
+    let query = diesel::update(users::table.find(db_user.id)).set(UserSettings {
+        slippage: req.slippage,
+        degen_mode: req.degen_mode,
+    });
+    execute_with_better_error(&mut conn, vec![query]).await?;
+
+    let db_user = User {
+        slippage: req.slippage.unwrap_or(db_user.slippage),
+        degen_mode: req.degen_mode.unwrap_or(db_user.degen_mode),
+        ..db_user
+    };
+    me_response(db_user, &mut conn).await
+}
+
+async fn me_response(
+    db_user: User,
+    conn: &mut DbPoolConnection<'_>,
+) -> ApiResult<Json<MeResponse>> {
+    let subaccounts = SubAccount::get_subaccounts_by_user_id(db_user.id, conn).await?;
+    Ok(Json(MeResponse::new(db_user, subaccounts)))
+}
@@ -0,0 +1,27 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::header,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    cache::ICache,
+    http_server::{controllers::InternalState, utils::api_error::ApiResult},
+    utils::metrics,
+};
+
+/// Prometheus scrape endpoint. Gauges of the DB pool, cache and indexer are sampled here.
+pub async fn scrape(State(state): InternalState) -> ApiResult<Response> {
+    metrics::refresh_gauges(
+        &state.config,
+        Arc::clone(&state.pool),
+        state.aptos_client.as_ref(),
+        state.cache.dataset_ages(),
+    )
+    .await;
+
+    let body = metrics::render()?;
+    Ok(([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body).into_response())
+}
@@ -4,7 +4,17 @@ use axum::extract::State;
 
 use crate::http_server::HttpServer;
 
+pub mod api_keys;
 pub mod auth;
+pub mod charts;
+pub mod delegation;
 pub mod health;
+pub mod key_export;
+pub mod link;
+pub mod markets;
+pub mod me;
+pub mod metrics;
+pub mod trading;
+pub mod ws;
 
 type InternalState = State<Arc<HttpServer>>;
@@ -0,0 +1,311 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+};
+use reqwest::Client;
+
+use crate::{
+    cache::ICache,
+    http_server::{
+        controllers::InternalState,
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::{
+        api::{
+            requests::{cancel_order::CancelOrder, place_order::PlaceOrder},
+            responses::trading::{
+                BalancesResponse, OpenOrderResponse, OrderResponse, PositionResponse,
+                SubaccountBalance,
+            },
+        },
+        db::users::User,
+    },
+    utils::{
+        database_connection::get_db_connection,
+        decibel_api::{fetch_account_overview, fetch_open_orders, fetch_positions},
+        decibel_transaction::cancel_order_to_subaccount,
+        orders::OrderParams,
+    },
+};
+
+pub const TRADING_TAG: &str = "trading";
+
+#[utoipa::path(
+    post,
+    path = "/orders",
+    tag = TRADING_TAG,
+    request_body = PlaceOrder,
+    responses(
+        (status = 200, description = "Places a market order, or a limit order when a price is given", body = OrderResponse)
+    ),
+    security(
+        ("BearerAuth" = []),
+        ("ApiKeyAuth" = [])
+    )
+)]
+pub async fn place_order(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    Json(req): Json<PlaceOrder>,
+) -> ApiResult<Json<OrderResponse>> {
+    let Some(market) = state.cache.get_market(&req.market).await else {
+        return Err(ApiError::bad_request("Market not found"));
+    };
+    let Some(asset_context) = state.cache.get_asset_context(&market.market_name).await else {
+        return Err(ApiError::Upstream(anyhow::anyhow!(
+            "Unable to get market data"
+        )));
+    };
+    let order = OrderParams {
+        is_long: req.is_long,
+        leverage: req.leverage,
+        amount: req.amount,
+        limit_price: req.price,
+    };
+    order.validate(&market).map_err(ApiError::bad_request)?;
+
+    let mut conn = get_db_connection(&state.pool).await?;
+    let subaccount = state
+        .trading
+        .subaccount_by_address(&db_user, req.subaccount.as_deref(), &mut conn)
+        .await
+        .map_err(ApiError::bad_request)?;
+    let txns = state
+        .trading
+        .place_order(
+            &db_user,
+            &subaccount,
+            &market,
+            &asset_context,
+            &order,
+            &mut conn,
+        )
+        .await
+        .map_err(ApiError::signing)?;
+
+    let token = state.trading.collateral_token(&mut conn).await?;
+    let summary = order.summary(
+        &market.market_name,
+        order
+            .limit_price
+            .as_ref()
+            .unwrap_or(&asset_context.mark_price),
+        &token.symbol,
+    );
+    let txn_hash = txns.order_txn_hash();
+    let id = state
+        .trading
+        .enqueue_order(&db_user, summary, txns, None, None, &mut conn)
+        .await?;
+    tracing::info!(
+        "{} queued order to subaccount {} from the API",
+        db_user.address,
+        subaccount.address
+    );
+    Ok(Json(OrderResponse {
+        id,
+        txn_hash,
+        subaccount: subaccount.address,
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/orders/{order_id}",
+    tag = TRADING_TAG,
+    params(
+        ("order_id" = String, Path, description = "Exchange order id"),
+        CancelOrder
+    ),
+    responses(
+        (status = 200, description = "Cancels an open order", body = OrderResponse)
+    ),
+    security(
+        ("BearerAuth" = []),
+        ("ApiKeyAuth" = [])
+    )
+)]
+pub async fn cancel_order(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    Path(order_id): Path<String>,
+    Query(req): Query<CancelOrder>,
+) -> ApiResult<Json<OrderResponse>> {
+    let Ok(order_id) = order_id.parse::<u128>() else {
+        return Err(ApiError::bad_request("Invalid order id"));
+    };
+    let Some(market) = state.cache.get_market(&req.market).await else {
+        return Err(ApiError::bad_request("Market not found"));
+    };
+
+    let mut conn = get_db_connection(&state.pool).await?;
+    let subaccount = state
+        .trading
+        .subaccount_by_address(&db_user, req.subaccount.as_deref(), &mut conn)
+        .await
+        .map_err(ApiError::bad_request)?;
+    let payload = cancel_order_to_subaccount(
+        &state.config.network().contract_address,
+        &subaccount.address,
+        order_id,
+        &market.market_addr,
+    )?;
+    let txn = state
+        .trading
+        .sign_txn(&db_user, payload, &mut conn)
+        .await
+        .map_err(ApiError::signing)?;
+
+    let summary = format!(
+        "Order <b>{}</b> on <b>{}</b> cancelled",
+        order_id, market.market_name
+    );
+    let txn_hash = Some(format!("0x{}", txn.committed_hash().to_hex()));
+    let id = state
+        .trading
+        .enqueue_transaction(
+            &db_user,
+            "cancel_order",
+            summary,
+            txn,
+            None,
+            None,
+            &mut conn,
+        )
+        .await?;
+    Ok(Json(OrderResponse {
+        id,
+        txn_hash,
+        subaccount: subaccount.address,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/orders",
+    tag = TRADING_TAG,
+    responses(
+        (status = 200, description = "Returns the open orders of every subaccount", body = Vec<OpenOrderResponse>)
+    ),
+    security(
+        ("BearerAuth" = []),
+        ("ApiKeyAuth" = [])
+    )
+)]
+pub async fn list_open_orders(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+) -> ApiResult<Json<Vec<OpenOrderResponse>>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    let subaccounts = state.trading.subaccounts(&db_user, &mut conn).await?;
+
+    let client = Client::new();
+    let mut orders = vec![];
+    for subaccount in subaccounts {
+        let open_orders = fetch_open_orders(
+            &client,
+            &state.config.network().decibel_url,
+            &subaccount.address,
+        )
+        .await
+        .map_err(ApiError::Upstream)?;
+        orders.extend(
+            open_orders
+                .into_iter()
+                .map(|order| OpenOrderResponse::new(&subaccount.address, order)),
+        );
+    }
+    Ok(Json(orders))
+}
+
+#[utoipa::path(
+    get,
+    path = "/positions",
+    tag = TRADING_TAG,
+    responses(
+        (status = 200, description = "Returns the positions of every subaccount", body = Vec<PositionResponse>)
+    ),
+    security(
+        ("BearerAuth" = []),
+        ("ApiKeyAuth" = [])
+    )
+)]
+pub async fn list_positions(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+) -> ApiResult<Json<Vec<PositionResponse>>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    let subaccounts = state.trading.subaccounts(&db_user, &mut conn).await?;
+
+    let client = Client::new();
+    let mut positions = vec![];
+    for subaccount in subaccounts {
+        let open_positions = fetch_positions(
+            &client,
+            &state.config.network().decibel_url,
+            &subaccount.address,
+        )
+        .await
+        .map_err(ApiError::Upstream)?;
+        positions.extend(
+            open_positions
+                .into_iter()
+                .map(|position| PositionResponse::new(&subaccount.address, position)),
+        );
+    }
+    Ok(Json(positions))
+}
+
+#[utoipa::path(
+    get,
+    path = "/balances",
+    tag = TRADING_TAG,
+    responses(
+        (status = 200, description = "Returns the collateral in the wallet and in every subaccount", body = BalancesResponse)
+    ),
+    security(
+        ("BearerAuth" = []),
+        ("ApiKeyAuth" = [])
+    )
+)]
+pub async fn list_balances(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+) -> ApiResult<Json<BalancesResponse>> {
+    let mut conn = get_db_connection(&state.pool).await?;
+    let token = state.trading.collateral_token(&mut conn).await?;
+    let wallet = db_user.trading_account().to_string();
+    let wallet_balance = state
+        .trading
+        .token_balance(&token, &wallet)
+        .await
+        .map_err(ApiError::Upstream)?;
+    let subaccounts = state.trading.subaccounts(&db_user, &mut conn).await?;
+
+    let client = Client::new();
+    let mut balances = vec![];
+    for subaccount in subaccounts {
+        let overview = fetch_account_overview(
+            &client,
+            &state.config.network().decibel_url,
+            &subaccount.address,
+        )
+        .await
+        .map_err(ApiError::Upstream)?;
+        balances.push(SubaccountBalance {
+            is_primary: subaccount.is_primary(),
+            address: subaccount.address,
+            withdrawable: overview.withdrawable(token.decimals).to_string(),
+            equity: overview.perp_equity_balance.to_string(),
+            unrealized_pnl: overview.unrealized_pnl.to_string(),
+            total_margin: overview.total_margin.to_string(),
+        });
+    }
+
+    Ok(Json(BalancesResponse {
+        wallet,
+        symbol: token.symbol,
+        wallet_balance: wallet_balance.to_string(),
+        subaccounts: balances,
+    }))
+}
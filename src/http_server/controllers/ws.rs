@@ -0,0 +1,172 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use axum::{
+    Extension,
+    extract::{
+        State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket, close_code},
+    },
+    response::Response,
+};
+use serde_json::json;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+use crate::{
+    http_server::{controllers::InternalState, middlewares::authentication::Claims},
+    models::{api::requests::ws_request::WsRequest, db::users::User},
+    utils::{
+        push::{PushEvent, PushHub, TOPIC_MARK_PRICE, TOPICS},
+        shutdown_utils,
+    },
+};
+
+/// Topic with the market it is narrowed to
+type Subscription = (String, Option<String>);
+
+/// Upgrades to a WebSocket that pushes the topics the client subscribes to, until the JWT it
+/// was opened with expires
+pub async fn connect(
+    State(state): InternalState,
+    Extension(db_user): Extension<User>,
+    claims: Option<Extension<Claims>>,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let push_hub = Arc::clone(&state.push_hub);
+    let expires_at = claims.map(|Extension(claims)| claims.exp);
+    ws.on_upgrade(move |socket| serve(socket, push_hub, db_user.id, expires_at))
+}
+
+/// Time left until `exp`, unix seconds, forever without one
+fn until_expiry(exp: Option<usize>) -> Duration {
+    let Some(exp) = exp else {
+        return Duration::MAX;
+    };
+    let now = chrono::Utc::now().timestamp().max(0) as u64;
+    Duration::from_secs((exp as u64).saturating_sub(now))
+}
+
+async fn serve(
+    mut socket: WebSocket,
+    push_hub: Arc<PushHub>,
+    user_id: Uuid,
+    expires_at: Option<usize>,
+) {
+    let cancel_token = shutdown_utils::get_shutdown_token();
+    let expiry = tokio::time::sleep(until_expiry(expires_at));
+    tokio::pin!(expiry);
+    let mut events = push_hub.subscribe();
+    let mut subscriptions = HashSet::<Subscription>::new();
+
+    loop {
+        tokio::select! {
+            _ = cancel_token.cancelled() => {
+                let close = CloseFrame {
+                    code: close_code::AWAY,
+                    reason: "Server shutting down".into(),
+                };
+                let _ = socket.send(Message::Close(Some(close))).await;
+                break;
+            }
+            _ = &mut expiry => {
+                let close = CloseFrame {
+                    code: close_code::POLICY,
+                    reason: "Token expired".into(),
+                };
+                let _ = socket.send(Message::Close(Some(close))).await;
+                break;
+            }
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = handle_request(&text, &mut subscriptions);
+                    if socket.send(Message::Text(reply.to_string().into())).await.is_err() {
+                        break;
+                    }
+                }
+                // pings are answered by axum
+                Some(Ok(Message::Binary(_) | Message::Ping(_) | Message::Pong(_))) => {}
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if event.user_id().is_some_and(|id| id != user_id)
+                        || !is_subscribed(&subscriptions, &event)
+                    {
+                        continue;
+                    }
+                    let message = event.to_message().to_string();
+                    if socket.send(Message::Text(message.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    tracing::warn!("WebSocket client of {} skipped {} events", user_id, skipped);
+                }
+                Err(RecvError::Closed) => break,
+            },
+        }
+    }
+}
+
+fn handle_request(text: &str, subscriptions: &mut HashSet<Subscription>) -> serde_json::Value {
+    let request = match serde_json::from_str::<WsRequest>(text) {
+        Ok(request) => request,
+        Err(err) => return error_message(&format!("Invalid message: {}", err)),
+    };
+    let (topics, subscribe) = match request {
+        WsRequest::Subscribe { topics } => (topics, true),
+        WsRequest::Unsubscribe { topics } => (topics, false),
+    };
+    let parsed = match topics
+        .iter()
+        .map(|topic| parse_topic(topic))
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(parsed) => parsed,
+        Err(msg) => return error_message(&msg),
+    };
+    for subscription in parsed {
+        if subscribe {
+            subscriptions.insert(subscription);
+        } else {
+            subscriptions.remove(&subscription);
+        }
+    }
+
+    let mut topics = subscriptions
+        .iter()
+        .map(|(topic, market)| match market {
+            Some(market) => format!("{}:{}", topic, market),
+            None => topic.clone(),
+        })
+        .collect::<Vec<_>>();
+    topics.sort();
+    json!({ "topic": "subscriptions", "data": { "topics": topics } })
+}
+
+fn parse_topic(topic: &str) -> Result<Subscription, String> {
+    let (name, market) = match topic.split_once(':') {
+        Some((name, market)) => (name, Some(market.to_string())),
+        None => (topic, None),
+    };
+    if !TOPICS.contains(&name) {
+        return Err(format!("Unknown topic {}", name));
+    }
+    if market.is_some() && name != TOPIC_MARK_PRICE {
+        return Err(format!("Topic {} can't be narrowed to a market", name));
+    }
+    Ok((name.to_string(), market))
+}
+
+fn is_subscribed(subscriptions: &HashSet<Subscription>, event: &PushEvent) -> bool {
+    subscriptions.iter().any(|(topic, market)| {
+        topic == event.topic()
+            && market
+                .as_deref()
+                .is_none_or(|market| Some(market) == event.market())
+    })
+}
+
+fn error_message(msg: &str) -> serde_json::Value {
+    json!({ "topic": "error", "data": { "msg": msg } })
+}
@@ -0,0 +1,48 @@
+use std::{num::NonZeroU32, sync::Arc, time::Duration};
+
+use governor::{DefaultDirectRateLimiter, Quota, RateLimiter};
+use moka::future::Cache;
+use uuid::Uuid;
+
+use crate::{http_server::utils::api_error::ApiError, models::db::api_keys::ApiKey};
+
+/// Limiters of keys that made no request for this long are dropped, and rebuilt with the
+/// quota stored on the key on their next request
+const LIMITER_IDLE_SECS: u64 = 600;
+const MAX_LIMITERS: u64 = 10_000;
+
+/// Rate limiter per API key, each with the quota stored on its key. Only charged once the
+/// request signature checked out, so knowing a key id is not enough to use up its quota.
+pub struct ApiKeyRateLimiter {
+    limiters: Cache<Uuid, Arc<DefaultDirectRateLimiter>>,
+}
+
+impl Default for ApiKeyRateLimiter {
+    fn default() -> Self {
+        Self {
+            limiters: Cache::builder()
+                .max_capacity(MAX_LIMITERS)
+                .time_to_idle(Duration::from_secs(LIMITER_IDLE_SECS))
+                .build(),
+        }
+    }
+}
+
+impl ApiKeyRateLimiter {
+    /// Fails once `api_key` used up its quota
+    pub async fn check(&self, api_key: &ApiKey) -> Result<(), ApiError> {
+        let quota = u32::try_from(api_key.rate_limit_per_minute)
+            .ok()
+            .and_then(NonZeroU32::new)
+            .unwrap_or(NonZeroU32::MIN);
+        let limiter = self
+            .limiters
+            .get_with(api_key.id, async {
+                Arc::new(RateLimiter::direct(Quota::per_minute(quota)))
+            })
+            .await;
+        limiter
+            .check()
+            .map_err(|_| ApiError::TooManyRequests("API key rate limit exceeded".to_string()))
+    }
+}
@@ -1,14 +1,32 @@
-// use axum::{extract::Request, http::header, middleware::Next, response::Response};
-// use jsonwebtoken::{decode, DecodingKey, Validation};
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{OriginalUri, Request},
+    http::{HeaderMap, Method, header},
+    middleware::Next,
+    response::Response,
+};
+use jsonwebtoken::{DecodingKey, Validation, decode};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    http_server::{
+        middlewares::api_key_rate_limit::ApiKeyRateLimiter,
+        utils::api_error::{ApiError, ApiResult},
+    },
+    models::db::{api_keys::ApiKey, users::User},
+    utils::{
+        api_keys::{API_KEY_HEADER, API_SIGNATURE_HEADER, API_TIMESTAMP_HEADER, verify_signature},
+        database_connection::get_db_connection,
+        database_utils::{ArcDbPool, DbPoolConnection},
+    },
+};
 
-// use crate::{db::users::User, http_server::utils::err_handler::{response_401_unhandled_err, response_401_with_const, response_401_with_message}, utils::{database_connection::get_db_connection, database_utils::ArcDbPool}};
-// #[derive(Serialize, Deserialize, Clone)]
-// pub struct TelegramClaims {
-//     pub id: String,
-//     pub exp: usize,
-//     pub iat: usize,
-// }
+/// Signed bodies are buffered to check the signature, orders are far smaller than this
+const MAX_SIGNED_BODY_BYTES: usize = 64 * 1024;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -16,31 +34,151 @@ pub struct Claims {
     pub exp: usize,
     pub iat: usize,
 }
-// pub async fn tg_authentication(
-//     mut req: Request,
-//     next: Next,
-//     pool: ArcDbPool,
-//     jwt_secret: String
-// ) -> Result<Response, Response> {
-//     let auth_header = req.headers_mut().get(header::AUTHORIZATION);
-//     let token = auth_header
-//         .and_then(|e| e.to_str().ok())
-//         .filter(|e| e.starts_with("Bearer"))
-//         .ok_or(response_401_with_const())?
-//         .split_whitespace()
-//         .last()
-//         .ok_or(response_401_with_const())?;
-
-//     let token_data = decode::<TelegramClaims>(
-//         token,
-//         &DecodingKey::from_secret(jwt_secret.as_ref()),
-//         &Validation::default()
-//     ).map_err(|e| response_401_unhandled_err(e))?;
-
-//     let mut conn = get_db_connection(&pool).await.ok().ok_or(response_401_with_message("Failed to get db pool"))?;
-//     let telegram_id = token_data.claims.id.parse::<i64>().ok().ok_or(response_401_with_message("Failed to parse telegram id"))?;
-//     let db_user = User::get_by_telegram_id(telegram_id, &mut conn).await.map_err(|_| response_401_with_message("User not found"))?.ok_or(response_401_with_message("User not found"))?;
-
-//     req.extensions_mut().insert(db_user);
-//     Ok(next.run(req).await)
-// }
+
+/// Token of an `Authorization: Bearer <token>` header
+pub fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Token of the `access_token` query param of a WebSocket upgrade, browsers can't set headers
+/// on those
+fn websocket_token(req: &Request) -> Option<String> {
+    let is_upgrade = req
+        .headers()
+        .get(header::UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+    if !is_upgrade {
+        return None;
+    }
+    url::form_urlencoded::parse(req.uri().query()?.as_bytes())
+        .find(|(key, _)| key == "access_token")
+        .map(|(_, token)| token.into_owned())
+        .filter(|token| !token.is_empty())
+}
+
+/// Validates the bearer JWT, or the signature of an API key request, and makes its `User`
+/// available to handlers as `Extension<User>`. Requests with a JWT also get its
+/// `Extension<Claims>`, requests signed with an API key get the key as `Extension<ApiKey>`
+/// and count against its rate limit.
+pub async fn authentication(
+    req: Request,
+    next: Next,
+    pool: ArcDbPool,
+    config: Arc<Config>,
+    api_key_rate_limiter: Arc<ApiKeyRateLimiter>,
+) -> ApiResult<Response> {
+    let mut conn = get_db_connection(&pool).await?;
+    let (mut req, user_id) = if req.headers().contains_key(API_KEY_HEADER) {
+        let (mut req, api_key) = authenticate_api_key(req, &config, &mut conn).await?;
+        api_key_rate_limiter.check(&api_key).await?;
+        let user_id = api_key.user_id;
+        req.extensions_mut().insert(api_key);
+        (req, user_id)
+    } else {
+        let claims = authenticate_jwt(&req, &config.jwt_config.secret)?;
+        let user_id = Uuid::parse_str(&claims.id)
+            .map_err(|_| ApiError::unauthorized("Failed to parse user id"))?;
+        let mut req = req;
+        req.extensions_mut().insert(claims);
+        (req, user_id)
+    };
+
+    let mut db_user = User::get_by_id(user_id, &mut conn)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("User not found"))?;
+    // Tokens issued before a merge keep working for the user it was merged into
+    if let Some(merged_into) = db_user.merged_into {
+        db_user = User::get_by_id(merged_into, &mut conn)
+            .await?
+            .ok_or_else(|| ApiError::unauthorized("User not found"))?;
+    }
+    drop(conn);
+
+    req.extensions_mut().insert(db_user);
+    Ok(next.run(req).await)
+}
+
+fn authenticate_jwt(req: &Request, jwt_secret: &str) -> ApiResult<Claims> {
+    let token = bearer_token(req.headers())
+        .map(str::to_string)
+        .or_else(|| websocket_token(req))
+        .ok_or_else(|| ApiError::unauthorized("Unauthorized"))?;
+
+    let token_data = decode::<Claims>(
+        &token,
+        &DecodingKey::from_secret(jwt_secret.as_ref()),
+        &Validation::default(),
+    )
+    .map_err(ApiError::unauthorized)?;
+    Ok(token_data.claims)
+}
+
+/// Checks the API key headers and signature, and the scope of the key against the method.
+/// The body is read to check the signature, so the request is rebuilt around it.
+async fn authenticate_api_key(
+    req: Request,
+    config: &Config,
+    conn: &mut DbPoolConnection<'_>,
+) -> ApiResult<(Request, ApiKey)> {
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| ApiError::unauthorized(format!("Missing {} header", name)))
+    };
+    let key_id = header(API_KEY_HEADER)?;
+    let timestamp = header(API_TIMESTAMP_HEADER)?;
+    let signature = header(API_SIGNATURE_HEADER)?;
+
+    let signed_at = timestamp
+        .parse::<i64>()
+        .map_err(|_| ApiError::unauthorized("Timestamp must be unix time in milliseconds"))?;
+    let ttl_millis = config.api_key_config.signature_ttl_secs * 1000;
+    if (chrono::Utc::now().timestamp_millis() - signed_at).abs() > ttl_millis {
+        return Err(ApiError::unauthorized(
+            "Timestamp is too far from the server time",
+        ));
+    }
+
+    let api_key = ApiKey::get_by_key_id(&key_id, conn)
+        .await?
+        .ok_or_else(|| ApiError::unauthorized("API key is unknown or revoked"))?;
+    if !api_key.can_trade() && !matches!(*req.method(), Method::GET | Method::HEAD) {
+        return Err(ApiError::forbidden("API key is read-only"));
+    }
+
+    // handlers see the path relative to the nest they are in, the client signed the full one
+    let path_and_query = req
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| &uri.0)
+        .unwrap_or(req.uri())
+        .path_and_query()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+    let method = req.method().as_str().to_string();
+    let (parts, body) = req.into_parts();
+    let body = axum::body::to_bytes(body, MAX_SIGNED_BODY_BYTES)
+        .await
+        .map_err(|_| ApiError::bad_request("Request body is too large"))?;
+    verify_signature(
+        &config.api_key_config.secret,
+        &api_key,
+        &timestamp,
+        &method,
+        &path_and_query,
+        &body,
+        &signature,
+    )
+    .map_err(ApiError::unauthorized)?;
+
+    ApiKey::touch(api_key.id, conn).await?;
+    Ok((Request::from_parts(parts, Body::from(body)), api_key))
+}
@@ -1 +1,3 @@
+pub mod api_key_rate_limit;
 pub mod authentication;
+pub mod http_metrics;
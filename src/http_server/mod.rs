@@ -5,15 +5,28 @@ pub mod utils;
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use crate::{
+    cache::Cache,
     config::Config,
-    http_server::controllers::{auth, health},
-    utils::{aptos_client::AptosClient, database_utils::ArcDbPool, shutdown_utils},
-};
-use axum::{
-    Router,
-    extract::DefaultBodyLimit,
-    routing::{get, post},
+    http_server::{
+        controllers::{
+            api_keys, auth, charts, delegation, health, key_export, link, markets, me, metrics,
+            trading, ws,
+        },
+        middlewares::{
+            api_key_rate_limit::ApiKeyRateLimiter, authentication, http_metrics::http_metrics,
+        },
+        utils::{
+            api_error::{self, add_error_responses},
+            trace::make_request_span,
+        },
+    },
+    models::api::responses::error::ErrorResponse,
+    utils::{
+        chain_client::ChainClient, database_utils::ArcDbPool, push::PushHub, shutdown_utils,
+        trading::Trading,
+    },
 };
+use axum::{Router, body::Body, extract::DefaultBodyLimit, middleware, routing::get};
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
 use tower_governor::{GovernorLayer, governor::GovernorConfigBuilder};
@@ -23,31 +36,71 @@ use tower_http::{
     limit::RequestBodyLimitLayer,
     request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
     timeout::{RequestBodyTimeoutLayer, TimeoutLayer},
-    trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
+    trace::{DefaultOnResponse, TraceLayer},
 };
 use tracing::Level;
-use utoipa::OpenApi;
-use utoipa_axum::router::OpenApiRouter;
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
+use utoipa_axum::{router::OpenApiRouter, routes};
 use utoipa_swagger_ui::SwaggerUi;
 
 #[derive(OpenApi)]
-#[openapi(
-    servers((url = "/api/v1"))
-)]
+#[openapi(components(schemas(ErrorResponse)), modifiers(&SecurityAddon))]
 struct ApiDoc;
 
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "BearerAuth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "ApiKeyAuth",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-Api-Key",
+                "Also send X-Api-Timestamp, unix time in milliseconds, and X-Api-Signature, the \
+                hex HMAC-SHA256 of timestamp + method + path with query + body keyed with the \
+                key secret",
+            ))),
+        );
+    }
+}
+
 pub struct HttpServer {
     pool: ArcDbPool,
     config: Arc<Config>,
-    aptos_client: Arc<AptosClient>,
+    aptos_client: Arc<dyn ChainClient>,
+    cache: Arc<Cache>,
+    trading: Arc<Trading>,
+    push_hub: Arc<PushHub>,
 }
 
 impl HttpServer {
-    pub fn new(config: Arc<Config>, pool: ArcDbPool, aptos_client: Arc<AptosClient>) -> Self {
+    pub fn new(
+        config: Arc<Config>,
+        pool: ArcDbPool,
+        aptos_client: Arc<dyn ChainClient>,
+        cache: Arc<Cache>,
+        trading: Arc<Trading>,
+        push_hub: Arc<PushHub>,
+    ) -> Self {
         Self {
             pool,
             config,
             aptos_client,
+            cache,
+            trading,
+            push_hub,
         }
     }
 
@@ -58,39 +111,41 @@ impl HttpServer {
 
         let listener_address = format!("0.0.0.0:{}", state.config.server_config.port);
         let listener = TcpListener::bind(listener_address).await?;
-
-        axum::serve(
-            listener,
-            state
-                .router()
-                .into_make_service_with_connect_info::<SocketAddr>(),
-        )
-        .with_graceful_shutdown(Self::shutdown_signal())
-        .await
-        .expect("HTTP server crashed");
+        let metrics_address = format!("0.0.0.0:{}", state.config.server_config.metrics_port);
+        let metrics_listener = TcpListener::bind(metrics_address).await?;
+
+        let (api, metrics) = tokio::join!(
+            axum::serve(
+                listener,
+                state
+                    .router()
+                    .into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(Self::shutdown_signal())
+            .into_future(),
+            axum::serve(metrics_listener, state.metrics_router())
+                .with_graceful_shutdown(Self::shutdown_signal())
+                .into_future(),
+        );
+        api.expect("HTTP server crashed");
+        metrics.expect("Metrics server crashed");
 
         tracing::info!("HTTP server completed");
         Ok(())
     }
 
-    fn router(self: &Arc<Self>) -> Router {
+    pub fn router(self: &Arc<Self>) -> Router {
         let api_middleware = ServiceBuilder::new()
             .layer(CompressionLayer::new().quality(CompressionLevel::Fastest))
             .layer(
                 TraceLayer::new_for_http()
-                    .make_span_with(
-                        DefaultMakeSpan::new()
-                            .level(Level::INFO)
-                            .include_headers(true),
-                    )
+                    .make_span_with(make_request_span::<Body>)
                     .on_response(
                         DefaultOnResponse::new()
                             .level(Level::INFO)
                             .include_headers(true),
                     ),
             )
-            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
-            .layer(PropagateRequestIdLayer::x_request_id())
             .layer(RequestBodyTimeoutLayer::new(Duration::from_secs(4)))
             .layer(TimeoutLayer::new(Duration::from_secs(5)));
 
@@ -120,37 +175,77 @@ impl HttpServer {
             }
         });
 
-        // let pool = Arc::clone(&self.pool);
-        // let jwt_secret = self.config.jwt_config.secret.to_string();
-
-        let (router, api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
-            .route("/health", get(health::check))
+        let api_key_rate_limiter = Arc::new(ApiKeyRateLimiter::default());
+
+        let pool = Arc::clone(&self.pool);
+        let config = Arc::clone(&self.config);
+        let authenticated = OpenApiRouter::new()
+            .routes(routes!(me::get_me))
+            .routes(routes!(me::update_settings))
+            .routes(routes!(api_keys::create_key, api_keys::list_keys))
+            .routes(routes!(api_keys::revoke_key))
+            .routes(routes!(link::link_telegram))
+            .routes(routes!(trading::place_order, trading::list_open_orders))
+            .routes(routes!(trading::cancel_order))
+            .routes(routes!(trading::list_positions))
+            .routes(routes!(trading::list_balances))
+            .route("/ws", get(ws::connect))
+            .layer(middleware::from_fn(move |req, next| {
+                authentication::authentication(
+                    req,
+                    next,
+                    Arc::clone(&pool),
+                    Arc::clone(&config),
+                    Arc::clone(&api_key_rate_limiter),
+                )
+            }));
+
+        let (router, mut api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
             .nest(
                 "/api/v1",
                 OpenApiRouter::new()
-                    .nest(
-                        "/auth",
-                        OpenApiRouter::new().route("/connect-wallet", post(auth::connect_wallet)), // Uncomment below when you need Telegram authentication
-                                                                                                   // .route("/tg-verify", get(auth::tg_verify))
-                                                                                                   // .layer(middleware::from_fn(move |req, next| {
-                                                                                                   //     authentication::tg_authentication(
-                                                                                                   //         req,
-                                                                                                   //         next,
-                                                                                                   //         Arc::clone(&pool),
-                                                                                                   //         jwt_secret.clone()
-                                                                                                   //     )
-                                                                                                   // }))
-                    )
+                    .routes(routes!(auth::nonce))
+                    .routes(routes!(auth::connect_wallet))
+                    .routes(routes!(auth::tg_verify))
+                    .routes(routes!(link::link_telegram_with_wallet))
+                    .routes(routes!(delegation::delegate_trading))
+                    .routes(routes!(delegation::revoke_delegation))
+                    .routes(routes!(key_export::confirm_page, key_export::open_link))
+                    .routes(routes!(markets::list_markets))
+                    .routes(routes!(markets::get_market))
+                    .routes(routes!(markets::list_asset_contexts))
+                    .routes(routes!(charts::get_chart))
+                    .merge(authenticated)
                     .layer(api_middleware), // Apply here, at the end of /api/v1 nest
             )
             .layer(DefaultBodyLimit::max(8 * 1024 * 1024))
             .layer(RequestBodyLimitLayer::new(8 * 1024 * 1024))
             .layer(cors)
             .layer(governor)
+            // outermost, so the rate limit errors carry the request id as well
+            .layer(middleware::from_fn(api_error::with_request_id))
+            .layer(PropagateRequestIdLayer::x_request_id())
+            .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
             .with_state(Arc::clone(self))
             .split_for_parts();
 
-        router.merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", api.clone()))
+        add_error_responses(&mut api);
+        // probes come often from few addresses, the rate limits would fail them under load
+        let probes = Router::new()
+            .route("/health", get(health::check))
+            .route("/ready", get(health::ready))
+            .with_state(Arc::clone(self));
+        router
+            .merge(probes)
+            .merge(SwaggerUi::new("/docs").url("/api-docs/openapi.json", api.clone()))
+            .layer(middleware::from_fn(http_metrics))
+    }
+
+    /// Served on its own port, out of reach of the public and of its rate limits
+    pub fn metrics_router(self: &Arc<Self>) -> Router {
+        Router::new()
+            .route("/metrics", get(metrics::scrape))
+            .with_state(Arc::clone(self))
     }
 
     async fn shutdown_signal() {
@@ -0,0 +1,185 @@
+use axum::{
+    Json,
+    body::Body,
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use utoipa::openapi::{ContentBuilder, OpenApi, Ref, RefOr, ResponseBuilder, path::Operation};
+
+use crate::{models::api::responses::error::ErrorResponse, utils::chain_client::TxnRejected};
+
+pub type ApiResult<T> = Result<T, ApiError>;
+
+/// Error of an API handler, rendered as an `ErrorResponse` with the matching status
+#[derive(Debug)]
+pub enum ApiError {
+    /// The request is malformed or breaks a rule, the message tells the client what to fix
+    BadRequest(String),
+    Unauthorized(String),
+    /// Authenticated, but not allowed to do this, such as a read-only API key placing orders
+    Forbidden(String),
+    NotFound(String),
+    /// Well formed, but the txn it asks for fails simulation or is not allowed
+    UnprocessableEntity(String),
+    TooManyRequests(String),
+    /// The Aptos node, Decibel or the signer failed, their message is passed through
+    Upstream(anyhow::Error),
+    /// Database or server fault, the details are logged but not sent to the client
+    Internal(anyhow::Error),
+}
+
+impl ApiError {
+    pub fn bad_request(msg: impl ToString) -> Self {
+        ApiError::BadRequest(msg.to_string())
+    }
+
+    pub fn unauthorized(msg: impl ToString) -> Self {
+        ApiError::Unauthorized(msg.to_string())
+    }
+
+    pub fn forbidden(msg: impl ToString) -> Self {
+        ApiError::Forbidden(msg.to_string())
+    }
+
+    pub fn not_found(msg: impl ToString) -> Self {
+        ApiError::NotFound(msg.to_string())
+    }
+
+    /// A failure to sign a txn, 422 when the txn itself was rejected and 502 otherwise
+    pub fn signing(err: anyhow::Error) -> Self {
+        match err.downcast_ref::<TxnRejected>() {
+            Some(TxnRejected(msg)) => {
+                ApiError::UnprocessableEntity(msg.trim_start_matches("❌ ").to_string())
+            }
+            None => ApiError::Upstream(err),
+        }
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::BadRequest(_) => "ERR_400",
+            ApiError::Unauthorized(_) => "ERR_401",
+            ApiError::Forbidden(_) => "ERR_403",
+            ApiError::NotFound(_) => "ERR_404",
+            ApiError::UnprocessableEntity(_) => "ERR_422",
+            ApiError::TooManyRequests(_) => "ERR_429",
+            ApiError::Upstream(_) => "ERR_502",
+            ApiError::Internal(_) => "ERR_500",
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(msg)
+            | ApiError::Unauthorized(msg)
+            | ApiError::Forbidden(msg)
+            | ApiError::NotFound(msg)
+            | ApiError::UnprocessableEntity(msg)
+            | ApiError::TooManyRequests(msg) => msg.clone(),
+            ApiError::Upstream(err) => err.to_string(),
+            ApiError::Internal(_) => "Internal server error".to_string(),
+        }
+    }
+}
+
+/// Database and other server faults, use `ApiError::Upstream` for failures of the services
+/// the API calls
+impl<E: Into<anyhow::Error>> From<E> for ApiError {
+    fn from(err: E) -> Self {
+        ApiError::Internal(err.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match &self {
+            ApiError::Internal(err) => tracing::error!("API internal error: {:?}", err),
+            ApiError::Upstream(err) => tracing::warn!("API upstream error: {:?}", err),
+            _ => {}
+        }
+        let body = ErrorResponse {
+            code: self.code().to_string(),
+            msg: self.message(),
+            request_id: None,
+        };
+        let mut response = (self.status(), Json(body.clone())).into_response();
+        // `with_request_id` fills in the request id on the way out
+        response.extensions_mut().insert(body);
+        response
+    }
+}
+
+/// Adds the `x-request-id` of the request to the body of `ApiError` responses
+pub async fn with_request_id(req: Request, next: Next) -> Response {
+    let request_id = req
+        .headers()
+        .get("x-request-id")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let mut response = next.run(req).await;
+    let Some(mut body) = response.extensions_mut().remove::<ErrorResponse>() else {
+        return response;
+    };
+    body.request_id = request_id;
+    let Ok(bytes) = serde_json::to_vec(&body) else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Documents the `ErrorResponse` of 4XX and 5XX statuses on every operation of `openapi`
+pub fn add_error_responses(openapi: &mut OpenApi) {
+    let error_response = |description: &str| {
+        ResponseBuilder::new()
+            .description(description)
+            .content(
+                "application/json",
+                ContentBuilder::new()
+                    .schema(Some(Ref::from_schema_name("ErrorResponse")))
+                    .build(),
+            )
+            .build()
+    };
+    let client_error = error_response("Request rejected, see `code` and `msg`");
+    let server_error = error_response("Upstream or server failure");
+
+    for path_item in openapi.paths.paths.values_mut() {
+        let operations = [
+            &mut path_item.get,
+            &mut path_item.put,
+            &mut path_item.post,
+            &mut path_item.delete,
+            &mut path_item.patch,
+        ];
+        for operation in operations.into_iter().flatten() {
+            insert_response(operation, "4XX", &client_error);
+            insert_response(operation, "5XX", &server_error);
+        }
+    }
+}
+
+fn insert_response(operation: &mut Operation, status: &str, response: &utoipa::openapi::Response) {
+    operation
+        .responses
+        .responses
+        .entry(status.to_string())
+        .or_insert_with(|| RefOr::T(response.clone()));
+}
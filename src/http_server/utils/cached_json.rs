@@ -0,0 +1,50 @@
+use aptos_crypto::HashValue;
+use axum::{
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::http_server::utils::api_error::ApiError;
+
+/// JSON response clients may cache for `max_age_secs`, with an ETag of the body so a
+/// revalidation with a matching `If-None-Match` gets an empty 304
+pub fn cached_json<T: Serialize>(headers: &HeaderMap, body: &T, max_age_secs: u64) -> Response {
+    let body = match serde_json::to_vec(body) {
+        Ok(body) => body,
+        Err(err) => return ApiError::Internal(err.into()).into_response(),
+    };
+    let etag = format!("\"{}\"", HashValue::sha3_256_of(&body).to_hex());
+    let cache_control = format!("public, max-age={}", max_age_secs);
+
+    let is_fresh = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.split(',').any(|tag| {
+                let tag = tag.trim();
+                tag == "*" || tag.trim_start_matches("W/") == etag
+            })
+        });
+    let mut response = if is_fresh {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )],
+            body,
+        )
+            .into_response()
+    };
+
+    let response_headers = response.headers_mut();
+    if let Ok(etag) = HeaderValue::from_str(&etag) {
+        response_headers.insert(header::ETAG, etag);
+    }
+    if let Ok(cache_control) = HeaderValue::from_str(&cache_control) {
+        response_headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    response
+}
@@ -1 +1,5 @@
-pub mod err_handler;
+pub mod api_error;
+pub mod cached_json;
+pub mod sign_in;
+pub mod telegram_init_data;
+pub mod trace;
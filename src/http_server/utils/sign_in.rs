@@ -0,0 +1,86 @@
+use std::str::FromStr;
+
+use aptos_sdk::types::account_address::AccountAddress;
+use chrono::{DateTime, Utc};
+
+/// Allowed clock drift between the wallet and the server
+const CLOCK_SKEW_SECS: i64 = 60;
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Aptos account:";
+
+/// Sign-In-With-Aptos message, signed by the wallet as is or wrapped in the wallet's own
+/// `signMessage` envelope:
+///
+/// ```text
+/// <domain> wants you to sign in with your Aptos account:
+/// <address>
+///
+/// Nonce: <nonce>
+/// Issued At: <RFC 3339>
+/// Expiration Time: <RFC 3339>
+/// ```
+#[derive(Debug)]
+pub struct SignInMessage {
+    pub domain: String,
+    pub address: AccountAddress,
+    pub nonce: String,
+    pub issued_at: DateTime<Utc>,
+    pub expiration_time: DateTime<Utc>,
+}
+
+impl SignInMessage {
+    pub fn parse(message: &str) -> anyhow::Result<Self> {
+        let mut lines = message.lines().map(str::trim);
+        let domain = lines
+            .by_ref()
+            .find_map(|line| line.strip_suffix(HEADER_SUFFIX))
+            .ok_or_else(|| anyhow::anyhow!("Sign-in header is missing"))?
+            .to_string();
+        let address = lines
+            .next()
+            .map(AccountAddress::from_str)
+            .transpose()?
+            .ok_or_else(|| anyhow::anyhow!("Sign-in address is missing"))?;
+
+        let rest = lines.collect::<Vec<_>>();
+        let field = |name: &str| {
+            let prefix = format!("{}: ", name);
+            rest.iter()
+                .find_map(|line| line.strip_prefix(prefix.as_str()))
+                .ok_or_else(|| anyhow::anyhow!("Sign-in field `{}` is missing", name))
+        };
+        let timestamp = |name: &str| -> anyhow::Result<DateTime<Utc>> {
+            Ok(DateTime::parse_from_rfc3339(field(name)?)?.with_timezone(&Utc))
+        };
+        Ok(Self {
+            domain,
+            address,
+            nonce: field("Nonce")?.to_string(),
+            issued_at: timestamp("Issued At")?,
+            expiration_time: timestamp("Expiration Time")?,
+        })
+    }
+
+    /// Checks the message was made for this server and `address`, and is still valid
+    pub fn validate(&self, domain: &str, address: &str) -> anyhow::Result<()> {
+        if self.domain != domain {
+            anyhow::bail!("Sign-in message is for another domain");
+        }
+        if self.address != AccountAddress::from_str(address)? {
+            anyhow::bail!("Sign-in message is for another address");
+        }
+        let now = Utc::now();
+        if self.issued_at > now + chrono::Duration::seconds(CLOCK_SKEW_SECS) {
+            anyhow::bail!("Sign-in message is issued in the future");
+        }
+        if self.expiration_time <= now {
+            anyhow::bail!("Sign-in message has expired");
+        }
+        Ok(())
+    }
+}
+
+/// Domain sign-in messages are made for, the authority of the terminal url
+pub fn sign_in_domain(terminal_url: &str) -> anyhow::Result<String> {
+    Ok(url::Url::parse(terminal_url)?.authority().to_string())
+}
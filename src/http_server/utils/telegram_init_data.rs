@@ -0,0 +1,57 @@
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How old `auth_date` may be, Mini Apps get fresh init data on every launch
+const MAX_AGE_SECS: i64 = 3600;
+
+#[derive(Deserialize)]
+struct InitDataUser {
+    id: i64,
+}
+
+/// Checks the `hash` of Telegram Mini App `initData` and returns the Telegram id of its user.
+/// The hash is HMAC-SHA256 of the data-check-string, keyed with HMAC-SHA256 of the bot token
+/// keyed with `WebAppData`.
+pub fn verify_init_data(init_data: &str, bot_token: &str) -> anyhow::Result<i64> {
+    let mut hash = None;
+    let mut fields = Vec::new();
+    for (key, value) in url::form_urlencoded::parse(init_data.as_bytes()) {
+        if key == "hash" {
+            hash = Some(value.into_owned());
+        } else {
+            fields.push((key.into_owned(), value.into_owned()));
+        }
+    }
+    let hash = hex::decode(hash.ok_or_else(|| anyhow::anyhow!("Init data hash is missing"))?)?;
+    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let data_check_string = fields
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let mut secret_key = HmacSha256::new_from_slice(b"WebAppData")?;
+    secret_key.update(bot_token.as_bytes());
+    let secret_key = secret_key.finalize().into_bytes();
+    let mut mac = HmacSha256::new_from_slice(&secret_key)?;
+    mac.update(data_check_string.as_bytes());
+    mac.verify_slice(&hash)
+        .map_err(|_| anyhow::anyhow!("Init data hash does not match"))?;
+
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Init data field `{}` is missing", name))
+    };
+    let auth_date = field("auth_date")?.parse::<i64>()?;
+    if chrono::Utc::now().timestamp() - auth_date > MAX_AGE_SECS {
+        anyhow::bail!("Init data has expired");
+    }
+    let user = serde_json::from_str::<InitDataUser>(field("user")?)?;
+    Ok(user.id)
+}
@@ -0,0 +1,34 @@
+use axum::http::{HeaderMap, HeaderValue, Request};
+use tracing::Span;
+
+use crate::utils::api_keys::API_SIGNATURE_HEADER;
+
+/// Headers carrying credentials, their values are left out of request spans
+const REDACTED_HEADERS: [&str; 4] = [
+    "authorization",
+    "cookie",
+    "sec-websocket-protocol",
+    API_SIGNATURE_HEADER,
+];
+
+/// Span of a request like `DefaultMakeSpan` with headers, without credentials. The query is
+/// left out as well, WebSocket upgrades pass their token in it.
+pub fn make_request_span<B>(req: &Request<B>) -> Span {
+    tracing::info_span!(
+        "request",
+        method = %req.method(),
+        uri = %req.uri().path(),
+        version = ?req.version(),
+        headers = ?redacted_headers(req.headers()),
+    )
+}
+
+fn redacted_headers(headers: &HeaderMap) -> HeaderMap {
+    let mut headers = headers.clone();
+    for name in REDACTED_HEADERS {
+        if let Some(value) = headers.get_mut(name) {
+            *value = HeaderValue::from_static("[redacted]");
+        }
+    }
+    headers
+}
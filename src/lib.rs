@@ -4,6 +4,7 @@ pub mod http_server;
 pub mod models;
 #[path = "db_migrations/schema.rs"]
 pub mod schema;
+pub mod signer;
 pub mod telegram_bot;
 pub mod utils;
 pub mod workers;
@@ -20,15 +21,16 @@ use crate::{
     http_server::HttpServer,
     telegram_bot::TelegramBot,
     utils::{
-        aptos_client::AptosClient, database_connection::new_db_pool, market_indexer::MarketIndexer,
-        shutdown_utils,
+        aptos_client::AptosClient, chain_client::ChainClient, database_connection::new_db_pool,
+        market_indexer::MarketIndexer, push::PushHub, shutdown_utils, trading::Trading,
     },
+    workers::Worker,
 };
 
-pub async fn init() -> anyhow::Result<(HttpServer, TelegramBot<Cache>)> {
+pub async fn init() -> anyhow::Result<(HttpServer, TelegramBot<Cache>, Worker)> {
     let config = Arc::new(init_config().context("Failed to initialize configuration")?);
     let pool = new_db_pool(&config.db_config.url, config.db_config.pool_size).await;
-    let aptos_client = Arc::new(
+    let aptos_client: Arc<dyn ChainClient> = Arc::new(
         AptosClient::new(Arc::clone(&config))
             .await
             .context("Failed to initialize aptos client")?,
@@ -36,22 +38,40 @@ pub async fn init() -> anyhow::Result<(HttpServer, TelegramBot<Cache>)> {
     tokio::spawn(shutdown_utils::poll_for_shutdown_signal());
 
     let cache = Arc::new(Cache::default());
+    let trading = Arc::new(Trading::new(Arc::clone(&config), Arc::clone(&aptos_client)));
+    let push_hub = Arc::new(PushHub::new());
 
-    init_market(&config.decibel_url, Arc::clone(&cache))
-        .await
-        .context("Failed to initialize market")?;
+    let market_indexer = init_market(
+        &config.network().decibel_url,
+        Arc::clone(&cache),
+        Arc::clone(&push_hub),
+    )
+    .await
+    .context("Failed to initialize market")?;
 
     Ok((
         HttpServer::new(
             Arc::clone(&config),
             Arc::clone(&pool),
             Arc::clone(&aptos_client),
+            Arc::clone(&cache),
+            Arc::clone(&trading),
+            Arc::clone(&push_hub),
         ),
         TelegramBot::new(
             Arc::clone(&config),
             Arc::clone(&pool),
             Arc::clone(&aptos_client),
             Arc::clone(&cache),
+            Arc::clone(&trading),
+        ),
+        Worker::new(
+            Arc::clone(&config),
+            Arc::clone(&pool),
+            Arc::clone(&aptos_client),
+            Arc::clone(&trading),
+            Arc::new(market_indexer),
+            Arc::clone(&push_hub),
         ),
     ))
 }
@@ -69,13 +89,18 @@ fn init_config() -> anyhow::Result<Config> {
     Ok(config)
 }
 
-async fn init_market(decibel_url: &str, cache: Arc<Cache>) -> anyhow::Result<()> {
+/// Fills the cache before anything reads it, the returned indexer keeps it fresh
+async fn init_market(
+    decibel_url: &str,
+    cache: Arc<Cache>,
+    push_hub: Arc<PushHub>,
+) -> anyhow::Result<MarketIndexer<Cache>> {
     let client = Client::new();
-    let market_indexer = MarketIndexer::new(decibel_url.to_string(), cache);
+    let market_indexer = MarketIndexer::new(decibel_url.to_string(), cache, push_hub);
 
     market_indexer.fetch_and_store_markets(&client).await?;
     market_indexer
         .fetch_and_store_asset_contexts(&client)
         .await?;
-    Ok(())
+    Ok(market_indexer)
 }
@@ -1,6 +1,14 @@
+use std::sync::Arc;
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let (http_server, telegram_bot) = pace_api::init().await.expect("Failed to initialize server");
+    let (http_server, telegram_bot, worker) =
+        pace_api::init().await.expect("Failed to initialize server");
+    tokio::spawn(async move {
+        if let Err(e) = Arc::new(worker).start().await {
+            tracing::error!("Worker crashed: {:?}", e);
+        }
+    });
     tokio::spawn(async move {
         if let Err(e) = telegram_bot.start().await {
             tracing::error!("Bot crashed: {:?}", e);
@@ -0,0 +1,11 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CancelOrder {
+    /// Market name of the order
+    pub market: String,
+    /// Subaccount address, the primary subaccount when left out
+    pub subaccount: Option<String>,
+}
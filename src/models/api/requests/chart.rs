@@ -0,0 +1,12 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ChartQuery {
+    /// Candle interval such as `1m`, `1h` or `1d`, `1h` when left out
+    pub interval: Option<String>,
+    /// Window ending now such as `24h` or `7d`, `24h` when left out. At most 700 candles of
+    /// the interval may fit in it
+    pub range: Option<String>,
+}
@@ -3,6 +3,7 @@ use utoipa::ToSchema;
 
 #[derive(Deserialize, ToSchema)]
 pub struct ConnectWallet {
+    /// Sign-In-With-Aptos message carrying a nonce from `/auth/nonce`, exactly as signed
     pub message: String,
     pub public_key: String,
     pub signature: String,
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateApiKey {
+    /// Label to tell keys apart, up to 64 characters
+    pub name: String,
+    /// `read` or `trade`
+    pub scope: String,
+}
@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::models::api::requests::connect_wallet::ConnectWallet;
+
+#[derive(Deserialize, ToSchema)]
+pub struct DelegationRequest {
+    #[serde(flatten)]
+    pub wallet: ConnectWallet,
+    /// Hash of the txn the connected wallet submitted with the returned `pending_function`.
+    /// Left out to get the function to sign.
+    pub txn_hash: Option<String>,
+}
@@ -0,0 +1,18 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::models::api::requests::connect_wallet::ConnectWallet;
+
+#[derive(Deserialize, ToSchema)]
+pub struct LinkTelegram {
+    /// One-time code issued by the bot under Settings
+    pub code: String,
+}
+
+/// Links without a web session, the wallet signs a sign-in message instead
+#[derive(Deserialize, ToSchema)]
+pub struct LinkTelegramWithWallet {
+    pub code: String,
+    #[serde(flatten)]
+    pub wallet: ConnectWallet,
+}
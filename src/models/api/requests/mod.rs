@@ -1 +1,10 @@
+pub mod cancel_order;
+pub mod chart;
 pub mod connect_wallet;
+pub mod create_api_key;
+pub mod delegation;
+pub mod link_telegram;
+pub mod place_order;
+pub mod tg_verify;
+pub mod update_settings;
+pub mod ws_request;
@@ -0,0 +1,20 @@
+use bigdecimal::BigDecimal;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct PlaceOrder {
+    /// Market name, such as `BTC/USD`
+    pub market: String,
+    pub is_long: bool,
+    /// Between 1 and the max leverage of the market
+    pub leverage: u8,
+    /// Margin in collateral units
+    #[schema(value_type = String, example = "10.5")]
+    pub amount: BigDecimal,
+    /// Limit price, a market order is placed when left out
+    #[schema(value_type = Option<String>, example = "65000")]
+    pub price: Option<BigDecimal>,
+    /// Subaccount address, the primary subaccount when left out
+    pub subaccount: Option<String>,
+}
@@ -0,0 +1,8 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct TgVerify {
+    /// `Telegram.WebApp.initData` of the Mini App, as received
+    pub init_data: String,
+}
@@ -0,0 +1,10 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+/// Fields left out keep their value
+#[derive(Deserialize, ToSchema)]
+pub struct UpdateSettings {
+    /// Between 1 and 99 percent
+    pub slippage: Option<i64>,
+    pub degen_mode: Option<bool>,
+}
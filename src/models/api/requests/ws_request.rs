@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+/// Message a WebSocket client sends, e.g. `{"op": "subscribe", "topics": ["mark_price:BTC/USD"]}`.
+/// Topics are `mark_price`, `orders`, `fills` and `positions`, `mark_price:<market>` narrows
+/// mark prices to one market.
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum WsRequest {
+    Subscribe { topics: Vec<String> },
+    Unsubscribe { topics: Vec<String> },
+}
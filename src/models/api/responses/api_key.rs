@@ -0,0 +1,41 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::db::api_keys::ApiKey;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    /// Sent in the `X-Api-Key` header
+    pub key_id: String,
+    pub name: String,
+    /// `read` or `trade`
+    pub scope: String,
+    pub rate_limit_per_minute: i32,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(api_key: ApiKey) -> Self {
+        Self {
+            id: api_key.id,
+            key_id: api_key.key_id,
+            name: api_key.name,
+            scope: api_key.scope,
+            rate_limit_per_minute: api_key.rate_limit_per_minute,
+            last_used_at: api_key.last_used_at,
+            created_at: api_key.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreatedApiKeyResponse {
+    #[serde(flatten)]
+    pub api_key: ApiKeyResponse,
+    /// Signs requests, shown only once
+    pub secret: String,
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -5,3 +6,12 @@ use utoipa::ToSchema;
 pub struct AuthResponse {
     pub token: String,
 }
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct NonceResponse {
+    /// Goes in the header line of the sign-in message
+    pub domain: String,
+    pub nonce: String,
+    /// The nonce is rejected after this time
+    pub expires_at: DateTime<Utc>,
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct DelegationResponse {
+    /// Bot wallet the connected wallet delegates trading to
+    pub delegate_address: String,
+    /// Whether the bot trades for the connected wallet
+    pub active: bool,
+    /// Entry function the connected wallet signs and submits next, its txn hash is sent back
+    /// as `txn_hash`. Unset when there is nothing to sign.
+    pub pending_function: Option<EntryFunctionCall>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct EntryFunctionCall {
+    /// `<address>::<module>::<function>`
+    pub function: String,
+    pub arguments: Vec<String>,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Body of every error response
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    /// Stable error code such as `ERR_404`, safe to match on
+    pub code: String,
+    pub msg: String,
+    /// `x-request-id` of the request, to look it up in the logs
+    pub request_id: Option<String>,
+}
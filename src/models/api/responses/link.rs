@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LinkResponse {
+    /// Auth token of the linked user, replaces the one of a merged user
+    pub token: String,
+    pub user_id: Uuid,
+    /// The wallet must delegate trading again, to the bot wallet of the linked user
+    pub redelegate: bool,
+}
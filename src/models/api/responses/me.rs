@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::models::db::{subaccounts::SubAccount, users::User};
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct MeResponse {
+    pub id: Uuid,
+    pub tg_username: Option<String>,
+    /// Wallet the user connected from the terminal
+    pub connected_wallet: Option<String>,
+    /// Bot wallet of the user
+    pub address: String,
+    /// `custodial` or `delegated`
+    pub custody_mode: String,
+    pub slippage: i64,
+    pub degen_mode: bool,
+    pub subaccounts: Vec<SubaccountResponse>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SubaccountResponse {
+    pub address: String,
+    pub is_primary: bool,
+}
+
+impl MeResponse {
+    pub fn new(db_user: User, subaccounts: Vec<SubAccount>) -> Self {
+        Self {
+            id: db_user.id,
+            tg_username: db_user.tg_username,
+            connected_wallet: db_user.connected_wallet,
+            address: db_user.address,
+            custody_mode: db_user.custody_mode,
+            slippage: db_user.slippage,
+            degen_mode: db_user.degen_mode,
+            subaccounts: subaccounts
+                .into_iter()
+                .map(|subaccount| SubaccountResponse {
+                    is_primary: subaccount.is_primary(),
+                    address: subaccount.address,
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    /// False when any check failed, the response is then a 503
+    pub ready: bool,
+    pub checks: Vec<ReadinessCheck>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessCheck {
+    /// One of `database`, `node`, `decibel`, `cache` and `indexer`
+    pub name: String,
+    pub ok: bool,
+    /// What failed, or what was observed such as the indexer lag
+    pub detail: Option<String>,
+    pub latency_ms: u64,
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::utils::decibel_api::{OpenOrder, Position};
+
+/// Txn queued for submission
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OrderResponse {
+    /// Id of the queued txn
+    pub id: Uuid,
+    /// Unset for limit orders of custodial users, their order is signed once the margin
+    /// deposit committed
+    pub txn_hash: Option<String>,
+    pub subaccount: String,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct PositionResponse {
+    pub subaccount: String,
+    pub market: String,
+    /// Negative for shorts
+    pub size: f64,
+    pub entry_price: f64,
+    pub leverage: Option<f64>,
+    pub liquidation_price: Option<f64>,
+    pub unrealized_funding: Option<f64>,
+}
+
+impl PositionResponse {
+    pub fn new(subaccount: &str, position: Position) -> Self {
+        Self {
+            subaccount: subaccount.to_string(),
+            market: position.market,
+            size: position.size,
+            entry_price: position.entry_price,
+            leverage: position.user_leverage,
+            liquidation_price: position.estimated_liquidation_price,
+            unrealized_funding: position.unrealized_funding,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct OpenOrderResponse {
+    pub subaccount: String,
+    pub market: String,
+    pub order_id: String,
+    pub price: f64,
+    pub orig_size: f64,
+    pub remaining_size: f64,
+    pub is_buy: bool,
+    pub is_reduce_only: bool,
+}
+
+impl OpenOrderResponse {
+    pub fn new(subaccount: &str, order: OpenOrder) -> Self {
+        Self {
+            subaccount: subaccount.to_string(),
+            market: order.market,
+            order_id: order.order_id,
+            price: order.price,
+            orig_size: order.orig_size,
+            remaining_size: order.remaining_size,
+            is_buy: order.is_buy,
+            is_reduce_only: order.is_reduce_only,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct BalancesResponse {
+    /// Wallet holding the user's collateral, the connected wallet for delegated users
+    pub wallet: String,
+    pub symbol: String,
+    /// Collateral in the wallet
+    pub wallet_balance: String,
+    pub subaccounts: Vec<SubaccountBalance>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct SubaccountBalance {
+    pub address: String,
+    pub is_primary: bool,
+    pub equity: String,
+    pub unrealized_pnl: String,
+    pub total_margin: String,
+    /// Collateral that can leave the subaccount
+    pub withdrawable: String,
+}
@@ -0,0 +1,101 @@
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, Queryable, prelude::Insertable};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::{schema::api_keys, utils::database_utils::DbPoolConnection};
+
+/// Reads accounts, markets and orders
+pub const SCOPE_READ: &str = "read";
+/// Also places and cancels orders
+pub const SCOPE_TRADE: &str = "trade";
+
+/// Key for scripted access to the API. Requests are signed with an HMAC of its random secret,
+/// which the server needs to verify them, so rather than a hash the secret is stored
+/// encrypted with `api_key_config.secret`.
+#[derive(Debug, Queryable, Clone)]
+#[diesel(table_name = api_keys)]
+#[diesel(primary_key(id))]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub key_id: String,
+    /// Hex of nonce | AES-256-GCM ciphertext of the secret
+    pub encrypted_secret: String,
+    pub name: String,
+    pub scope: String,
+    pub rate_limit_per_minute: i32,
+    pub last_used_at: Option<chrono::NaiveDateTime>,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl ApiKey {
+    pub fn can_trade(&self) -> bool {
+        self.scope == SCOPE_TRADE
+    }
+
+    /// Active key with `key_id`
+    pub async fn get_by_key_id(
+        key_id: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        api_keys::table
+            .filter(api_keys::key_id.eq(key_id))
+            .filter(api_keys::revoked_at.is_null())
+            .select(api_keys::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+
+    /// Active keys of the user ordered by creation
+    pub async fn get_by_user_id(
+        user_id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        api_keys::table
+            .filter(api_keys::user_id.eq(user_id))
+            .filter(api_keys::revoked_at.is_null())
+            .order(api_keys::created_at.asc())
+            .select(api_keys::all_columns)
+            .load::<Self>(conn)
+            .await
+    }
+
+    /// Revokes the key `id` of the user, `false` if the user has no such active key
+    pub async fn revoke(
+        id: Uuid,
+        user_id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<bool> {
+        let revoked = diesel::update(
+            api_keys::table
+                .filter(api_keys::id.eq(id))
+                .filter(api_keys::user_id.eq(user_id))
+                .filter(api_keys::revoked_at.is_null()),
+        )
+        .set(api_keys::revoked_at.eq(chrono::Utc::now().naive_utc()))
+        .execute(conn)
+        .await?;
+        Ok(revoked > 0)
+    }
+
+    pub async fn touch(id: Uuid, conn: &mut DbPoolConnection<'_>) -> diesel::QueryResult<()> {
+        diesel::update(api_keys::table.find(id))
+            .set(api_keys::last_used_at.eq(chrono::Utc::now().naive_utc()))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = api_keys)]
+pub struct NewApiKey {
+    pub user_id: Uuid,
+    pub key_id: String,
+    pub encrypted_secret: String,
+    pub name: String,
+    pub scope: String,
+    pub rate_limit_per_minute: i32,
+}
@@ -0,0 +1,34 @@
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, prelude::Insertable};
+use diesel_async::RunQueryDsl;
+
+use crate::{schema::auth_nonces, utils::database_utils::DbPoolConnection};
+
+/// Nonce handed out for one Sign-In-With-Aptos message
+#[derive(Debug, Insertable)]
+#[diesel(table_name = auth_nonces)]
+pub struct NewAuthNonce {
+    pub nonce: String,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+impl NewAuthNonce {
+    /// Marks the nonce used, `false` if it is unknown, used or expired
+    pub async fn consume(
+        nonce: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<bool> {
+        let now = chrono::Utc::now().naive_utc();
+        let consumed = diesel::update(
+            auth_nonces::table
+                .filter(auth_nonces::nonce.eq(nonce))
+                .filter(auth_nonces::used_at.is_null())
+                .filter(auth_nonces::expires_at.gt(now)),
+        )
+        .set(auth_nonces::used_at.eq(now))
+        .returning(auth_nonces::nonce)
+        .get_result::<String>(conn)
+        .await
+        .optional()?;
+        Ok(consumed.is_some())
+    }
+}
@@ -0,0 +1,89 @@
+use diesel::{
+    ExpressionMethods, OptionalExtension, QueryDsl, Queryable, prelude::Insertable,
+    upsert::excluded,
+};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::{
+    schema::gas_usage,
+    utils::{database_utils::DbPoolConnection, db_execution::execute_with_better_error},
+};
+
+/// Sponsored gas fees of a user on one UTC day, in octas
+#[derive(Debug, Queryable, Insertable, Clone)]
+#[diesel(table_name = gas_usage)]
+#[diesel(primary_key(user_id, day))]
+pub struct GasUsage {
+    pub user_id: Uuid,
+    pub day: chrono::NaiveDate,
+    pub gas_fee: i64,
+    pub txn_count: i32,
+}
+
+impl GasUsage {
+    pub async fn get_spent_today(
+        user_id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<i64> {
+        let gas_fee = gas_usage::table
+            .filter(gas_usage::user_id.eq(user_id))
+            .filter(gas_usage::day.eq(chrono::Utc::now().date_naive()))
+            .select(gas_usage::gas_fee)
+            .first::<i64>(conn)
+            .await
+            .optional()?;
+        Ok(gas_fee.unwrap_or_default())
+    }
+
+    /// Adds a sponsored txn to today's usage of the user unless the usage already reached
+    /// `quota`. Checked and added in one statement, so concurrent txns of the user can't all
+    /// slip under the quota. Returns whether the txn was counted.
+    pub async fn try_record(
+        user_id: Uuid,
+        gas_fee: u64,
+        quota: u64,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<bool> {
+        let usage = Self {
+            user_id,
+            day: chrono::Utc::now().date_naive(),
+            gas_fee: i64::try_from(gas_fee)?,
+            txn_count: 1,
+        };
+        let recorded = diesel::insert_into(gas_usage::table)
+            .values(usage)
+            .on_conflict((gas_usage::user_id, gas_usage::day))
+            .do_update()
+            .set((
+                gas_usage::gas_fee.eq(gas_usage::gas_fee + excluded(gas_usage::gas_fee)),
+                gas_usage::txn_count.eq(gas_usage::txn_count + 1),
+            ))
+            .filter(gas_usage::gas_fee.lt(i64::try_from(quota)?))
+            .execute(conn)
+            .await?;
+        Ok(recorded > 0)
+    }
+
+    /// Replaces the simulated gas fee of a sponsored txn counted on `day` with `committed_fee`,
+    /// what it was charged on chain
+    pub async fn correct(
+        user_id: Uuid,
+        day: chrono::NaiveDate,
+        simulated_fee: i64,
+        committed_fee: u64,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        let query = diesel::update(
+            gas_usage::table
+                .filter(gas_usage::user_id.eq(user_id))
+                .filter(gas_usage::day.eq(day)),
+        )
+        .set(
+            gas_usage::gas_fee
+                .eq(gas_usage::gas_fee - simulated_fee + i64::try_from(committed_fee)?),
+        );
+        execute_with_better_error(conn, vec![query]).await?;
+        Ok(())
+    }
+}
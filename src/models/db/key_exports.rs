@@ -0,0 +1,97 @@
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, Queryable, prelude::Insertable};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::{
+    schema::{key_export_links, key_exports},
+    utils::{database_utils::DbPoolConnection, db_execution::execute_with_better_error},
+};
+
+pub const OUTCOME_WRONG_PIN: &str = "wrong_pin";
+pub const OUTCOME_RATE_LIMITED: &str = "rate_limited";
+pub const OUTCOME_EXPORTED: &str = "exported";
+pub const OUTCOME_LINK_CREATED: &str = "link_created";
+pub const OUTCOME_LINK_OPENED: &str = "link_opened";
+
+/// Audit trail entry, one per private key export attempt
+#[derive(Debug, Queryable, Clone)]
+#[diesel(table_name = key_exports)]
+#[diesel(primary_key(id))]
+pub struct KeyExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub delivery: String,
+    pub outcome: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = key_exports)]
+pub struct NewKeyExport {
+    pub user_id: Uuid,
+    pub delivery: String,
+    pub outcome: String,
+}
+
+impl KeyExport {
+    pub async fn record(
+        user_id: Uuid,
+        delivery: &str,
+        outcome: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        let query = diesel::insert_into(key_exports::table).values(NewKeyExport {
+            user_id,
+            delivery: delivery.to_string(),
+            outcome: outcome.to_string(),
+        });
+        execute_with_better_error(conn, vec![query]).await?;
+        Ok(())
+    }
+
+    /// Attempts of the user with one of `outcomes` since `since`
+    pub async fn count_since(
+        user_id: Uuid,
+        since: chrono::NaiveDateTime,
+        outcomes: &[&str],
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<i64> {
+        key_exports::table
+            .filter(key_exports::user_id.eq(user_id))
+            .filter(key_exports::created_at.ge(since))
+            .filter(key_exports::outcome.eq_any(outcomes))
+            .count()
+            .get_result::<i64>(conn)
+            .await
+    }
+}
+
+/// One-time export link, only the hash of its token is stored
+#[derive(Debug, Insertable)]
+#[diesel(table_name = key_export_links)]
+pub struct NewKeyExportLink {
+    pub token_hash: String,
+    pub user_id: Uuid,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+impl NewKeyExportLink {
+    /// Marks the link used and returns its user, `None` if it is unknown, used or expired
+    pub async fn redeem(
+        token_hash: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Uuid>> {
+        let now = chrono::Utc::now().naive_utc();
+        diesel::update(
+            key_export_links::table
+                .filter(key_export_links::token_hash.eq(token_hash))
+                .filter(key_export_links::used_at.is_null())
+                .filter(key_export_links::expires_at.gt(now)),
+        )
+        .set(key_export_links::used_at.eq(now))
+        .returning(key_export_links::user_id)
+        .get_result::<Uuid>(conn)
+        .await
+        .optional()
+    }
+}
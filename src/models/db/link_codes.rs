@@ -0,0 +1,51 @@
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, prelude::Insertable};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use uuid::Uuid;
+
+use crate::{schema::link_codes, utils::database_utils::DbPoolConnection};
+
+/// One-time code linking a Telegram user to a wallet, only the hash of the code is stored
+#[derive(Debug, Insertable)]
+#[diesel(table_name = link_codes)]
+pub struct NewLinkCode {
+    pub code_hash: String,
+    pub user_id: Uuid,
+    pub expires_at: chrono::NaiveDateTime,
+}
+
+impl NewLinkCode {
+    /// User of the code, `None` if it is unknown, used or expired. Leaves the code unused.
+    pub async fn get_user_id(
+        code_hash: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Uuid>> {
+        link_codes::table
+            .filter(link_codes::code_hash.eq(code_hash))
+            .filter(link_codes::used_at.is_null())
+            .filter(link_codes::expires_at.gt(chrono::Utc::now().naive_utc()))
+            .select(link_codes::user_id)
+            .first::<Uuid>(conn)
+            .await
+            .optional()
+    }
+
+    /// Marks the code used and returns its user, `None` if it is unknown, used or expired.
+    /// Takes a plain connection so it runs in the transaction of the link it allows.
+    pub async fn redeem(
+        code_hash: &str,
+        conn: &mut AsyncPgConnection,
+    ) -> diesel::QueryResult<Option<Uuid>> {
+        let now = chrono::Utc::now().naive_utc();
+        diesel::update(
+            link_codes::table
+                .filter(link_codes::code_hash.eq(code_hash))
+                .filter(link_codes::used_at.is_null())
+                .filter(link_codes::expires_at.gt(now)),
+        )
+        .set(link_codes::used_at.eq(now))
+        .returning(link_codes::user_id)
+        .get_result::<Uuid>(conn)
+        .await
+        .optional()
+    }
+}
@@ -1,5 +1,12 @@
+pub mod api_keys;
+pub mod auth_nonces;
+pub mod gas_usage;
+pub mod key_exports;
+pub mod link_codes;
 pub mod processor_status;
 pub mod subaccounts;
 pub mod tokens;
+pub mod trading_delegations;
+pub mod transaction_outbox;
 pub mod users;
 pub mod wallets;
@@ -12,6 +12,22 @@ pub struct ProcessorStatus {
     pub last_transaction_timestamp: Option<chrono::NaiveDateTime>,
 }
 
+impl ProcessorStatus {
+    pub async fn upsert(&self, conn: &mut DbPoolConnection<'_>) -> diesel::QueryResult<usize> {
+        diesel::insert_into(processor_status::table)
+            .values(self)
+            .on_conflict(processor_status::processor)
+            .do_update()
+            .set((
+                processor_status::last_success_version.eq(self.last_success_version),
+                processor_status::last_updated.eq(diesel::dsl::now),
+                processor_status::last_transaction_timestamp.eq(self.last_transaction_timestamp),
+            ))
+            .execute(conn)
+            .await
+    }
+}
+
 #[derive(AsChangeset, Debug, Queryable)]
 #[diesel(table_name = processor_status)]
 /// Only tracking the latest version successfully processed
@@ -1,75 +1,113 @@
-// use aptos_indexer_processor_sdk::utils::convert::standardize_address;
-// use diesel::{
-//     AsChangeset, ExpressionMethods, OptionalExtension, QueryDsl, Queryable, prelude::Insertable,
-// };
-// use diesel_async::RunQueryDsl;
-// use uuid::Uuid;
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use diesel::{
+    AsChangeset, ExpressionMethods, OptionalExtension, QueryDsl, Queryable, prelude::Insertable,
+};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
 
-// use crate::{schema::subaccounts, utils::database_utils::DbPoolConnection};
+use crate::{schema::subaccounts, utils::database_utils::DbPoolConnection};
 
-// #[derive(AsChangeset, Debug, Queryable)]
-// #[diesel(table_name = subaccounts)]
-// #[diesel(primary_key(id))]
-// pub struct SubAccount {
-//     pub id: Uuid,
-//     pub wallet_id: Uuid,
-//     pub address: String,
-//     pub is_primary: Option<bool>,
-// }
+#[derive(AsChangeset, Debug, Queryable, Clone)]
+#[diesel(table_name = subaccounts)]
+#[diesel(primary_key(id))]
+pub struct SubAccount {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub address: String,
+    pub is_primary: Option<bool>,
+    pub created_at: chrono::NaiveDateTime,
+}
 
-// impl SubAccount {
-//     pub async fn get_subaccounts_by_wallet_id(
-//         wallet_id: Uuid,
-//         conn: &mut DbPoolConnection<'_>,
-//     ) -> diesel::QueryResult<Vec<Self>> {
-//         subaccounts::table
-//             .filter(subaccounts::wallet_id.eq(wallet_id))
-//             .load::<Self>(conn)
-//             .await
-//     }
-//     pub async fn get_primary_subaccount_by_wallet_id(
-//         wallet_id: Uuid,
-//         conn: &mut DbPoolConnection<'_>,
-//     ) -> diesel::QueryResult<Option<SubAccount>> {
-//         subaccounts::table
-//             .filter(subaccounts::wallet_id.eq(wallet_id))
-//             .filter(subaccounts::is_primary.eq(Some(true)))
-//             .select(subaccounts::all_columns)
-//             .first::<Self>(conn)
-//             .await
-//             .optional()
-//     }
+impl SubAccount {
+    pub async fn get_subaccounts_by_user_id(
+        user_id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        subaccounts::table
+            .filter(subaccounts::user_id.eq(user_id))
+            .order((subaccounts::created_at.asc(), subaccounts::id.asc()))
+            .load::<Self>(conn)
+            .await
+    }
 
-//     pub async fn get_by_id(
-//         id: Uuid,
-//         conn: &mut DbPoolConnection<'_>,
-//     ) -> diesel::QueryResult<Option<Self>> {
-//         subaccounts::table
-//             .filter(subaccounts::id.eq(id))
-//             .select(subaccounts::all_columns)
-//             .first::<Self>(conn)
-//             .await
-//             .optional()
-//     }
-// }
+    pub async fn get_primary_subaccount_by_user_id(
+        user_id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<SubAccount>> {
+        subaccounts::table
+            .filter(subaccounts::user_id.eq(user_id))
+            .filter(subaccounts::is_primary.eq(Some(true)))
+            .select(subaccounts::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
 
-// #[derive(AsChangeset, Debug, Insertable)]
-// #[diesel(table_name = subaccounts)]
-// #[diesel(primary_key(id))]
-// pub struct NewSubAccount {
-//     pub id: Uuid,
-//     pub wallet_id: Uuid,
-//     pub address: String,
-//     pub is_primary: bool,
-// }
+    pub async fn get_by_id(
+        id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        subaccounts::table
+            .filter(subaccounts::id.eq(id))
+            .select(subaccounts::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
 
-// impl NewSubAccount {
-//     pub fn to_db_subaccount(wallet_id: Uuid, address: String, is_primary: bool) -> Self {
-//         Self {
-//             id: Uuid::new_v4(),
-//             wallet_id,
-//             address: standardize_address(&address),
-//             is_primary,
-//         }
-//     }
-// }
+    /// Subaccounts among `addresses`, which must be standardized
+    pub async fn get_by_addresses(
+        addresses: &[String],
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        subaccounts::table
+            .filter(subaccounts::address.eq_any(addresses))
+            .select(subaccounts::all_columns)
+            .load::<Self>(conn)
+            .await
+    }
+
+    pub fn is_primary(&self) -> bool {
+        self.is_primary.unwrap_or(false)
+    }
+
+    /// Short label used on buttons, e.g. `#2 0x1a2b…9f02`
+    pub fn label(&self, idx: usize) -> String {
+        let short = if self.address.len() > 10 {
+            format!(
+                "{}…{}",
+                &self.address[..6],
+                &self.address[self.address.len() - 4..]
+            )
+        } else {
+            self.address.clone()
+        };
+        format!(
+            "#{} {}{}",
+            idx + 1,
+            short,
+            if self.is_primary() { " ⭐" } else { "" }
+        )
+    }
+}
+
+#[derive(AsChangeset, Debug, Insertable)]
+#[diesel(table_name = subaccounts)]
+#[diesel(primary_key(id))]
+pub struct NewSubAccount {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub address: String,
+    pub is_primary: bool,
+}
+
+impl NewSubAccount {
+    pub fn to_db_subaccount(user_id: Uuid, address: String, is_primary: bool) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            address: standardize_address(&address),
+            is_primary,
+        }
+    }
+}
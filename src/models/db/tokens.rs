@@ -1,34 +1,70 @@
-// use diesel::{AsChangeset, ExpressionMethods, OptionalExtension, QueryDsl, Queryable};
-// use diesel_async::RunQueryDsl;
-// use uuid::Uuid;
+use bigdecimal::BigDecimal;
+use diesel::{AsChangeset, ExpressionMethods, OptionalExtension, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
 
-// use crate::{schema::tokens, utils::database_utils::DbPoolConnection};
+use crate::{schema::tokens, utils::database_utils::DbPoolConnection};
 
-// #[derive(AsChangeset, Debug, Queryable)]
-// #[diesel(table_name = tokens)]
-// #[diesel(primary_key(id))]
-// pub struct Token {
-//     pub id: Uuid,
-//     pub name: String,
-//     pub symbol: String,
-//     pub address: String,
-//     pub coin_addr: Option<String>,
-//     pub decimals: i32,
-// }
+#[derive(AsChangeset, Debug, Queryable, Clone)]
+#[diesel(table_name = tokens)]
+#[diesel(primary_key(id))]
+pub struct Token {
+    pub id: Uuid,
+    pub name: String,
+    pub symbol: String,
+    pub address: String,
+    pub decimals: i32,
+    pub is_collateral: bool,
+    /// Network the token lives on, the `chain_id` of the network profile
+    pub chain_id: i32,
+}
 
-// impl Token {
-//     pub async fn get_tokens(conn: &mut DbPoolConnection<'_>) -> diesel::QueryResult<Vec<Self>> {
-//         tokens::table.load::<Self>(conn).await
-//     }
-//     pub async fn get_token_by_symbol(
-//         symbol: String,
-//         conn: &mut DbPoolConnection<'_>,
-//     ) -> diesel::QueryResult<Option<Self>> {
-//         tokens::table
-//             .filter(tokens::symbol.eq(symbol))
-//             .select(tokens::all_columns)
-//             .first::<Self>(conn)
-//             .await
-//             .optional()
-//     }
-// }
+impl Token {
+    pub async fn get_tokens(
+        chain_id: i32,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        tokens::table
+            .filter(tokens::chain_id.eq(chain_id))
+            .load::<Self>(conn)
+            .await
+    }
+
+    pub async fn get_token_by_symbol(
+        chain_id: i32,
+        symbol: String,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        tokens::table
+            .filter(tokens::chain_id.eq(chain_id))
+            .filter(tokens::symbol.eq(symbol))
+            .select(tokens::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+
+    /// Asset deposited into Decibel subaccounts and used as margin
+    pub async fn get_collateral_token(
+        chain_id: i32,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        tokens::table
+            .filter(tokens::chain_id.eq(chain_id))
+            .filter(tokens::is_collateral.eq(true))
+            .select(tokens::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+
+    /// Converts a human readable amount into on-chain units, dropping extra precision
+    pub fn to_base_units(&self, amount: &BigDecimal) -> anyhow::Result<u64> {
+        let scaled_amount = amount * BigDecimal::from(10u64.pow(self.decimals as u32));
+        Ok(scaled_amount.with_scale(0).to_string().parse::<u64>()?)
+    }
+
+    pub fn from_base_units(&self, amount: &BigDecimal) -> BigDecimal {
+        amount / BigDecimal::from(10u64.pow(self.decimals as u32))
+    }
+}
@@ -0,0 +1,65 @@
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, Queryable,
+    prelude::Insertable,
+};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::{schema::trading_delegations, utils::database_utils::DbPoolConnection};
+
+/// Trading rights `owner_address` granted on-chain to the bot wallet `delegate_address`
+#[derive(Debug, Queryable, Clone)]
+#[diesel(table_name = trading_delegations)]
+#[diesel(primary_key(id))]
+pub struct TradingDelegation {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub owner_address: String,
+    pub delegate_address: String,
+    pub created_at: chrono::NaiveDateTime,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+    /// `delegate_trading_to` txn of the owner
+    pub txn_hash: Option<String>,
+    /// `revoke_delegation` txn of the owner
+    pub revoke_txn_hash: Option<String>,
+}
+
+impl TradingDelegation {
+    pub async fn get_active(
+        user_id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        trading_delegations::table
+            .filter(trading_delegations::user_id.eq(user_id))
+            .filter(trading_delegations::revoked_at.is_null())
+            .select(trading_delegations::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+
+    /// Whether `txn_hash` already delegated or revoked trading
+    pub async fn is_txn_used(
+        txn_hash: &str,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            trading_delegations::table.filter(
+                trading_delegations::txn_hash
+                    .eq(txn_hash)
+                    .or(trading_delegations::revoke_txn_hash.eq(txn_hash)),
+            ),
+        ))
+        .get_result(conn)
+        .await
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = trading_delegations)]
+pub struct NewTradingDelegation {
+    pub user_id: Uuid,
+    pub owner_address: String,
+    pub delegate_address: String,
+    pub txn_hash: Option<String>,
+}
@@ -0,0 +1,231 @@
+use anyhow::Context;
+use aptos_sdk::{
+    bcs,
+    types::transaction::{SignedTransaction, TransactionPayload},
+};
+use diesel::{
+    AsChangeset, BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl, Queryable,
+    prelude::Insertable,
+};
+use diesel_async::RunQueryDsl;
+use uuid::Uuid;
+
+use crate::{
+    schema::transaction_outbox,
+    utils::{chain_client::SignedTxn, database_utils::DbPoolConnection},
+};
+
+/// Not signed yet, waits for the txn it depends on to commit
+pub const STATUS_WAITING: &str = "waiting";
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_SUBMITTED: &str = "submitted";
+pub const STATUS_COMMITTED: &str = "committed";
+pub const STATUS_FAILED: &str = "failed";
+
+#[derive(AsChangeset, Debug, Queryable, Clone)]
+#[diesel(table_name = transaction_outbox)]
+#[diesel(primary_key(id))]
+pub struct OutboxTransaction {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub summary: String,
+    /// BCS encoded `TransactionPayload`, kept to re-sign expired txns
+    pub payload: Vec<u8>,
+    /// BCS encoded `SignedTransaction`, `None` while waiting
+    pub signed_txn: Option<Vec<u8>>,
+    pub txn_hash: Option<String>,
+    pub status: String,
+    pub attempts: i32,
+    pub error: Option<String>,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub chat_id: Option<i64>,
+    pub message_id: Option<i32>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+    /// Txn that has to commit before this one is signed
+    pub depends_on: Option<Uuid>,
+    /// Gas fee counted against the user's daily quota, `None` when the sender pays
+    pub sponsored_gas_fee: Option<i64>,
+    /// Claimed by a worker until then
+    pub locked_until: Option<chrono::NaiveDateTime>,
+}
+
+impl OutboxTransaction {
+    pub async fn get_by_id(
+        id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        transaction_outbox::table
+            .filter(transaction_outbox::id.eq(id))
+            .select(transaction_outbox::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+
+    /// Txns the worker still has to sign, submit or confirm, oldest first
+    pub async fn get_open(
+        limit: i64,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        transaction_outbox::table
+            .filter(transaction_outbox::status.eq_any([
+                STATUS_WAITING,
+                STATUS_PENDING,
+                STATUS_SUBMITTED,
+            ]))
+            .order(transaction_outbox::created_at.asc())
+            .limit(limit)
+            .select(transaction_outbox::all_columns)
+            .load::<Self>(conn)
+            .await
+    }
+
+    /// Claims up to `limit` open txns no other worker holds for `lease`, oldest first. Rows
+    /// another worker is claiming at the same time are skipped rather than waited for.
+    pub async fn claim_open(
+        limit: i64,
+        lease: chrono::Duration,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Vec<Self>> {
+        let now = chrono::Utc::now().naive_utc();
+        let open = transaction_outbox::table
+            .filter(transaction_outbox::status.eq_any([
+                STATUS_WAITING,
+                STATUS_PENDING,
+                STATUS_SUBMITTED,
+            ]))
+            .filter(
+                transaction_outbox::locked_until
+                    .is_null()
+                    .or(transaction_outbox::locked_until.lt(now)),
+            )
+            .order(transaction_outbox::created_at.asc())
+            .limit(limit)
+            .select(transaction_outbox::id)
+            .for_update()
+            .skip_locked();
+        let mut txns =
+            diesel::update(transaction_outbox::table.filter(transaction_outbox::id.eq_any(open)))
+                .set(transaction_outbox::locked_until.eq(Some(now + lease)))
+                .get_results::<Self>(conn)
+                .await?;
+        txns.sort_by_key(|txn| txn.created_at);
+        Ok(txns)
+    }
+
+    /// Gives a claimed txn back, the next batch picks it up again if it is still open
+    pub async fn unlock(id: Uuid, conn: &mut DbPoolConnection<'_>) -> diesel::QueryResult<usize> {
+        diesel::update(transaction_outbox::table.filter(transaction_outbox::id.eq(id)))
+            .set(transaction_outbox::locked_until.eq(None::<chrono::NaiveDateTime>))
+            .execute(conn)
+            .await
+    }
+
+    /// Whether the worker still has txns of the user to sign, submit or confirm
+    pub async fn has_open(
+        user_id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            transaction_outbox::table
+                .filter(transaction_outbox::user_id.eq(user_id))
+                .filter(transaction_outbox::status.eq_any([
+                    STATUS_WAITING,
+                    STATUS_PENDING,
+                    STATUS_SUBMITTED,
+                ])),
+        ))
+        .get_result(conn)
+        .await
+    }
+
+    pub fn signed_txn(&self) -> anyhow::Result<SignedTransaction> {
+        let signed_txn = self
+            .signed_txn
+            .as_deref()
+            .context("Outbox txn is not signed yet")?;
+        Ok(bcs::from_bytes(signed_txn)?)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| chrono::Utc::now().naive_utc() > expires_at)
+    }
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = transaction_outbox)]
+pub struct NewOutboxTransaction {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: String,
+    pub summary: String,
+    pub payload: Vec<u8>,
+    pub signed_txn: Option<Vec<u8>>,
+    pub status: String,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub chat_id: Option<i64>,
+    pub message_id: Option<i32>,
+    pub depends_on: Option<Uuid>,
+    pub sponsored_gas_fee: Option<i64>,
+}
+
+impl NewOutboxTransaction {
+    pub fn from_signed_txn(
+        user_id: Uuid,
+        kind: &str,
+        summary: String,
+        txn: &SignedTxn,
+        chat_id: Option<i64>,
+        message_id: Option<i32>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: Uuid::new_v4(),
+            user_id,
+            kind: kind.to_string(),
+            summary,
+            payload: bcs::to_bytes(txn.payload())?,
+            signed_txn: Some(bcs::to_bytes(&**txn)?),
+            status: STATUS_PENDING.to_string(),
+            expires_at: Some(expiration_of(txn)?),
+            chat_id,
+            message_id,
+            depends_on: None,
+            sponsored_gas_fee: txn.sponsored_gas_fee().map(i64::try_from).transpose()?,
+        })
+    }
+
+    /// `payload` to sign once the txn `depends_on` committed
+    pub fn waiting_on(
+        depends_on: Uuid,
+        user_id: Uuid,
+        kind: &str,
+        summary: String,
+        payload: &TransactionPayload,
+        chat_id: Option<i64>,
+        message_id: Option<i32>,
+    ) -> anyhow::Result<Self> {
+        Ok(Self {
+            id: Uuid::new_v4(),
+            user_id,
+            kind: kind.to_string(),
+            summary,
+            payload: bcs::to_bytes(payload)?,
+            signed_txn: None,
+            status: STATUS_WAITING.to_string(),
+            expires_at: None,
+            chat_id,
+            message_id,
+            depends_on: Some(depends_on),
+            sponsored_gas_fee: None,
+        })
+    }
+}
+
+pub fn expiration_of(txn: &SignedTransaction) -> anyhow::Result<chrono::NaiveDateTime> {
+    let expires_at = chrono::DateTime::from_timestamp(txn.expiration_timestamp_secs() as i64, 0)
+        .context("Invalid txn expiration")?;
+    Ok(expires_at.naive_utc())
+}
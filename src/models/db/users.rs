@@ -6,6 +6,11 @@ use uuid::Uuid;
 
 use crate::{schema::users, utils::database_utils::DbPoolConnection};
 
+/// The bot wallet of the user holds the funds and trades them
+pub const CUSTODY_CUSTODIAL: &str = "custodial";
+/// Funds stay in `connected_wallet`, which delegated trading to the bot wallet
+pub const CUSTODY_DELEGATED: &str = "delegated";
+
 #[derive(AsChangeset, Debug, Queryable, Clone, Insertable)]
 #[diesel(table_name = users)]
 #[diesel(primary_key(id))]
@@ -19,9 +24,34 @@ pub struct User {
     pub wallet_id: String,
     pub slippage: i64,
     pub degen_mode: bool,
+    pub custody_mode: String,
+    /// Argon2 hash of the PIN confirming key exports
+    pub pin_hash: Option<String>,
+    /// Set once the user was merged into another one, which holds its identities since
+    pub merged_into: Option<Uuid>,
+}
+
+/// Settings the user can change from the terminal, `None` fields are left untouched
+#[derive(AsChangeset, Debug)]
+#[diesel(table_name = users)]
+pub struct UserSettings {
+    pub slippage: Option<i64>,
+    pub degen_mode: Option<bool>,
 }
 
 impl User {
+    pub async fn get_by_id(
+        id: Uuid,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> diesel::QueryResult<Option<Self>> {
+        users::table
+            .filter(users::id.eq(id))
+            .select(users::all_columns)
+            .first::<Self>(conn)
+            .await
+            .optional()
+    }
+
     pub async fn get_by_telegram_id(
         tg_id: i64,
         conn: &mut DbPoolConnection<'_>,
@@ -75,6 +105,22 @@ impl User {
             wallet_id,
             slippage: 20,
             degen_mode: false,
+            custody_mode: CUSTODY_CUSTODIAL.to_string(),
+            pin_hash: None,
+            merged_into: None,
+        }
+    }
+
+    pub fn is_delegated(&self) -> bool {
+        self.custody_mode == CUSTODY_DELEGATED
+    }
+
+    /// Account owning the subaccounts the user trades, the connected wallet once trading is
+    /// delegated to the bot wallet
+    pub fn trading_account(&self) -> &str {
+        match &self.connected_wallet {
+            Some(connected_wallet) if self.is_delegated() => connected_wallet,
+            _ => &self.address,
         }
     }
 }
@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, KeyInit},
+};
+use anyhow::Context;
+use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
+use aptos_sdk::types::account_address::AccountAddress;
+use argon2::Argon2;
+use serde::{Deserialize, Serialize};
+
+use crate::signer::{Signer, SigningMessage, account_address, generate_private_key, sign_with};
+
+/// One file per account, the private key encrypted with AES-256-GCM under a key derived
+/// from the keystore password with Argon2
+#[derive(Debug, Serialize, Deserialize)]
+struct KeystoreEntry {
+    address: String,
+    public_key: String,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+pub struct KeystoreSigner {
+    path: PathBuf,
+    password: String,
+}
+
+impl KeystoreSigner {
+    pub fn new(path: &str, password: &str) -> anyhow::Result<Self> {
+        if password.is_empty() {
+            anyhow::bail!("Keystore password must not be empty");
+        }
+        let path = PathBuf::from(path);
+        std::fs::create_dir_all(&path).context("Failed to create keystore directory")?;
+        Ok(Self {
+            path,
+            password: password.to_string(),
+        })
+    }
+
+    fn entry_path(&self, address: &AccountAddress) -> PathBuf {
+        self.path
+            .join(format!("{}.json", address.to_standard_string()))
+    }
+
+    fn cipher(&self, salt: &[u8]) -> anyhow::Result<Aes256Gcm> {
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(self.password.as_bytes(), salt, &mut key)
+            .map_err(|err| anyhow::anyhow!("Failed to derive keystore key: {}", err))?;
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+
+    async fn load_key(&self, address: AccountAddress) -> anyhow::Result<Ed25519PrivateKey> {
+        let contents = tokio::fs::read(self.entry_path(&address))
+            .await
+            .with_context(|| format!("No keystore entry for {}", address))?;
+        let entry = serde_json::from_slice::<KeystoreEntry>(&contents)?;
+        let salt = hex::decode(&entry.salt)?;
+        let nonce = hex::decode(&entry.nonce)?;
+        let ciphertext = hex::decode(&entry.ciphertext)?;
+        let private_key = self
+            .cipher(&salt)?
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+            .map_err(|_| anyhow::anyhow!("Failed to decrypt keystore entry for {}", address))?;
+        Ok(Ed25519PrivateKey::try_from(private_key.as_slice())?)
+    }
+
+    async fn store_key(&self, private_key: &Ed25519PrivateKey) -> anyhow::Result<AccountAddress> {
+        let public_key = Ed25519PublicKey::from(private_key);
+        let address = account_address(&public_key);
+        let salt: [u8; 16] = rand::random();
+        let nonce: [u8; 12] = rand::random();
+        let ciphertext = self
+            .cipher(&salt)?
+            .encrypt(Nonce::from_slice(&nonce), private_key.to_bytes().as_slice())
+            .map_err(|_| anyhow::anyhow!("Failed to encrypt keystore entry"))?;
+        let entry = KeystoreEntry {
+            address: address.to_standard_string(),
+            public_key: public_key.to_string(),
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        };
+        tokio::fs::write(
+            self.entry_path(&address),
+            serde_json::to_vec_pretty(&entry)?,
+        )
+        .await
+        .context("Failed to write keystore entry")?;
+        Ok(address)
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for KeystoreSigner {
+    async fn create_wallet(&self, _wallet_name: &str) -> anyhow::Result<(String, String, String)> {
+        let private_key = generate_private_key()?;
+        let address = self.store_key(&private_key).await?;
+        let public_key = Ed25519PublicKey::from(&private_key);
+        Ok((
+            address.to_standard_string(),
+            address.to_standard_string(),
+            public_key.to_string(),
+        ))
+    }
+
+    async fn sign_message(
+        &self,
+        address: AccountAddress,
+        message: &SigningMessage,
+    ) -> anyhow::Result<Ed25519Signature> {
+        let private_key = self.load_key(address).await?;
+        sign_with(&private_key, message)
+    }
+
+    async fn export_private_key(&self, address: &str) -> anyhow::Result<String> {
+        let address = AccountAddress::from_hex_literal(address)?;
+        let private_key = self.load_key(address).await?;
+        Ok(hex::encode(private_key.to_bytes()))
+    }
+}
@@ -0,0 +1,75 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use aptos_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
+use aptos_sdk::types::account_address::AccountAddress;
+
+use crate::signer::{
+    Signer, SigningMessage, account_address, generate_private_key, parse_private_key, sign_with,
+};
+
+/// Keeps keys in process memory. Wallets created at runtime are lost on restart, so this is
+/// meant for localnet and tests, with long lived accounts passed in through the config.
+pub struct LocalSigner {
+    keys: RwLock<HashMap<AccountAddress, Ed25519PrivateKey>>,
+}
+
+impl LocalSigner {
+    pub fn new(private_keys: &[String]) -> anyhow::Result<Self> {
+        let mut keys = HashMap::new();
+        for private_key in private_keys {
+            let private_key = parse_private_key(private_key)?;
+            keys.insert(
+                account_address(&Ed25519PublicKey::from(&private_key)),
+                private_key,
+            );
+        }
+        Ok(Self {
+            keys: RwLock::new(keys),
+        })
+    }
+
+    fn with_key<T>(
+        &self,
+        address: AccountAddress,
+        f: impl FnOnce(&Ed25519PrivateKey) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let keys = self.keys.read().unwrap_or_else(|err| err.into_inner());
+        let private_key = keys
+            .get(&address)
+            .ok_or_else(|| anyhow::anyhow!("No local key for {}", address))?;
+        f(private_key)
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for LocalSigner {
+    async fn create_wallet(&self, _wallet_name: &str) -> anyhow::Result<(String, String, String)> {
+        let private_key = generate_private_key()?;
+        let public_key = Ed25519PublicKey::from(&private_key);
+        let address = account_address(&public_key);
+        self.keys
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(address, private_key);
+        Ok((
+            address.to_standard_string(),
+            address.to_standard_string(),
+            public_key.to_string(),
+        ))
+    }
+
+    async fn sign_message(
+        &self,
+        address: AccountAddress,
+        message: &SigningMessage,
+    ) -> anyhow::Result<Ed25519Signature> {
+        self.with_key(address, |private_key| sign_with(private_key, message))
+    }
+
+    async fn export_private_key(&self, address: &str) -> anyhow::Result<String> {
+        let address = AccountAddress::from_hex_literal(address)?;
+        self.with_key(address, |private_key| {
+            Ok(hex::encode(private_key.to_bytes()))
+        })
+    }
+}
@@ -0,0 +1,83 @@
+pub mod keystore;
+pub mod local;
+pub mod turnkey;
+
+use std::sync::Arc;
+
+use anyhow::Context;
+use aptos_crypto::{
+    SigningKey,
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+};
+use aptos_sdk::types::{
+    account_address::AccountAddress,
+    transaction::{RawTransaction, RawTransactionWithData, authenticator::AuthenticationKey},
+};
+
+use crate::{
+    config::{Config, SignerConfig},
+    signer::{keystore::KeystoreSigner, local::LocalSigner, turnkey::TurnkeySigner},
+};
+
+/// Custodial key backend holding the Ed25519 keys of user wallets
+#[async_trait::async_trait]
+pub trait Signer: Send + Sync + 'static {
+    /// Creates a new account, returning its wallet id, address and public key
+    async fn create_wallet(&self, wallet_name: &str) -> anyhow::Result<(String, String, String)>;
+
+    async fn sign_message(
+        &self,
+        address: AccountAddress,
+        message: &SigningMessage,
+    ) -> anyhow::Result<Ed25519Signature>;
+
+    /// Hex encoded private key of `address`
+    async fn export_private_key(&self, address: &str) -> anyhow::Result<String>;
+}
+
+/// Txn a wallet signs, plain when the sender pays gas or with a fee payer when sponsored
+pub enum SigningMessage {
+    Transaction(RawTransaction),
+    FeePayer(RawTransactionWithData),
+}
+
+pub fn new_signer(config: &Config) -> anyhow::Result<Arc<dyn Signer>> {
+    let signer: Arc<dyn Signer> = match &config.signer_config {
+        SignerConfig::Turnkey => {
+            let turnkey_config = config
+                .turnkey_config
+                .as_ref()
+                .context("turnkey_config is required by the turnkey signer")?;
+            Arc::new(TurnkeySigner::new(turnkey_config)?)
+        }
+        SignerConfig::Local { private_keys } => Arc::new(LocalSigner::new(private_keys)?),
+        SignerConfig::Keystore { path, password } => Arc::new(KeystoreSigner::new(path, password)?),
+    };
+    Ok(signer)
+}
+
+pub fn parse_private_key(private_key: &str) -> anyhow::Result<Ed25519PrivateKey> {
+    let seed_bytes = hex::decode(private_key.trim_start_matches("0x"))
+        .context("Failed to decode private key string")?;
+    Ok(Ed25519PrivateKey::try_from(seed_bytes.as_slice())?)
+}
+
+pub fn generate_private_key() -> anyhow::Result<Ed25519PrivateKey> {
+    let seed: [u8; 32] = rand::random();
+    Ok(Ed25519PrivateKey::try_from(seed.as_slice())?)
+}
+
+pub fn sign_with(
+    private_key: &Ed25519PrivateKey,
+    message: &SigningMessage,
+) -> anyhow::Result<Ed25519Signature> {
+    let signature = match message {
+        SigningMessage::Transaction(raw_txn) => private_key.sign(raw_txn)?,
+        SigningMessage::FeePayer(raw_txn) => private_key.sign(raw_txn)?,
+    };
+    Ok(signature)
+}
+
+pub fn account_address(public_key: &Ed25519PublicKey) -> AccountAddress {
+    AuthenticationKey::ed25519(public_key).account_address()
+}
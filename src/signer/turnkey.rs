@@ -0,0 +1,150 @@
+use anyhow::Context;
+use aptos_crypto::{
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+    traits::signing_message,
+};
+use aptos_sdk::types::account_address::AccountAddress;
+use turnkey_client::generated::immutable::common::v1::{
+    AddressFormat, Curve, HashFunction, PathFormat, PayloadEncoding,
+};
+use turnkey_client::generated::{
+    CreateWalletIntent, ExportWalletAccountIntent, SignRawPayloadIntentV2, WalletAccountParams,
+};
+use turnkey_client::{TurnkeyClient, TurnkeyP256ApiKey};
+use turnkey_enclave_encrypt::{ExportClient, QuorumPublicKey};
+
+use crate::{
+    config::TurnkeyConfig,
+    signer::{Signer, SigningMessage},
+    utils::metrics::time_turnkey,
+};
+
+pub struct TurnkeySigner {
+    turnkey: TurnkeyClient<TurnkeyP256ApiKey>,
+    organization_id: String,
+}
+
+impl TurnkeySigner {
+    pub fn new(config: &TurnkeyConfig) -> anyhow::Result<Self> {
+        let turnkey_api_key = TurnkeyP256ApiKey::from_strings(
+            config.api_private_key.clone(),
+            Some(config.api_public_key.to_string()),
+        )
+        .context("Failed to get turnkey p256 api key")?;
+
+        let turnkey = TurnkeyClient::builder()
+            .api_key(turnkey_api_key)
+            .build()
+            .context("Failed to build turnkey client")?;
+
+        Ok(Self {
+            turnkey,
+            organization_id: config.organization_id.clone(),
+        })
+    }
+
+    fn get_public_key_str_from_private_key_str(private_key: &str) -> anyhow::Result<String> {
+        let seed_bytes = hex::decode(private_key).context("Failed to decode private key string")?;
+        let sender_key = Ed25519PrivateKey::try_from(seed_bytes.as_slice())?;
+
+        let sender_public_key = Ed25519PublicKey::from(&sender_key);
+        let sender_public_key_str = sender_public_key.to_string();
+        Ok(sender_public_key_str)
+    }
+}
+
+#[async_trait::async_trait]
+impl Signer for TurnkeySigner {
+    async fn create_wallet(&self, wallet_name: &str) -> anyhow::Result<(String, String, String)> {
+        let create_wallet_result = time_turnkey(
+            "create_wallet",
+            self.turnkey.create_wallet(
+                self.organization_id.clone(),
+                self.turnkey.current_timestamp(),
+                CreateWalletIntent {
+                    wallet_name: wallet_name.into(),
+                    accounts: vec![WalletAccountParams {
+                        curve: Curve::Ed25519,
+                        path_format: PathFormat::Bip32,
+                        path: "m/44'/60'/0'/0".to_string(),
+                        address_format: AddressFormat::Aptos,
+                    }],
+                    mnemonic_length: None,
+                },
+            ),
+        )
+        .await?;
+
+        assert_eq!(create_wallet_result.addresses.len(), 1);
+
+        let wallet_address = create_wallet_result
+            .addresses
+            .first()
+            .context("Failed to get first wallet address")?;
+        let wallet_id = create_wallet_result.wallet_id;
+        let private_key = self.export_private_key(wallet_address).await?;
+        let public_key = Self::get_public_key_str_from_private_key_str(&private_key)?;
+        Ok((wallet_id, wallet_address.clone(), public_key))
+    }
+
+    async fn sign_message(
+        &self,
+        address: AccountAddress,
+        message: &SigningMessage,
+    ) -> anyhow::Result<Ed25519Signature> {
+        let signing_message = match message {
+            SigningMessage::Transaction(raw_txn) => signing_message(raw_txn)?,
+            SigningMessage::FeePayer(raw_txn) => signing_message(raw_txn)?,
+        };
+        let txn_sign_result = time_turnkey(
+            "sign_raw_payload",
+            self.turnkey.sign_raw_payload(
+                self.organization_id.clone(),
+                self.turnkey.current_timestamp(),
+                SignRawPayloadIntentV2 {
+                    sign_with: address.to_string(),
+                    payload: hex::encode(signing_message),
+                    encoding: PayloadEncoding::Hexadecimal,
+                    hash_function: HashFunction::NotApplicable,
+                },
+            ),
+        )
+        .await?;
+
+        let r_hex = format!("{:0>64}", txn_sign_result.r);
+        let s_hex = format!("{:0>64}", txn_sign_result.s);
+
+        let txn_signature_hex = format!("{}{}", r_hex, s_hex);
+
+        assert_eq!(txn_signature_hex.len(), 128);
+
+        let sig_bytes = hex::decode(txn_signature_hex).unwrap();
+
+        assert_eq!(sig_bytes.len(), 64);
+
+        Ok(Ed25519Signature::try_from(sig_bytes.as_slice())?)
+    }
+
+    async fn export_private_key(&self, address: &str) -> anyhow::Result<String> {
+        let mut export_client = ExportClient::new(&QuorumPublicKey::production_signer());
+        let export_wallet_result = time_turnkey(
+            "export_wallet_account",
+            self.turnkey.export_wallet_account(
+                self.organization_id.clone(),
+                self.turnkey.current_timestamp(),
+                ExportWalletAccountIntent {
+                    address: address.into(),
+                    target_public_key: export_client.target_public_key()?,
+                },
+            ),
+        )
+        .await?;
+
+        let export_bundle = export_wallet_result.export_bundle;
+        let private_key_bytes =
+            export_client.decrypt_private_key(export_bundle, self.organization_id.clone())?;
+
+        let private_key = hex::encode(&private_key_bytes);
+        Ok(private_key)
+    }
+}
@@ -1,115 +1,90 @@
-// // use anyhow::Context;
-// use std::sync::Arc;
-// use teloxide::{
-//     prelude::*,
-//     types::ParseMode,
-// };
-// use uuid::Uuid;
+use std::sync::Arc;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
+};
 
-// use crate::{
-//     cache::Cache,
-//     db::{tokens::Token, wallets::Wallet},
-//     telegram_bot::{
-//         TelegramBot, actions::CallbackQueryProcessor, build_text_for_contact_support,
-//         escape_markdown_v2,
-//     },
-//     utils::{database_connection::get_db_connection, view_requests::view_fa_balance_request},
-// };
+use crate::{
+    cache::Cache,
+    models::db::{subaccounts::SubAccount, users::User},
+    telegram_bot::{
+        TelegramBot,
+        actions::{CallbackQueryProcessor, UserAction},
+    },
+    utils::database_connection::get_db_connection,
+};
 
-// pub struct Accounts {
-//     pub user_id: Uuid,
-//     pub token: String,
-// }
+pub struct Accounts;
 
-// #[async_trait::async_trait]
-// impl CallbackQueryProcessor for Accounts {
-//     async fn process(
-//         &self,
-//         cfg: Arc<TelegramBot<Cache>>,
-//         bot: Bot,
-//         callback_query: CallbackQuery,
-//     ) -> anyhow::Result<()> {
-//         let msg = callback_query
-//             .message
-//             .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for Accounts {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let tg_id = from.id.0 as i64;
+        let chat_id = msg.chat().id;
 
-//         let mut conn = get_db_connection(&cfg.pool).await?;
-//         let wallets = Wallet::get_wallets_by_user_id(self.user_id, &mut conn).await?;
-//         let token_opt = Token::get_token_by_symbol(self.token.clone(), &mut conn).await?;
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
+        let subaccounts = cfg.subaccounts(&db_user, &mut conn).await?;
 
-//         let db_token = if let Some(token) = token_opt {
-//             token
-//         } else {
-//             bot.send_message(msg.chat().id, build_text_for_contact_support())
-//                 .await?;
-//             return Ok(());
-//         };
+        bot.send_message(chat_id, build_text_for_accounts(&subaccounts))
+            .reply_markup(build_keyboard_for_accounts(&subaccounts))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+}
 
-//         let pending_message = bot
-//             .send_message(msg.chat().id, "Fetching wallets...")
-//             .await?;
-//         let mut text = String::new();
+pub fn build_text_for_accounts(subaccounts: &[SubAccount]) -> String {
+    let mut text = String::from("🗂 <b>Decibel Accounts</b>\n\n");
+    for (idx, subaccount) in subaccounts.iter().enumerate() {
+        text.push_str(&format!(
+            "{}. <code>{}</code>{}\n",
+            idx + 1,
+            subaccount.address,
+            if subaccount.is_primary() {
+                " - Primary"
+            } else {
+                ""
+            }
+        ));
+    }
+    text.push_str(
+        "\nThe primary account is used for deposits and degen mode trades. \
+        Order confirmations let you pick any of your accounts.",
+    );
+    text
+}
 
-//         if !wallets.is_empty() {
-//             for (idx, wallet) in wallets.iter().enumerate() {
-//                 let request = view_fa_balance_request(&db_token.address, &wallet.address)?;
-//                 let response = cfg.aptos_client.view(&request).await?;
-//                 let balance_json = response.get(0).cloned().unwrap_or(serde_json::json!("0"));
-
-//                 let balance = serde_json::from_value::<String>(balance_json)?.parse::<u64>()?;
-
-//                 text.push_str(&build_text_for_wallet(
-//                     idx + 1,
-//                     &wallet.address,
-//                     wallet.is_primary,
-//                     balance / 10u64.pow(db_token.decimals as u32),
-//                     &db_token.symbol,
-//                 ));
-//                 text.push_str("\n\n");
-//             }
-//         }
-
-//         bot.edit_message_text(msg.chat().id, pending_message.id, escape_markdown_v2(&text))
-//             // .reply_markup(build_keyboard_for_accounts())
-//             .parse_mode(ParseMode::MarkdownV2)
-//             .await?;
-//         Ok(())
-//     }
-// }
-
-// fn build_text_for_wallet(
-//     idx: usize,
-//     address: &str,
-//     is_primary: bool,
-//     balance: u64,
-//     symbol: &str,
-// ) -> String {
-//     format!(
-//         "{}. {} {}\n\n\
-//         Address: `{}`\n\
-//         Balance: {} {}\n\
-//         Secured by Turnkey",
-//         idx,
-//         &address[..5],
-//         if is_primary { "- Primary" } else { "" },
-//         address,
-//         balance,
-//         symbol
-//     )
-// }
-
-// // fn build_keyboard_for_accounts() -> InlineKeyboardMarkup {
-// //     InlineKeyboardMarkup::new(
-// //         vec![
-// //             vec![
-// //                 InlineKeyboardButton::callback("Set primary account", "todo")
-// //             ],
-// //             vec![
-// //                 InlineKeyboardButton::callback("New Decibel Account", "todo")
-// //             ],
-// //             vec![
-// //                 InlineKeyboardButton::callback("Manage Account", "todo")
-// //             ],
-// //         ]
-// //     )
-// // }
+pub fn build_keyboard_for_accounts(subaccounts: &[SubAccount]) -> InlineKeyboardMarkup {
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = subaccounts
+        .iter()
+        .enumerate()
+        .filter(|(_, subaccount)| !subaccount.is_primary())
+        .map(|(idx, subaccount)| {
+            vec![InlineKeyboardButton::callback(
+                format!("Set primary account #{}", idx + 1),
+                UserAction::SetPrimarySubaccount { id: subaccount.id }.to_string(),
+            )]
+        })
+        .collect();
+    keyboard.push(vec![InlineKeyboardButton::callback(
+        "➕ New Decibel Account",
+        UserAction::CreateSubaccount.to_string(),
+    )]);
+    keyboard.push(vec![InlineKeyboardButton::callback(
+        "🔻 Close",
+        UserAction::Cancel.to_string(),
+    )]);
+    InlineKeyboardMarkup::new(keyboard)
+}
@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
+    utils::html,
+};
+
+use crate::{
+    cache::Cache,
+    models::db::{
+        api_keys::{ApiKey, SCOPE_READ, SCOPE_TRADE},
+        users::User,
+    },
+    telegram_bot::{
+        TelegramBot,
+        actions::{CallbackQueryProcessor, UserAction},
+    },
+    utils::database_connection::get_db_connection,
+};
+
+pub struct ApiKeys;
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for ApiKeys {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let tg_id = from.id.0 as i64;
+        let chat_id = msg.chat().id;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
+        let api_keys = ApiKey::get_by_user_id(db_user.id, &mut conn).await?;
+
+        bot.send_message(chat_id, build_text_for_api_keys(&api_keys))
+            .reply_markup(build_keyboard_for_api_keys(&api_keys))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+}
+
+pub fn build_text_for_api_keys(api_keys: &[ApiKey]) -> String {
+    let mut text = String::from("🔐 <b>API keys</b>\n\n");
+    if api_keys.is_empty() {
+        text.push_str("No API keys yet.\n");
+    }
+    for (idx, api_key) in api_keys.iter().enumerate() {
+        text.push_str(&format!(
+            "{}. <b>{}</b> <code>{}</code> - {}, {} req/min{}\n",
+            idx + 1,
+            // names are picked by the user through the API
+            html::escape(&api_key.name),
+            api_key.key_id,
+            api_key.scope,
+            api_key.rate_limit_per_minute,
+            api_key
+                .last_used_at
+                .map(|last_used_at| format!(", last used {}", last_used_at.format("%Y-%m-%d")))
+                .unwrap_or_default()
+        ));
+    }
+    text.push_str(
+        "\nKeys let scripts call the API with signed requests. \
+        Read keys only see your accounts, trade keys also place and cancel orders.",
+    );
+    text
+}
+
+pub fn build_keyboard_for_api_keys(api_keys: &[ApiKey]) -> InlineKeyboardMarkup {
+    let mut keyboard: Vec<Vec<InlineKeyboardButton>> = api_keys
+        .iter()
+        .enumerate()
+        .map(|(idx, api_key)| {
+            vec![InlineKeyboardButton::callback(
+                format!("🗑 Revoke key #{}", idx + 1),
+                UserAction::RevokeApiKey { id: api_key.id }.to_string(),
+            )]
+        })
+        .collect();
+    keyboard.push(vec![
+        InlineKeyboardButton::callback(
+            "➕ Read key",
+            UserAction::CreateApiKey {
+                scope: SCOPE_READ.to_string(),
+            }
+            .to_string(),
+        ),
+        InlineKeyboardButton::callback(
+            "➕ Trade key",
+            UserAction::CreateApiKey {
+                scope: SCOPE_TRADE.to_string(),
+            }
+            .to_string(),
+        ),
+    ]);
+    keyboard.push(vec![InlineKeyboardButton::callback(
+        "🔻 Close",
+        UserAction::Cancel.to_string(),
+    )]);
+    InlineKeyboardMarkup::new(keyboard)
+}
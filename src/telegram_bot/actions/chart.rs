@@ -100,7 +100,7 @@
 //         let start = end - 86400 * 1000;
 //         let url = format!(
 //             "{}/api/v1/candlesticks?market={}&interval={}&startTime={}&endTime={}",
-//             _cfg.config.decibel_url,
+//             _cfg.config.network().decibel_url,
 //             market.market_addr,
 //             &self.interval,
 //             start,
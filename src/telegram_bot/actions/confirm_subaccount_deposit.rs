@@ -1,6 +1,6 @@
 use bigdecimal::BigDecimal;
-use std::{str::FromStr, sync::Arc};
-use teloxide::{prelude::*, types::ParseMode};
+use std::sync::Arc;
+use teloxide::prelude::*;
 
 use crate::{
     cache::Cache,
@@ -8,7 +8,6 @@ use crate::{
     telegram_bot::{TelegramBot, actions::CallbackQueryProcessor},
     utils::{
         database_connection::get_db_connection, decibel_transaction::deposit_to_subaccount_at,
-        view_requests::view_primary_subaccount,
     },
 };
 
@@ -38,43 +37,37 @@ impl CallbackQueryProcessor for ConfirmSubaccountDeposit {
                 anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
             })?;
         // request primary
-        let request = view_primary_subaccount(&cfg.config.contract_address, &db_user.address)?;
-        let response = cfg.aptos_client.view(&request).await?;
-        let value = response
-            .get(0)
-            .ok_or_else(|| anyhow::anyhow!("Primary subaccount not found"))?;
-        let subaccount = value
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Expected primary subaccount as string"))?;
+        let subaccount = cfg.primary_subaccount(&db_user, &mut conn).await?;
         // balance
-        let scaled_amount = &self.amount * BigDecimal::from_str("1000000")?;
-        let amount_u64 = scaled_amount.with_scale(0).to_string().parse::<u64>()?;
+        let token = cfg.collateral_token(&mut conn).await?;
+        let amount_u64 = token.to_base_units(&self.amount)?;
         let payload = deposit_to_subaccount_at(
-            &cfg.config.contract_address,
-            subaccount,
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
+            &cfg.config.network().contract_address,
+            &subaccount.address,
+            &token.address,
             amount_u64,
         )?;
-        let txn = cfg
-            .aptos_client
-            .sign_txn_with_turnkey_and_fee_payer(&db_user.address, &db_user.public_key, payload)
-            .await?;
+        let txn = cfg.sign_txn(&db_user, payload, &mut conn).await?;
 
-        let txn_hash = cfg.aptos_client.submit_transaction_and_wait(txn).await?;
+        let processing_message = bot.send_message(chat_id, "⏳ Processing deposit…").await?;
+        cfg.enqueue_transaction(
+            &db_user,
+            "deposit",
+            format!(
+                "Deposited {} {} to your subaccount",
+                self.amount, token.symbol
+            ),
+            txn,
+            &processing_message,
+            &mut conn,
+        )
+        .await?;
 
         tracing::info!(
-            "{} deposited to subaccount {}: https://explorer.aptoslabs.com/txn/{}?network=decibel",
+            "{} queued deposit to subaccount {}",
             db_user.address,
-            subaccount,
-            txn_hash.clone()
+            subaccount.address
         );
-
-        bot.send_message(
-            chat_id,
-            format!("✅ Txn completed! <a href='https://explorer.aptoslabs.com/txn/{}?network=decibel'>View Txn</a>", txn_hash),
-        )
-        .parse_mode(ParseMode::Html)
-        .await?;
         Ok(())
     }
 }
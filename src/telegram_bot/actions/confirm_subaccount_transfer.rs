@@ -0,0 +1,129 @@
+use bigdecimal::BigDecimal;
+use reqwest::Client;
+use std::sync::Arc;
+use teloxide::prelude::*;
+
+use crate::{
+    cache::Cache,
+    models::db::users::User,
+    telegram_bot::{TelegramBot, actions::CallbackQueryProcessor},
+    utils::{
+        database_connection::get_db_connection,
+        decibel_api::fetch_account_overview,
+        decibel_transaction::{deposit_to_subaccount_at, withdraw_from_subaccount},
+    },
+};
+
+pub struct ConfirmSubaccountTransfer {
+    pub from: u8,
+    pub to: Option<u8>,
+    pub amount: BigDecimal,
+}
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for ConfirmSubaccountTransfer {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let chat_id = msg.chat().id;
+        let tg_id = from.id.0 as i64;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
+            })?;
+        let source = cfg.subaccount_at(&db_user, self.from, &mut conn).await?;
+        let destination = match self.to {
+            Some(to) => Some(cfg.subaccount_at(&db_user, to, &mut conn).await?),
+            None => None,
+        };
+
+        let token = cfg.collateral_token(&mut conn).await?;
+
+        // margin may have changed since the amount was entered
+        let withdrawable = fetch_account_overview(
+            &Client::new(),
+            &cfg.config.network().decibel_url,
+            &source.address,
+        )
+        .await?
+        .withdrawable(token.decimals);
+        if self.amount > withdrawable {
+            return Err(anyhow::anyhow!(
+                "❌ Only {} {} is withdrawable, the rest backs your open positions",
+                withdrawable,
+                token.symbol
+            ));
+        }
+
+        let processing_message = bot.send_message(chat_id, "Processing transfer...").await?;
+
+        let amount_u64 = token.to_base_units(&self.amount)?;
+        let payload = withdraw_from_subaccount(
+            &cfg.config.network().contract_address,
+            &source.address,
+            &token.address,
+            amount_u64,
+        )?;
+        let txn = cfg.sign_txn(&db_user, payload, &mut conn).await?;
+
+        // moving between subaccounts goes through the wallet, so the deposit is signed once the
+        // withdrawal landed
+        let kind = match destination {
+            Some(destination) => {
+                let payload = deposit_to_subaccount_at(
+                    &cfg.config.network().contract_address,
+                    &destination.address,
+                    &token.address,
+                    amount_u64,
+                )?;
+                cfg.enqueue_transaction_then(
+                    &db_user,
+                    (
+                        "withdraw",
+                        format!("Withdrew {} {} to your wallet", self.amount, token.symbol),
+                        txn,
+                    ),
+                    (
+                        "transfer",
+                        format!("Moved {} {} between accounts", self.amount, token.symbol),
+                        payload,
+                    ),
+                    &processing_message,
+                    &mut conn,
+                )
+                .await?;
+                "transfer"
+            }
+            None => {
+                cfg.enqueue_transaction(
+                    &db_user,
+                    "withdraw",
+                    format!("Withdrew {} {} to your wallet", self.amount, token.symbol),
+                    txn,
+                    &processing_message,
+                    &mut conn,
+                )
+                .await?;
+                "withdraw"
+            }
+        };
+
+        tracing::info!(
+            "{} queued {} from subaccount {}",
+            db_user.address,
+            kind,
+            source.address
+        );
+        Ok(())
+    }
+}
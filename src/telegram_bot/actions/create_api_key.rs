@@ -0,0 +1,64 @@
+use std::sync::Arc;
+use teloxide::{prelude::*, types::ParseMode};
+
+use crate::{
+    cache::Cache,
+    models::db::{api_keys::ApiKey, users::User},
+    telegram_bot::{
+        TelegramBot,
+        actions::{
+            CallbackQueryProcessor,
+            api_keys::{build_keyboard_for_api_keys, build_text_for_api_keys},
+        },
+    },
+    utils::{api_keys::create_api_key, database_connection::get_db_connection},
+};
+
+pub struct CreateApiKey {
+    pub scope: String,
+}
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for CreateApiKey {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let tg_id = from.id.0 as i64;
+        let chat_id = msg.chat().id;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
+        let name = format!(
+            "{} key {}",
+            self.scope,
+            chrono::Utc::now().format("%Y-%m-%d %H:%M")
+        );
+        let (api_key, secret) =
+            create_api_key(&cfg.config, db_user.id, &name, &self.scope, &mut conn).await?;
+
+        let api_keys = ApiKey::get_by_user_id(db_user.id, &mut conn).await?;
+        bot.edit_message_text(chat_id, msg.id(), build_text_for_api_keys(&api_keys))
+            .reply_markup(build_keyboard_for_api_keys(&api_keys))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        bot.send_message(
+            chat_id,
+            format!(
+                "🔐 API key created\n\nKey: <code>{}</code>\nSecret: <tg-spoiler><code>{}</code></tg-spoiler>\n\nThe secret is not shown again. Sign each request with it as described in the API docs, and delete this message once it is saved",
+                api_key.key_id, secret
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use teloxide::{prelude::*, types::ParseMode};
+
+use crate::{
+    cache::Cache,
+    models::db::{subaccounts::NewSubAccount, users::User},
+    schema::subaccounts,
+    telegram_bot::{
+        TelegramBot,
+        actions::{
+            CallbackQueryProcessor,
+            accounts::{build_keyboard_for_accounts, build_text_for_accounts},
+        },
+    },
+    utils::{
+        database_connection::get_db_connection, db_execution::execute_with_better_error,
+        decibel_transaction::create_new_subaccount,
+    },
+};
+
+pub struct CreateSubaccount;
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for CreateSubaccount {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let tg_id = from.id.0 as i64;
+        let chat_id = msg.chat().id;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
+        // make sure the default subaccount is registered before adding a new one
+        cfg.subaccounts(&db_user, &mut conn).await?;
+
+        let processing_message = bot
+            .send_message(chat_id, "Creating a new Decibel account...")
+            .await?;
+
+        let payload = create_new_subaccount(&cfg.config.network().contract_address)?;
+        let txn = cfg.sign_txn(&db_user, payload, &mut conn).await?;
+        let (txn_hash, events) = cfg
+            .aptos_client
+            .submit_transaction_and_wait_with_events(txn.into_inner())
+            .await?;
+
+        tracing::info!(
+            "{} created subaccount: {}",
+            db_user.address,
+            cfg.config.network().txn_url(&txn_hash)
+        );
+
+        let address = events
+            .iter()
+            .filter(|event| event.typ.to_string().contains("dex_accounts"))
+            .find_map(|event| event.data.get("subaccount").and_then(|v| v.as_str()))
+            .ok_or_else(|| anyhow::anyhow!("Subaccount address missing in transaction events"))?;
+
+        let new_subaccount =
+            NewSubAccount::to_db_subaccount(db_user.id, address.to_string(), false);
+        let query = diesel::insert_into(subaccounts::table)
+            .values(new_subaccount)
+            .on_conflict_do_nothing();
+        execute_with_better_error(&mut conn, vec![query]).await?;
+
+        let subaccounts = cfg.subaccounts(&db_user, &mut conn).await?;
+        bot.edit_message_text(
+            chat_id,
+            processing_message.id,
+            build_text_for_accounts(&subaccounts),
+        )
+        .reply_markup(build_keyboard_for_accounts(&subaccounts))
+        .parse_mode(ParseMode::Html)
+        .await?;
+        Ok(())
+    }
+}
@@ -36,7 +36,7 @@
 
 //         let wallet_name = format!("aptos-test-wallet-{}", telegram_id);
 //         let (wallet_id, wallet_address, wallet_public_key) =
-//             cfg.aptos_client.create_new_wallet_on_turnkey(&wallet_name).await?;
+//             cfg.aptos_client.create_new_wallet(&wallet_name).await?;
 //         let new_user = NewTelegramUser::to_db_user_with_custom_uuid(
 //             uuid::Uuid::new_v4(),
 //             telegram_id,
@@ -62,8 +62,8 @@
 
 //         let mut conn = get_db_connection(&cfg.pool).await?;
 
-//         let payload = delegate_trading_to(&cfg.config.contract_address, &wallet_address)?;
-//         let signed_txn = cfg.aptos_client.sign_txn_with_turnkey_and_fee_payer(
+//         let payload = delegate_trading_to(&cfg.config.network().contract_address, &wallet_address)?;
+//         let signed_txn = cfg.aptos_client.sign_txn_with_fee_payer(
 //             &wallet_address,
 //             &wallet_public_key,
 //             payload
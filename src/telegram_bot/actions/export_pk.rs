@@ -10,6 +10,7 @@ use crate::{
         TelegramBot,
         actions::{CallbackQueryProcessor, UserAction},
     },
+    utils::key_export::KeyDelivery,
 };
 
 pub struct ExportPk;
@@ -18,7 +19,7 @@ pub struct ExportPk;
 impl CallbackQueryProcessor for ExportPk {
     async fn process(
         &self,
-        _cfg: Arc<TelegramBot<Cache>>,
+        cfg: Arc<TelegramBot<Cache>>,
         bot: Bot,
         callback_query: CallbackQuery,
     ) -> anyhow::Result<()> {
@@ -27,12 +28,39 @@ impl CallbackQueryProcessor for ExportPk {
             .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
         let chat_id = msg.chat().id;
 
-        let text = "⚠️ Export Private Key\n\nYour private key gives full control over your funds.\n\nNever share it with anyone — not even admins or bots.\n\nDo you still want to export your key?";
-        let markup = InlineKeyboardMarkup::new(vec![vec![
-            InlineKeyboardButton::callback("🔑 Yes, show my key", UserAction::ShowPk.to_string()),
-            InlineKeyboardButton::callback("❌ Cancel", UserAction::Cancel.to_string()),
-        ]]);
-        bot.send_message(chat_id, text).reply_markup(markup).await?;
+        let text = "⚠️ Export Private Key\n\nYour private key gives full control over your funds.\n\nNever share it with anyone — not even admins or bots.\n\nEvery export needs your PIN. How do you want to receive your key?";
+        let mut keyboard = vec![
+            vec![InlineKeyboardButton::callback(
+                "💬 Show in chat",
+                UserAction::ShowPk {
+                    delivery: KeyDelivery::Chat,
+                }
+                .to_string(),
+            )],
+            vec![InlineKeyboardButton::callback(
+                "🔐 Encrypted to my key",
+                UserAction::ShowPk {
+                    delivery: KeyDelivery::Encrypted,
+                }
+                .to_string(),
+            )],
+        ];
+        if cfg.config.server_config.public_url.is_some() {
+            keyboard.push(vec![InlineKeyboardButton::callback(
+                "🔗 One-time link",
+                UserAction::ShowPk {
+                    delivery: KeyDelivery::Link,
+                }
+                .to_string(),
+            )]);
+        }
+        keyboard.push(vec![InlineKeyboardButton::callback(
+            "❌ Cancel",
+            UserAction::Cancel.to_string(),
+        )]);
+        bot.send_message(chat_id, text)
+            .reply_markup(InlineKeyboardMarkup::new(keyboard))
+            .await?;
         Ok(())
     }
 }
@@ -1,12 +1,11 @@
-use bigdecimal::BigDecimal;
-use std::{str::FromStr, sync::Arc};
+use std::sync::Arc;
 use teloxide::{prelude::*, types::ForceReply};
 
 use crate::{
     cache::Cache,
     models::db::users::User,
     telegram_bot::{TelegramBot, actions::CallbackQueryProcessor, states::PendingState},
-    utils::{database_connection::get_db_connection, view_requests::view_fa_balance_request},
+    utils::database_connection::get_db_connection,
 };
 
 pub struct ExternalWithdraw;
@@ -32,22 +31,14 @@ impl CallbackQueryProcessor for ExternalWithdraw {
             .ok_or_else(|| {
                 anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
             })?;
-        let request = view_fa_balance_request(
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
-            &db_user.address,
-        )?;
-        let response = cfg.aptos_client.view(&request).await?;
-        let balance_json = response.get(0).cloned().unwrap_or(serde_json::json!("0"));
-        let balance_str = serde_json::from_value::<String>(balance_json)?;
-        let balance = BigDecimal::from_str(&balance_str)?;
-        let divisor = BigDecimal::from(10u64.pow(6));
-        let usdc = balance / divisor;
+        let token = cfg.collateral_token(&mut conn).await?;
+        let usdc = cfg.token_balance(&token, &db_user.address).await?;
 
         let text = format!(
             "🌐 <b>Withdraw to External Wallet</b>\n\n\
-            Your main wallet balance: <b>{} USDC</b>\n\n\
+            Your main wallet balance: <b>{} {}</b>\n\n\
             Address: <code>{}</code>",
-            usdc, db_user.address
+            usdc, token.symbol, db_user.address
         );
 
         bot.send_message(chat_id, text)
@@ -55,7 +46,10 @@ impl CallbackQueryProcessor for ExternalWithdraw {
             .await?;
         bot.send_message(
             chat_id,
-            "Reply with the amount in USDC you want to withdraw",
+            format!(
+                "Reply with the amount in {} you want to withdraw",
+                token.symbol
+            ),
         )
         .reply_markup(ForceReply::new().selective())
         .await?;
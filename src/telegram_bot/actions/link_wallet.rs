@@ -0,0 +1,71 @@
+use std::sync::Arc;
+use teloxide::{prelude::*, types::ParseMode};
+
+use crate::{
+    cache::Cache,
+    models::db::{link_codes::NewLinkCode, users::User},
+    schema::link_codes,
+    telegram_bot::{TelegramBot, actions::CallbackQueryProcessor},
+    utils::{
+        account_link::{LINK_CODE_TTL_SECS, new_link_code},
+        database_connection::get_db_connection,
+        db_execution::execute_with_better_error,
+    },
+};
+
+pub struct LinkWallet;
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for LinkWallet {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let tg_id = from.id.0 as i64;
+        let chat_id = msg.chat().id;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
+        if let Some(connected_wallet) = &db_user.connected_wallet {
+            bot.send_message(
+                chat_id,
+                format!(
+                    "🔗 Your account is linked to wallet <code>{}</code>",
+                    connected_wallet
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
+
+        let (code, code_hash) = new_link_code();
+        let query = diesel::insert_into(link_codes::table).values(NewLinkCode {
+            code_hash,
+            user_id: db_user.id,
+            expires_at: chrono::Utc::now().naive_utc()
+                + chrono::Duration::seconds(LINK_CODE_TTL_SECS),
+        });
+        execute_with_better_error(&mut conn, vec![query]).await?;
+
+        bot.send_message(
+            chat_id,
+            format!(
+                "🔗 Enter this code in the terminal after connecting your wallet:\n\n<code>{}</code>\n\nIt expires in {} minutes. If the wallet already has an account, it is merged into this one",
+                code,
+                LINK_CODE_TTL_SECS / 60
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,5 +1,6 @@
 pub mod accounts;
 pub mod add_to_group;
+pub mod api_keys;
 pub mod ask_order_amount;
 pub mod balances;
 pub mod cancel;
@@ -7,18 +8,26 @@ pub mod change_degen_mode;
 pub mod change_notification;
 pub mod chart;
 pub mod confirm_subaccount_deposit;
+pub mod confirm_subaccount_transfer;
+pub mod create_api_key;
+pub mod create_subaccount;
 pub mod create_trading_account;
 pub mod deposit_to_subaccount;
 pub mod export_pk;
 pub mod external_withdraw;
 pub mod join_existing_clan;
+pub mod link_wallet;
 pub mod open_position;
 pub mod order_leverage;
 pub mod place_limit_order;
 pub mod place_order;
+pub mod revoke_api_key;
+pub mod select_subaccount_transfer;
+pub mod set_primary_subaccount;
 pub mod show_pk;
 pub mod slippage;
 pub mod stats;
+pub mod subaccount_transfer;
 pub mod transfer;
 pub mod update_slippage;
 
@@ -28,7 +37,7 @@ use bigdecimal::BigDecimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{cache::Cache, telegram_bot::TelegramBot};
+use crate::{cache::Cache, telegram_bot::TelegramBot, utils::key_export::KeyDelivery};
 
 #[async_trait::async_trait]
 pub trait CallbackQueryProcessor {
@@ -54,6 +63,7 @@ pub enum UserAction {
         is_long: bool,
         leverage: u8,
         amount: BigDecimal,
+        subaccount: u8,
     },
     Cancel,
     PlaceLimitOrder {
@@ -62,9 +72,12 @@ pub enum UserAction {
         leverage: u8,
         amount: BigDecimal,
         is_long: bool,
+        subaccount: u8,
     },
     ExportPk,
-    ShowPk,
+    ShowPk {
+        delivery: KeyDelivery,
+    },
     ChangeNotificationPreferences,
     Slippage,
     UpdateSlippage,
@@ -77,6 +90,31 @@ pub enum UserAction {
         amount: BigDecimal,
     },
     ExternalWithdraw,
+    Accounts,
+    CreateSubaccount,
+    SetPrimarySubaccount {
+        id: Uuid,
+    },
+    SubaccountTransfer {
+        to_wallet: bool,
+    },
+    SelectSubaccountTransfer {
+        from: u8,
+        to: Option<u8>,
+    },
+    ConfirmSubaccountTransfer {
+        from: u8,
+        to: Option<u8>,
+        amount: BigDecimal,
+    },
+    LinkWallet,
+    ApiKeys,
+    CreateApiKey {
+        scope: String,
+    },
+    RevokeApiKey {
+        id: Uuid,
+    },
 }
 
 impl ToString for UserAction {
@@ -96,7 +134,11 @@ impl ToString for UserAction {
                 is_long,
                 leverage,
                 amount,
-            } => format!("place|{}|{}|{}|{}", market_name, is_long, leverage, amount),
+                subaccount,
+            } => format!(
+                "place|{}|{}|{}|{}|{}",
+                market_name, is_long, leverage, amount, subaccount
+            ),
             UserAction::Cancel => "cancel".to_string(),
             UserAction::PlaceLimitOrder {
                 market_name,
@@ -104,12 +146,13 @@ impl ToString for UserAction {
                 leverage,
                 amount,
                 is_long,
+                subaccount,
             } => format!(
-                "limit|{}|{}|{}|{}|{}",
-                market_name, price, leverage, amount, is_long
+                "limit|{}|{}|{}|{}|{}|{}",
+                market_name, price, leverage, amount, is_long, subaccount
             ),
             UserAction::ExportPk => "export_pk".to_string(),
-            UserAction::ShowPk => "show_pk".to_string(),
+            UserAction::ShowPk { delivery } => format!("show_pk|{}", delivery),
             UserAction::ChangeNotificationPreferences => "change_notification".to_string(),
             UserAction::Slippage => "slippage".to_string(),
             UserAction::ChangeDegenMode { user_id, to } => {
@@ -121,6 +164,23 @@ impl ToString for UserAction {
                 format!("confirm_dep_to_sub|{}", amount)
             }
             UserAction::ExternalWithdraw => "external_withdraw".to_string(),
+            UserAction::Accounts => "accounts".to_string(),
+            UserAction::CreateSubaccount => "new_sub".to_string(),
+            UserAction::SetPrimarySubaccount { id } => format!("set_primary_sub|{}", id),
+            UserAction::SubaccountTransfer { to_wallet } => format!("sub_tx|{}", to_wallet),
+            UserAction::SelectSubaccountTransfer { from, to } => {
+                format!("sub_tx_sel|{}|{}", from, transfer_target_to_str(to))
+            }
+            UserAction::ConfirmSubaccountTransfer { from, to, amount } => format!(
+                "sub_tx_ok|{}|{}|{}",
+                from,
+                transfer_target_to_str(to),
+                amount
+            ),
+            UserAction::LinkWallet => "link_wallet".to_string(),
+            UserAction::ApiKeys => "api_keys".to_string(),
+            UserAction::CreateApiKey { scope } => format!("new_api_key|{}", scope),
+            UserAction::RevokeApiKey { id } => format!("rm_api_key|{}", id),
         }
     }
 }
@@ -143,35 +203,42 @@ impl FromStr for UserAction {
                     balance,
                 })
             }
-            "place" if parts.len() == 5 => {
+            "place" if parts.len() == 6 => {
                 let market_name = parts[1].to_string();
                 let is_long = parts[2].parse::<bool>().map_err(|_| ())?;
                 let leverage = parts[3].parse::<u8>().map_err(|_| ())?;
                 let amount = BigDecimal::from_str(&parts[4].to_string()).map_err(|_| ())?;
+                let subaccount = parts[5].parse::<u8>().map_err(|_| ())?;
                 Ok(UserAction::PlaceOrder {
                     market_name,
                     is_long,
                     leverage,
                     amount,
+                    subaccount,
                 })
             }
             "cancel" => Ok(UserAction::Cancel),
-            "limit" if parts.len() == 6 => {
+            "limit" if parts.len() == 7 => {
                 let market_name = parts[1].to_string();
                 let price = BigDecimal::from_str(&parts[2].to_string()).map_err(|_| ())?;
                 let leverage = parts[3].parse::<u8>().map_err(|_| ())?;
                 let amount = BigDecimal::from_str(&parts[4].to_string()).map_err(|_| ())?;
                 let is_long = parts[5].parse::<bool>().map_err(|_| ())?;
+                let subaccount = parts[6].parse::<u8>().map_err(|_| ())?;
                 Ok(UserAction::PlaceLimitOrder {
                     market_name,
                     price,
                     leverage,
                     amount,
                     is_long,
+                    subaccount,
                 })
             }
             "export_pk" => Ok(UserAction::ExportPk),
-            "show_pk" => Ok(UserAction::ShowPk),
+            "show_pk" if parts.len() == 2 => {
+                let delivery = parts[1].parse::<KeyDelivery>()?;
+                Ok(UserAction::ShowPk { delivery })
+            }
             "change_notification" => Ok(UserAction::ChangeNotificationPreferences),
             "slippage" => Ok(UserAction::Slippage),
             "change_degen" if parts.len() == 3 => {
@@ -186,7 +253,52 @@ impl FromStr for UserAction {
                 Ok(UserAction::ConfirmSubaccountDeposit { amount })
             }
             "external_withdraw" => Ok(UserAction::ExternalWithdraw),
+            "accounts" => Ok(UserAction::Accounts),
+            "new_sub" => Ok(UserAction::CreateSubaccount),
+            "set_primary_sub" if parts.len() == 2 => {
+                let id = Uuid::parse_str(parts[1]).map_err(|_| ())?;
+                Ok(UserAction::SetPrimarySubaccount { id })
+            }
+            "sub_tx" if parts.len() == 2 => {
+                let to_wallet = parts[1].parse::<bool>().map_err(|_| ())?;
+                Ok(UserAction::SubaccountTransfer { to_wallet })
+            }
+            "sub_tx_sel" if parts.len() == 3 => {
+                let from = parts[1].parse::<u8>().map_err(|_| ())?;
+                let to = transfer_target_from_str(parts[2])?;
+                Ok(UserAction::SelectSubaccountTransfer { from, to })
+            }
+            "sub_tx_ok" if parts.len() == 4 => {
+                let from = parts[1].parse::<u8>().map_err(|_| ())?;
+                let to = transfer_target_from_str(parts[2])?;
+                let amount = BigDecimal::from_str(parts[3]).map_err(|_| ())?;
+                Ok(UserAction::ConfirmSubaccountTransfer { from, to, amount })
+            }
+            "link_wallet" => Ok(UserAction::LinkWallet),
+            "api_keys" => Ok(UserAction::ApiKeys),
+            "new_api_key" if parts.len() == 2 => Ok(UserAction::CreateApiKey {
+                scope: parts[1].to_string(),
+            }),
+            "rm_api_key" if parts.len() == 2 => {
+                let id = Uuid::parse_str(parts[1]).map_err(|_| ())?;
+                Ok(UserAction::RevokeApiKey { id })
+            }
             _ => Err(()),
         }
     }
 }
+
+/// `w` stands for the user's wallet, otherwise the subaccount index
+fn transfer_target_to_str(to: &Option<u8>) -> String {
+    match to {
+        Some(idx) => idx.to_string(),
+        None => "w".to_string(),
+    }
+}
+
+fn transfer_target_from_str(s: &str) -> Result<Option<u8>, ()> {
+    match s {
+        "w" => Ok(None),
+        idx => idx.parse::<u8>().map(Some).map_err(|_| ()),
+    }
+}
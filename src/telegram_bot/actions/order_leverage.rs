@@ -10,6 +10,7 @@ use teloxide::{
 use crate::{
     cache::Cache,
     telegram_bot::{TelegramBot, actions::CallbackQueryProcessor, states::PendingState},
+    utils::database_connection::get_db_connection,
 };
 
 pub struct OrderLeverage {
@@ -31,6 +32,8 @@ impl CallbackQueryProcessor for OrderLeverage {
             .message
             .ok_or_else(|| anyhow::anyhow!("Message is missing in callback query"))?;
         let chat_id = msg.chat().id;
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let token = cfg.collateral_token(&mut conn).await?;
 
         {
             let mut state = cfg.state.lock().await;
@@ -48,10 +51,10 @@ impl CallbackQueryProcessor for OrderLeverage {
         bot.send_message(
             chat_id,
             format!(
-                "<b>💵 Enter the USDC amount you want to trade</b>\n\n\
+                "<b>💵 Enter the {} amount you want to trade</b>\n\n\
             Example: <code>{}</code>\n\
-            (This is in USDC — make sure you have enough balance in your wallet.)",
-                self.balance
+            (This is in {} — make sure you have enough balance in your wallet.)",
+                token.symbol, self.balance, token.symbol
             ),
         )
         .parse_mode(ParseMode::Html)
@@ -1,23 +1,13 @@
-use std::{str::FromStr, sync::Arc};
+use std::sync::Arc;
 
 use bigdecimal::BigDecimal;
-use teloxide::{
-    Bot,
-    payloads::SendMessageSetters,
-    prelude::Requester,
-    types::{CallbackQuery, ParseMode},
-};
+use teloxide::{Bot, prelude::Requester, types::CallbackQuery};
 
 use crate::{
     cache::{Cache, ICache},
     models::db::users::User,
     telegram_bot::{TelegramBot, actions::CallbackQueryProcessor},
-    utils::{
-        database_connection::get_db_connection,
-        decibel_transaction::{deposit_to_subaccount_at, place_order_to_subaccount},
-        perps_math::{notional_price, position_size, position_value},
-        view_requests::view_primary_subaccount,
-    },
+    utils::{database_connection::get_db_connection, orders::OrderParams},
 };
 
 pub struct PlaceLimitOrder {
@@ -26,6 +16,7 @@ pub struct PlaceLimitOrder {
     pub is_long: bool,
     pub leverage: u8,
     pub amount: BigDecimal,
+    pub subaccount: u8,
 }
 
 #[async_trait::async_trait]
@@ -58,86 +49,43 @@ impl CallbackQueryProcessor for PlaceLimitOrder {
             .ok_or_else(|| {
                 anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
             })?;
-        let request = view_primary_subaccount(&cfg.config.contract_address, &db_user.address)?;
-        let response = cfg.aptos_client.view(&request).await?;
-        let value = response
-            .get(0)
-            .ok_or_else(|| anyhow::anyhow!("Primary subaccount not found"))?;
-        let subaccount = value
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("Expected primary subaccount as string"))?;
-        let entry_price = asset_context.mark_price.clone();
-        let notional_price = notional_price(&self.amount, self.leverage);
-        let position_size = position_size(&notional_price, &entry_price);
-        let order_size = position_value(&position_size, &entry_price);
-
-        let scaled_price = &self.price * BigDecimal::from_str("100000000")?;
-        let price = scaled_price.with_scale(0).to_string().parse::<u64>()?;
-        // size
-        let rounded_size = order_size.with_scale(2);
-        let scaled_size = &rounded_size * BigDecimal::from_str("100000")?;
-        let size = scaled_size.with_scale(0).to_string().parse::<u64>()?;
-        // deposit amount to subaccount
-        let scaled_amount = &self.amount * BigDecimal::from_str("1000000")?;
-        let amt = scaled_amount.with_scale(0).to_string().parse::<u64>()?;
-        let payload = deposit_to_subaccount_at(
-            &cfg.config.contract_address,
-            subaccount,
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
-            amt,
-        )?;
-        let txn = cfg
-            .aptos_client
-            .sign_txn_with_turnkey_and_fee_payer(&db_user.address, &db_user.public_key, payload)
+        let subaccount = cfg
+            .subaccount_at(&db_user, self.subaccount, &mut conn)
             .await?;
-
-        let txn_hash = cfg.aptos_client.submit_transaction_and_wait(txn).await?;
-
-        tracing::info!(
-            "{} deposited to subaccount {}: https://explorer.aptoslabs.com/txn/{}?network=decibel",
-            db_user.address,
-            subaccount,
-            txn_hash.clone()
-        );
-        let payload = place_order_to_subaccount(
-            &cfg.config.contract_address,
-            subaccount,
-            &market.market_addr,
-            price,
-            size,
-            self.is_long,
-            2,
-            false,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-            None,
-        )?;
-        let txn = cfg
-            .aptos_client
-            .sign_txn_with_turnkey_and_fee_payer(&db_user.address, &db_user.public_key, payload)
+        let order = OrderParams {
+            is_long: self.is_long,
+            leverage: self.leverage,
+            amount: self.amount.clone(),
+            limit_price: Some(self.price.clone()),
+        };
+        let txns = cfg
+            .trading
+            .place_order(
+                &db_user,
+                &subaccount,
+                &market,
+                &asset_context,
+                &order,
+                &mut conn,
+            )
             .await?;
 
-        let txn_hash = cfg.aptos_client.submit_transaction_and_wait(txn).await?;
+        let token = cfg.collateral_token(&mut conn).await?;
+        let processing_message = bot.send_message(chat_id, "⏳ Placing your order…").await?;
+        cfg.enqueue_order(
+            &db_user,
+            order.summary(&market.market_name, &self.price, &token.symbol),
+            txns,
+            &processing_message,
+            &mut conn,
+        )
+        .await?;
 
         tracing::info!(
-            "{} placed order to subaccount {}: https://explorer.aptoslabs.com/txn/{}?network=decibel",
+            "{} queued order to subaccount {}",
             db_user.address,
-            subaccount,
-            txn_hash.clone()
+            subaccount.address
         );
-
-        let order_type = if self.is_long { "long" } else { "short" };
-        bot.send_message(
-            chat_id,
-            format!("✅ Trade opened! <b>{} {} {}x</b> for <b>{} USDC</b> at <b>${}</b> <a href='https://explorer.aptoslabs.com/txn/{}?network=decibel'>View Txn</a>", self.market_name, order_type.to_uppercase(), self.leverage, self.amount, self.price.clone(), txn_hash),
-        )
-        .parse_mode(ParseMode::Html)
-        .await?;
         Ok(())
     }
 }
@@ -0,0 +1,84 @@
+use reqwest::Client;
+use std::sync::Arc;
+use teloxide::{
+    prelude::*,
+    types::{ForceReply, ParseMode},
+};
+
+use crate::{
+    cache::Cache,
+    models::db::users::User,
+    telegram_bot::{TelegramBot, actions::CallbackQueryProcessor, states::PendingState},
+    utils::{database_connection::get_db_connection, decibel_api::fetch_account_overview},
+};
+
+pub struct SelectSubaccountTransfer {
+    pub from: u8,
+    pub to: Option<u8>,
+}
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for SelectSubaccountTransfer {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let chat_id = msg.chat().id;
+        let tg_id = from.id.0 as i64;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
+            })?;
+        let source = cfg.subaccount_at(&db_user, self.from, &mut conn).await?;
+        let destination = match self.to {
+            Some(to) => cfg.subaccount_at(&db_user, to, &mut conn).await?.address,
+            None => db_user.address.clone(),
+        };
+        let token = cfg.collateral_token(&mut conn).await?;
+        let withdrawable = fetch_account_overview(
+            &Client::new(),
+            &cfg.config.network().decibel_url,
+            &source.address,
+        )
+        .await?
+        .withdrawable(token.decimals);
+
+        let text = format!(
+            "💰 <b>Move funds</b>\n\n\
+            From: <code>{}</code>\n\
+            To: <code>{}</code>\n\n\
+            Withdrawable: <b>{} {}</b>",
+            source.address, destination, withdrawable, token.symbol
+        );
+        bot.send_message(chat_id, text)
+            .parse_mode(ParseMode::Html)
+            .await?;
+        bot.send_message(
+            chat_id,
+            format!("Reply with the amount in {}", token.symbol),
+        )
+        .reply_markup(ForceReply::new().selective())
+        .await?;
+        {
+            let mut state = cfg.state.lock().await;
+            state.insert(
+                chat_id,
+                PendingState::SubaccountTransferAmount {
+                    from: self.from,
+                    to: self.to,
+                    withdrawable,
+                },
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+use std::sync::Arc;
+
+use diesel::{ExpressionMethods, NullableExpressionMethods, query_dsl::methods::FilterDsl};
+use teloxide::{prelude::*, types::ParseMode};
+use uuid::Uuid;
+
+use crate::{
+    cache::Cache,
+    models::db::{subaccounts::SubAccount, users::User},
+    schema::subaccounts,
+    telegram_bot::{
+        TelegramBot,
+        actions::{
+            CallbackQueryProcessor,
+            accounts::{build_keyboard_for_accounts, build_text_for_accounts},
+        },
+    },
+    utils::{database_connection::get_db_connection, db_execution::execute_with_better_error},
+};
+
+pub struct SetPrimarySubaccount {
+    pub id: Uuid,
+}
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for SetPrimarySubaccount {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let tg_id = from.id.0 as i64;
+        let chat_id = msg.chat().id;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
+        let subaccount = SubAccount::get_by_id(self.id, &mut conn)
+            .await?
+            .filter(|subaccount| subaccount.user_id == db_user.id)
+            .ok_or_else(|| anyhow::anyhow!("Subaccount not found"))?;
+
+        let query = diesel::update(subaccounts::table.filter(subaccounts::user_id.eq(db_user.id)))
+            .set(subaccounts::is_primary.eq(subaccounts::id.eq(subaccount.id).nullable()));
+        execute_with_better_error(&mut conn, vec![query]).await?;
+
+        let subaccounts = cfg.subaccounts(&db_user, &mut conn).await?;
+        bot.edit_message_text(chat_id, msg.id(), build_text_for_accounts(&subaccounts))
+            .reply_markup(build_keyboard_for_accounts(&subaccounts))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        bot.send_message(
+            chat_id,
+            format!(
+                "⭐ Primary account set to <code>{}</code>",
+                subaccount.address
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        Ok(())
+    }
+}
@@ -1,15 +1,20 @@
-use std::{sync::Arc, time::Duration};
-use teloxide::{prelude::*, types::ParseMode};
-use tokio::time::sleep;
+use std::sync::Arc;
+use teloxide::prelude::*;
 
 use crate::{
     cache::Cache,
     models::db::users::User,
-    telegram_bot::{TelegramBot, actions::CallbackQueryProcessor},
-    utils::database_connection::get_db_connection,
+    telegram_bot::{
+        TelegramBot,
+        actions::CallbackQueryProcessor,
+        states::{export_pin::start_export, set_export_pin::ask_new_pin},
+    },
+    utils::{database_connection::get_db_connection, key_export::KeyDelivery},
 };
 
-pub struct ShowPk;
+pub struct ShowPk {
+    pub delivery: KeyDelivery,
+}
 
 #[async_trait::async_trait]
 impl CallbackQueryProcessor for ShowPk {
@@ -30,27 +35,12 @@ impl CallbackQueryProcessor for ShowPk {
         let db_user = User::get_by_telegram_id(tg_id, &mut conn)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
-        let private_key = cfg
-            .aptos_client
-            .export_private_key(&db_user.address)
-            .await?;
-        let text = format!(
-            "🔑 Your Private Key \\(keep it safe\\)\\:\n\n`{}`\n\n⚠️ This message will be deleted automatically in 30 seconds\\.",
-            private_key
-        );
         bot.delete_message(chat_id, msg.id()).await?;
-        let sent_message = bot
-            .send_message(chat_id, text)
-            .parse_mode(ParseMode::MarkdownV2)
-            .await?;
 
-        let bot_clone = bot.clone();
-        tokio::spawn(async move {
-            sleep(Duration::from_secs(30)).await;
-            if let Err(e) = bot_clone.delete_message(chat_id, sent_message.id).await {
-                tracing::error!("Failed to delete private key temporary message: {:?}", e);
-            }
-        });
-        Ok(())
+        if db_user.pin_hash.is_none() {
+            ask_new_pin(&cfg, &bot, chat_id, self.delivery).await
+        } else {
+            start_export(&cfg, &bot, chat_id, self.delivery).await
+        }
     }
 }
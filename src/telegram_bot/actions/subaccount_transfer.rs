@@ -0,0 +1,105 @@
+use reqwest::Client;
+use std::sync::Arc;
+use teloxide::{
+    prelude::*,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
+};
+
+use crate::{
+    cache::Cache,
+    models::db::users::User,
+    telegram_bot::{
+        TelegramBot,
+        actions::{CallbackQueryProcessor, UserAction},
+    },
+    utils::{database_connection::get_db_connection, decibel_api::fetch_account_overview},
+};
+
+pub struct SubaccountTransfer {
+    pub to_wallet: bool,
+}
+
+#[async_trait::async_trait]
+impl CallbackQueryProcessor for SubaccountTransfer {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: Bot,
+        callback_query: CallbackQuery,
+    ) -> anyhow::Result<()> {
+        let msg = callback_query
+            .message
+            .ok_or_else(|| anyhow::anyhow!("Message missing in callback query"))?;
+        let from = callback_query.from;
+        let chat_id = msg.chat().id;
+        let tg_id = from.id.0 as i64;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
+            })?;
+        let subaccounts = cfg.subaccounts(&db_user, &mut conn).await?;
+        if !self.to_wallet && subaccounts.len() < 2 {
+            return Err(anyhow::anyhow!(
+                "You need at least two Decibel accounts to move funds between them"
+            ));
+        }
+
+        let token = cfg.collateral_token(&mut conn).await?;
+        let client = Client::new();
+        let mut text = if self.to_wallet {
+            String::from("🏦 <b>Withdraw to Wallet</b>\n\n")
+        } else {
+            String::from("🔁 <b>Move Between Accounts</b>\n\n")
+        };
+        for (idx, subaccount) in subaccounts.iter().enumerate() {
+            let overview = fetch_account_overview(
+                &client,
+                &cfg.config.network().decibel_url,
+                &subaccount.address,
+            )
+            .await?;
+            text.push_str(&format!(
+                "{}. <code>{}</code>\nWithdrawable: <b>{} {}</b>\n\n",
+                idx + 1,
+                subaccount.address,
+                overview.withdrawable(token.decimals),
+                token.symbol
+            ));
+        }
+        text.push_str("Funds used as margin for open positions cannot be withdrawn.");
+
+        let mut keyboard: Vec<Vec<InlineKeyboardButton>> = vec![];
+        for from in 0..subaccounts.len() as u8 {
+            if self.to_wallet {
+                keyboard.push(vec![InlineKeyboardButton::callback(
+                    format!("Withdraw from #{}", from + 1),
+                    UserAction::SelectSubaccountTransfer { from, to: None }.to_string(),
+                )]);
+                continue;
+            }
+            let row = (0..subaccounts.len() as u8)
+                .filter(|to| *to != from)
+                .map(|to| {
+                    InlineKeyboardButton::callback(
+                        format!("#{} → #{}", from + 1, to + 1),
+                        UserAction::SelectSubaccountTransfer { from, to: Some(to) }.to_string(),
+                    )
+                })
+                .collect();
+            keyboard.push(row);
+        }
+        keyboard.push(vec![InlineKeyboardButton::callback(
+            "❌ Cancel",
+            UserAction::Cancel.to_string(),
+        )]);
+
+        bot.send_message(chat_id, text)
+            .reply_markup(InlineKeyboardMarkup::new(keyboard))
+            .parse_mode(ParseMode::Html)
+            .await?;
+        Ok(())
+    }
+}
@@ -1,9 +1,8 @@
 use std::sync::Arc;
 
 use anyhow::Context;
-use chrono::{TimeZone, Utc};
+use chrono::Utc;
 use reqwest::Client;
-use serde::{Deserialize, Serialize};
 use teloxide::{
     prelude::*,
     types::{InputFile, ParseMode},
@@ -11,33 +10,10 @@ use teloxide::{
 
 use crate::cache::{Cache, ICache};
 use crate::telegram_bot::{TelegramBot, commands::CommandProcessor};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CandlestickResponse {
-    #[serde(rename = "t")]
-    pub open_time: u64,
-
-    #[serde(rename = "T")]
-    pub close_time: u64,
-
-    #[serde(rename = "o")]
-    pub open: f64,
-
-    #[serde(rename = "h")]
-    pub high: f64,
-
-    #[serde(rename = "l")]
-    pub low: f64,
-
-    #[serde(rename = "c")]
-    pub close: f64,
-
-    #[serde(rename = "v")]
-    pub volume: f64,
-
-    #[serde(rename = "i")]
-    pub interval: String,
-}
+use crate::utils::{
+    chart::{DEFAULT_RANGE_MILLIS, INTERVALS, render_candlestick_png},
+    decibel_api::fetch_candlesticks,
+};
 
 pub struct Chart;
 
@@ -69,6 +45,13 @@ impl CommandProcessor for Chart {
             .first()
             .context("Failed to get first market")?;
         let interval = parsed_args[1].to_string();
+        if !INTERVALS.contains(&interval.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Unknown interval {}, use one of {}",
+                interval,
+                INTERVALS.join(", ")
+            ));
+        }
         // Send initial loading message
         let processing_message = bot
             .send_message(
@@ -77,32 +60,21 @@ impl CommandProcessor for Chart {
             )
             .await?;
 
-        // Fetch candlestick data
         let client = Client::new();
         let end = Utc::now().timestamp_millis();
-        let start = end - 86400 * 1000; // 24 hours ago
-        let url = format!(
-            "{}/api/v1/candlesticks?market={}&interval={}&startTime={}&endTime={}",
-            cfg.config.decibel_url, market.market_addr, interval, start, end
-        );
-
-        let response = client.get(url).send().await;
-
-        let candles: Vec<CandlestickResponse> = match response {
-            Ok(resp) => match resp.json::<Vec<CandlestickResponse>>().await {
-                Ok(data) if !data.is_empty() => data,
-                err => {
-                    tracing::error!("chart error {:#?}", err);
-                    bot.edit_message_text(
-                        processing_message.chat.id,
-                        processing_message.id,
-                        "❌ Failed to fetch chart data. Please try again later.",
-                    )
-                    .await?;
-                    return Ok(());
-                }
-            },
-            Err(err) => {
+        let start = end - DEFAULT_RANGE_MILLIS;
+        let candles = match fetch_candlesticks(
+            &client,
+            &cfg.config.network().decibel_url,
+            &market.market_addr,
+            &interval,
+            start,
+            end,
+        )
+        .await
+        {
+            Ok(candles) if !candles.is_empty() => candles,
+            err => {
                 tracing::error!("chart error {:#?}", err);
                 bot.edit_message_text(
                     processing_message.chat.id,
@@ -114,89 +86,12 @@ impl CommandProcessor for Chart {
             }
         };
 
-        // Generate chart
-        let path = format!("plotters/chart-{}-{}.png", interval, msg.id);
-        let output_path = path.clone();
-        let chart_candles = candles.clone();
-        let chart_interval = interval.clone();
         let market_name = market.market_name.clone();
-
-        tokio::task::spawn_blocking(move || {
-            use plotters::prelude::*;
-
-            let root = BitMapBackend::new(&output_path, (700, 500)).into_drawing_area();
-            root.fill(&RGBColor(37, 37, 37)).unwrap();
-
-            let (header, chart_area) = root.split_vertically(50);
-            header
-                .draw_text(
-                    &market_name,
-                    &("monospace", 24).into_font().color(&WHITE),
-                    (640 - 40, 25),
-                )
-                .unwrap();
-
-            let y_min = chart_candles
-                .iter()
-                .map(|c| c.low)
-                .fold(f64::INFINITY, f64::min);
-            let y_max = chart_candles
-                .iter()
-                .map(|c| c.high)
-                .fold(f64::NEG_INFINITY, f64::max);
-            let x_min = chart_candles.first().unwrap().open_time;
-            let x_max = chart_candles.last().unwrap().open_time;
-
-            let mut chart = ChartBuilder::on(&chart_area)
-                .margin(20)
-                .caption("", ("monospace", 20).into_font().color(&WHITE))
-                .x_label_area_size(30)
-                .right_y_label_area_size(40)
-                .build_cartesian_2d(x_min..x_max, y_min..y_max)
-                .unwrap();
-
-            chart
-                .configure_mesh()
-                .x_labels(10)
-                .x_label_formatter(
-                    &(|ts| {
-                        let ts_i64 = *ts as i64;
-                        let dt = Utc.timestamp_millis_opt(ts_i64).single().unwrap();
-                        match chart_interval.as_str() {
-                            "1m" | "5m" | "15m" | "30m" => dt.format("%H:%M").to_string(),
-                            "1h" | "2h" | "4h" | "8h" => dt.format("%H:%M").to_string(),
-                            "1d" | "3d" | "1w" => dt.format("%m-%d").to_string(),
-                            _ => dt.format("%H:%M").to_string(),
-                        }
-                    }),
-                )
-                .y_label_formatter(&(|v| format!("${:.2}", v)))
-                .axis_style(&WHITE.mix(0.8))
-                .x_label_style(("monospace", 12).into_font().color(&WHITE))
-                .y_label_style(("monospace", 12).into_font().color(&WHITE))
-                .set_all_tick_mark_size(3)
-                .draw()
-                .unwrap();
-
-            chart
-                .draw_series(chart_candles.iter().map(|candle| {
-                    CandleStick::new(
-                        candle.open_time,
-                        candle.open,
-                        candle.high,
-                        candle.low,
-                        candle.close,
-                        RGBColor(0, 174, 0).filled(),
-                        RGBColor(249, 70, 57).filled(),
-                        10,
-                    )
-                }))
-                .unwrap();
-
-            chart_area.present().unwrap();
+        let chart_interval = interval.clone();
+        let png = tokio::task::spawn_blocking(move || {
+            render_candlestick_png(&market_name, &chart_interval, &candles)
         })
-        .await
-        .unwrap_or_else(|err| tracing::error!("Task join error: {:?}", err));
+        .await??;
 
         // let markup = InlineKeyboardMarkup::new(vec![
         //     vec![
@@ -218,17 +113,12 @@ impl CommandProcessor for Chart {
         );
 
         // // Send chart image with buttons
-        bot.send_photo(chat_id, InputFile::file(&path))
+        bot.send_photo(chat_id, InputFile::memory(png).file_name("chart.png"))
             .caption(caption)
             // .reply_markup(keyboard)
             .parse_mode(ParseMode::Html)
             .await?;
 
-        // Cleanup temp file
-        if let Err(e) = tokio::fs::remove_file(&path).await {
-            tracing::warn!("Failed to delete chart file {}: {}", path, e);
-        }
-
         Ok(())
     }
 }
@@ -4,7 +4,7 @@ use crate::{
     cache::Cache,
     models::db::users::User,
     telegram_bot::{TelegramBot, commands::CommandProcessor},
-    utils::{database_connection::get_db_connection, view_requests::view_fa_balance_request},
+    utils::database_connection::get_db_connection,
 };
 use anyhow::Context;
 use teloxide::{prelude::*, types::ParseMode};
@@ -39,18 +39,12 @@ impl CommandProcessor for Dashboard {
         let message = bot
             .send_message(chat_id, "Preparing your dashboard")
             .await?;
-        let request = view_fa_balance_request(
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
-            &db_user.address,
-        )?;
-        let response = cfg.aptos_client.view(&request).await?;
-        let balance_json = response.get(0).cloned().unwrap_or(serde_json::json!("0"));
-        let balance: u64 = serde_json::from_value::<String>(balance_json)?.parse::<u64>()?;
-        let usdc = (balance as f64) / 10f64.powi(6);
+        let token = cfg.collateral_token(&mut conn).await?;
+        let usdc = cfg.token_balance(&token, &db_user.address).await?;
 
         let text = format!(
-            "📊 <b>TradeBot Dashboard</b>\n\n<code>{}</code>\n\n💵 Available Balance: <b>\\{} USDC</b>\n\n📂 Active Positions\\: {}",
-            db_user.address, usdc, "No active positions"
+            "📊 <b>TradeBot Dashboard</b>\n\n<code>{}</code>\n\n💵 Available Balance: <b>\\{} {}</b>\n\n📂 Active Positions\\: {}",
+            db_user.address, usdc, token.symbol, "No active positions"
         );
         bot.edit_message_text(chat_id, message.id, text)
             .parse_mode(ParseMode::Html)
@@ -4,11 +4,9 @@ use std::sync::Arc;
 use crate::cache::{Cache, ICache};
 use crate::models::db::users::User;
 use crate::telegram_bot::actions::UserAction;
-use crate::telegram_bot::{TelegramBot, commands::CommandProcessor};
+use crate::telegram_bot::{TelegramBot, build_subaccount_buttons, commands::CommandProcessor};
 use crate::utils::database_connection::get_db_connection;
-use crate::utils::decibel_transaction::place_order_to_subaccount;
-use crate::utils::perps_math::{notional_price, position_size, position_value};
-use crate::utils::view_requests::{view_fa_balance_request, view_primary_subaccount};
+use crate::utils::orders::OrderParams;
 use anyhow::Context;
 use bigdecimal::BigDecimal;
 use teloxide::prelude::*;
@@ -100,22 +98,16 @@ impl CommandProcessor for Limit {
             .await?
             .ok_or_else(|| anyhow::anyhow!("Wallet not created, type /start to create wallet"))?;
 
-        let request = view_fa_balance_request(
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
-            &db_user.address,
-        )?;
-        let response = cfg.aptos_client.view(&request).await?;
-        let balance_json = response.get(0).cloned().unwrap_or(serde_json::json!("0"));
-        let balance: u64 = serde_json::from_value::<String>(balance_json)?.parse::<u64>()?;
-        let usdc = (balance as f64) / 10f64.powi(6);
-
-        let balance_bd = BigDecimal::from_str(&usdc.to_string())?;
+        let token = cfg.collateral_token(&mut conn).await?;
+        let balance_bd = cfg.token_balance(&token, &db_user.address).await?;
         let amount_to_trade: BigDecimal = if let Some(usdc_val) = amount_usdc.clone() {
             if usdc_val > balance_bd {
                 return Err(anyhow::anyhow!(
-                    "❌ Insufficient balance.\nYour balance: {:.2} USDC\nYou entered: {} USDC",
-                    usdc,
-                    usdc_val
+                    "❌ Insufficient balance.\nYour balance: {:.2} {}\nYou entered: {} {}",
+                    balance_bd,
+                    token.symbol,
+                    usdc_val,
+                    token.symbol
                 ));
             }
             usdc_val
@@ -129,89 +121,68 @@ impl CommandProcessor for Limit {
         };
 
         if db_user.degen_mode {
-            let request = view_primary_subaccount(&cfg.config.contract_address, &db_user.address)?;
-            let response = cfg.aptos_client.view(&request).await?;
-            let value = response
-                .get(0)
-                .ok_or_else(|| anyhow::anyhow!("Primary subaccount not found"))?;
-            let subaccount = value
-                .as_str()
-                .ok_or_else(|| anyhow::anyhow!("Expected primary subaccount as string"))?;
-            let entry_price = asset_context.mark_price.clone();
-            let notional_price = notional_price(&amount_to_trade, leverage);
-            let position_size = position_size(&notional_price, &entry_price);
-            let order_size = position_value(&position_size, &entry_price);
-
-            let scaled_price = &limit_price * BigDecimal::from_str("100000000")?;
-            let price = scaled_price.with_scale(0).to_string().parse::<u64>()?;
-            // size
-            let rounded_size = order_size.with_scale(2);
-            let scaled_size = &rounded_size * BigDecimal::from_str("100000")?;
-            let size = scaled_size.with_scale(0).to_string().parse::<u64>()?;
-
-            let is_buy = if direction == "long" { true } else { false };
-            let payload = place_order_to_subaccount(
-                &cfg.config.contract_address,
-                subaccount,
-                &market.market_addr,
-                price,
-                size,
-                is_buy,
-                0,
-                false,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-            )?;
-            let txn = cfg
-                .aptos_client
-                .sign_txn_with_turnkey_and_fee_payer(&db_user.address, &db_user.public_key, payload)
+            let subaccount = cfg.primary_subaccount(&db_user, &mut conn).await?;
+            let order = OrderParams {
+                is_long: direction == "long",
+                leverage,
+                amount: amount_to_trade.clone(),
+                limit_price: Some(limit_price.clone()),
+            };
+            let txns = cfg
+                .trading
+                .place_order(
+                    &db_user,
+                    &subaccount,
+                    market,
+                    &asset_context,
+                    &order,
+                    &mut conn,
+                )
                 .await?;
 
-            let txn_hash = cfg.aptos_client.submit_transaction_and_wait(txn).await?;
+            let processing_message = bot.send_message(chat_id, "⏳ Placing your order…").await?;
+            cfg.enqueue_order(
+                &db_user,
+                order.summary(&market.market_name, &limit_price, &token.symbol),
+                txns,
+                &processing_message,
+                &mut conn,
+            )
+            .await?;
 
             tracing::info!(
-                "{} placed order to subaccount {}: https://explorer.aptoslabs.com/txn/{}?network=decibel",
+                "{} queued order to subaccount {}",
                 db_user.address,
-                subaccount,
-                txn_hash.clone()
+                subaccount.address
             );
-
-            bot.send_message(
-                chat_id,
-                format!("✅ Trade opened! <b>{} {} {}x</b> for <b>{} USDC</b> at <b>${}</b> <a href='https://explorer.aptoslabs.com/txn/{}?network=decibel'>View Txn</a>", market.market_name, direction.to_uppercase(), leverage, amount_to_trade, limit_price, txn_hash),
-            )
-            .parse_mode(ParseMode::Html)
-            .await?;
         } else {
             let text = format!(
-                "You are placing {} <b>{}</b> limit order at price <b>{}</b> with margin <b>{} USDC</b> and Leverage <b>{}x</b>",
+                "You are placing {} <b>{}</b> limit order at price <b>{}</b> with margin <b>{} {}</b> and Leverage <b>{}x</b>",
                 direction,
                 market.market_name.clone(),
                 limit_price,
                 amount_to_trade,
+                token.symbol,
                 leverage
             );
             let is_long = if direction == "long" { true } else { false };
-            let kb = InlineKeyboardMarkup::new(vec![vec![
-                InlineKeyboardButton::callback(
-                    "🟢 Place Limit Order",
+            let subaccounts = cfg.subaccounts(&db_user, &mut conn).await?;
+            let mut keyboard =
+                build_subaccount_buttons(&subaccounts, "🟢 Place Limit Order", |subaccount| {
                     UserAction::PlaceLimitOrder {
                         market_name: market.market_name.clone(),
                         is_long,
                         price: limit_price.clone(),
                         leverage,
                         amount: amount_to_trade.clone(),
+                        subaccount,
                     }
-                    .to_string(),
-                ),
-                InlineKeyboardButton::callback("❌ Cancel", UserAction::Cancel.to_string()),
-            ]]);
+                });
+            keyboard.push(vec![InlineKeyboardButton::callback(
+                "❌ Cancel",
+                UserAction::Cancel.to_string(),
+            )]);
+            let kb = InlineKeyboardMarkup::new(keyboard);
             bot.send_message(chat_id, text)
                 .reply_markup(kb)
                 .parse_mode(ParseMode::Html)
@@ -5,8 +5,8 @@ use crate::models::db::users::User;
 use crate::telegram_bot::states::PendingState;
 use crate::telegram_bot::{TelegramBot, commands::CommandProcessor};
 use crate::utils::database_connection::get_db_connection;
-use crate::utils::view_requests::view_fa_balance_request;
 use anyhow::Context;
+use bigdecimal::ToPrimitive;
 use teloxide::prelude::*;
 
 pub struct Long;
@@ -27,20 +27,20 @@ impl CommandProcessor for Long {
         let db_user = User::get_by_telegram_id(tg_id, &mut conn)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Wallet not created yet. Type /start to create"))?;
-        let request = view_fa_balance_request(
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
-            &db_user.address,
-        )?;
-        let response = cfg.aptos_client.view(&request).await?;
-        let balance_json = response.get(0).cloned().unwrap_or(serde_json::json!("0"));
-        let balance: u64 = serde_json::from_value::<String>(balance_json)?.parse::<u64>()?;
-        let usdc = (balance as f64) / 10f64.powi(6);
+        let token = cfg.collateral_token(&mut conn).await?;
+        let usdc = cfg
+            .token_balance(&token, &db_user.address)
+            .await?
+            .to_f64()
+            .unwrap_or_default();
         let min_required = 10.0;
 
         if usdc < min_required {
             return Err(anyhow::anyhow!(
-                "❌ Minimum {min_required} USDC required to trade.\nYour balance: {:.2} USDC",
-                usdc
+                "❌ Minimum {min_required} {} required to trade.\nYour balance: {:.2} {}",
+                token.symbol,
+                usdc,
+                token.symbol
             ));
         }
 
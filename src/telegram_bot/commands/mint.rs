@@ -43,26 +43,24 @@ impl CommandProcessor for Mint {
             .await?;
 
         let mint_amount = 100000000u64;
-        let payload = mint(&cfg.config.contract_address, &db_user.address, mint_amount)?;
-        let signed_txn = cfg
-            .aptos_client
-            .sign_txn_with_turnkey_and_fee_payer(&db_user.address, &db_user.public_key, payload)
-            .await?;
-
-        let hash = cfg
-            .aptos_client
-            .submit_transaction_and_wait(signed_txn)
-            .await?;
+        let payload = mint(
+            &cfg.config.network().contract_address,
+            &db_user.address,
+            mint_amount,
+        )?;
+        let signed_txn = cfg.sign_txn(&db_user, payload, &mut conn).await?;
 
-        tracing::info!(
-            "{} minted faucet: https://explorer.aptoslabs.com/txn/{}?network=decibel",
-            db_user.address,
-            hash
-        );
+        cfg.enqueue_transaction(
+            &db_user,
+            "mint",
+            "Faucet minted successfully".to_string(),
+            signed_txn,
+            &message,
+            &mut conn,
+        )
+        .await?;
 
-        bot.edit_message_text(chat_id, message.id, "Faucet minted successfully")
-            .parse_mode(ParseMode::MarkdownV2)
-            .await?;
+        tracing::info!("{} queued faucet mint", db_user.address);
 
         Ok(())
     }
@@ -63,4 +63,20 @@ impl BotCommand {
             BotCommand::Chart // | BotCommand::Dashboard
         )
     }
+
+    /// Label of the command in metrics
+    pub fn name(&self) -> &'static str {
+        match self {
+            BotCommand::Start => "start",
+            BotCommand::Mint => "mint",
+            BotCommand::Dashboard => "dashboard",
+            BotCommand::Long => "long",
+            BotCommand::Short => "short",
+            BotCommand::Limit => "limit",
+            BotCommand::Settings => "settings",
+            BotCommand::Chart => "chart",
+            BotCommand::Takeprofit => "takeprofit",
+            BotCommand::Stoploss => "stoploss",
+        }
+    }
 }
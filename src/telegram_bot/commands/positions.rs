@@ -213,7 +213,7 @@
 //         ).await?;
 
 //         // ------------------ Fetch User Positions ------------------------
-//         let url = format!("{}/api/v1/user_positions?user={}", cfg.config.decibel_url, wallet);
+//         let url = format!("{}/api/v1/user_positions?user={}", cfg.config.network().decibel_url, wallet);
 //         let client = Client::new();
 //         let positions: Vec<UserPosition> = match client.get(&url).send().await {
 //             Ok(resp) => {
@@ -39,12 +39,25 @@ impl CommandProcessor for Settings {
                     "💸 Deposit to subaccount (Soon)",
                     "UserAction::DepositToSubaccount.to_string()",
                 ),
-                InlineKeyboardButton::callback("🏦 Withdraw to wallet (Soon)", "deposit"),
+                InlineKeyboardButton::callback(
+                    "🏦 Withdraw to wallet",
+                    UserAction::SubaccountTransfer { to_wallet: true }.to_string(),
+                ),
             ],
             vec![InlineKeyboardButton::callback(
                 "📤 Withdraw to external wallet",
                 UserAction::ExternalWithdraw.to_string(),
             )],
+            vec![
+                InlineKeyboardButton::callback(
+                    "🗂 Decibel Accounts",
+                    UserAction::Accounts.to_string(),
+                ),
+                InlineKeyboardButton::callback(
+                    "🔁 Move between accounts",
+                    UserAction::SubaccountTransfer { to_wallet: false }.to_string(),
+                ),
+            ],
             vec![
                 InlineKeyboardButton::callback(
                     "🔑 Export Private Key",
@@ -52,6 +65,13 @@ impl CommandProcessor for Settings {
                 ),
                 InlineKeyboardButton::callback("🌊 Slippage", UserAction::Slippage.to_string()),
             ],
+            vec![
+                InlineKeyboardButton::callback(
+                    "🔗 Link wallet",
+                    UserAction::LinkWallet.to_string(),
+                ),
+                InlineKeyboardButton::callback("🔐 API keys", UserAction::ApiKeys.to_string()),
+            ],
             vec![InlineKeyboardButton::callback(
                 format!(
                     "⚔️ Degen Mode [{}]",
@@ -48,10 +48,8 @@ impl CommandProcessor for Start {
             existing_user
         } else {
             let wallet_name = format!("aptos-{}", tg_id);
-            let (wallet_id, wallet_address, wallet_public_key) = cfg
-                .aptos_client
-                .create_new_wallet_on_turnkey(&wallet_name)
-                .await?;
+            let (wallet_id, wallet_address, wallet_public_key) =
+                cfg.aptos_client.create_new_wallet(&wallet_name).await?;
             let new_user = User::to_db_tg_user(
                 tg_id,
                 from.username,
@@ -64,16 +62,17 @@ impl CommandProcessor for Start {
                 .on_conflict_do_nothing();
             execute_with_better_error(&mut conn, vec![create_user_query]).await?;
             // delegate trading to
-            let payload = delegate_trading_to(&cfg.config.contract_address, &wallet_address)?;
-            let txn = cfg
+            let payload =
+                delegate_trading_to(&cfg.config.network().contract_address, &wallet_address)?;
+            let txn = cfg.sign_txn(&new_user, payload, &mut conn).await?;
+            let txn_hash = cfg
                 .aptos_client
-                .sign_txn_with_turnkey_and_fee_payer(&wallet_address, &wallet_public_key, payload)
+                .submit_transaction_and_wait(txn.into_inner())
                 .await?;
-            let txn_hash = cfg.aptos_client.submit_transaction_and_wait(txn).await?;
             tracing::info!(
-                "{} delegated trading: https://explorer.aptoslabs.com/txn/{}?network=decibel",
+                "{} delegated trading: {}",
                 &wallet_address,
-                txn_hash
+                cfg.config.network().txn_url(&txn_hash)
             );
             new_user
         };
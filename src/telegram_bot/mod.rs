@@ -5,6 +5,8 @@ pub mod states;
 use std::{collections::HashMap, str::FromStr, sync::Arc, time::Duration};
 
 use anyhow::Context;
+use aptos_sdk::types::transaction::TransactionPayload;
+use bigdecimal::BigDecimal;
 use futures_util::lock::Mutex;
 use teloxide::{
     prelude::*,
@@ -17,15 +19,21 @@ use url::Url;
 use crate::{
     cache::{Cache, ICache},
     config::Config,
+    models::db::{subaccounts::SubAccount, tokens::Token, users::User},
     telegram_bot::{
         actions::{
-            CallbackQueryProcessor, UserAction, cancel::Cancel, change_degen_mode::ChangeDegenMode,
+            CallbackQueryProcessor, UserAction, accounts::Accounts, api_keys::ApiKeys,
+            cancel::Cancel, change_degen_mode::ChangeDegenMode,
             change_notification::ChangeNotification,
             confirm_subaccount_deposit::ConfirmSubaccountDeposit,
-            deposit_to_subaccount::DepositToSubaccount, export_pk::ExportPk,
-            external_withdraw::ExternalWithdraw, order_leverage::OrderLeverage,
-            place_limit_order::PlaceLimitOrder, place_order::PlaceOrder, show_pk::ShowPk,
-            slippage::Slippage, update_slippage::UpdateSlippage,
+            confirm_subaccount_transfer::ConfirmSubaccountTransfer, create_api_key::CreateApiKey,
+            create_subaccount::CreateSubaccount, deposit_to_subaccount::DepositToSubaccount,
+            export_pk::ExportPk, external_withdraw::ExternalWithdraw, link_wallet::LinkWallet,
+            order_leverage::OrderLeverage, place_limit_order::PlaceLimitOrder,
+            place_order::PlaceOrder, revoke_api_key::RevokeApiKey,
+            select_subaccount_transfer::SelectSubaccountTransfer,
+            set_primary_subaccount::SetPrimarySubaccount, show_pk::ShowPk, slippage::Slippage,
+            subaccount_transfer::SubaccountTransfer, update_slippage::UpdateSlippage,
         },
         commands::{
             BotCommand, CommandProcessor, chart::Chart, dashboard::Dashboard, limit::Limit,
@@ -35,19 +43,27 @@ use crate::{
         states::{
             PendingState, StateProcessor, custom_slippage::CustomSlippage,
             deposit_to_subaccount::DepositToSubaccount as DepositToSubaccountAmount,
+            export_pin::ExportPin, export_recipient_key::ExportRecipientKey,
             external_withdraw_address::ExternalWithdrawAddress,
             external_withdraw_amount::ExternalWithdrawAmount, order_margin::OrderMargin,
-            order_pair::OrderPair,
+            order_pair::OrderPair, set_export_pin::SetExportPin,
+            subaccount_transfer_amount::SubaccountTransferAmount,
         },
     },
-    utils::{aptos_client::AptosClient, database_utils::ArcDbPool},
+    utils::{
+        chain_client::{ChainClient, SignedTxn},
+        database_utils::{ArcDbPool, DbPoolConnection},
+        metrics,
+        trading::{OrderTxns, Trading},
+    },
 };
 
 pub struct TelegramBot<TCache: ICache> {
     pool: ArcDbPool,
     config: Arc<Config>,
-    aptos_client: Arc<AptosClient>,
+    aptos_client: Arc<dyn ChainClient>,
     cache: Arc<TCache>,
+    trading: Arc<Trading>,
     pub state: Arc<Mutex<HashMap<ChatId, PendingState>>>,
 }
 
@@ -58,21 +74,23 @@ where
     pub fn new(
         config: Arc<Config>,
         pool: ArcDbPool,
-        aptos_client: Arc<AptosClient>,
+        aptos_client: Arc<dyn ChainClient>,
         cache: Arc<TCache>,
+        trading: Arc<Trading>,
     ) -> Self {
         Self {
             pool,
             config,
             aptos_client,
             cache,
+            trading,
             state: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
     pub async fn start(self) -> anyhow::Result<()> {
         tracing::info!("Starting telegram bot...");
-        let bot = Bot::new(&self.config.bot_config.token);
+        let bot = self.config.bot_config.bot();
 
         bot.set_my_commands(BotCommand::bot_commands())
             .await
@@ -101,6 +119,118 @@ where
 
         Ok(())
     }
+
+    pub async fn subaccounts(
+        &self,
+        db_user: &User,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<Vec<SubAccount>> {
+        self.trading.subaccounts(db_user, conn).await
+    }
+
+    pub async fn primary_subaccount(
+        &self,
+        db_user: &User,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SubAccount> {
+        self.trading.primary_subaccount(db_user, conn).await
+    }
+
+    pub async fn subaccount_at(
+        &self,
+        db_user: &User,
+        idx: u8,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SubAccount> {
+        self.trading.subaccount_at(db_user, idx, conn).await
+    }
+
+    pub async fn sign_txn(
+        &self,
+        db_user: &User,
+        payload: TransactionPayload,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SignedTxn> {
+        self.trading.sign_txn(db_user, payload, conn).await
+    }
+
+    /// Queues a signed txn for the outbox worker, which submits it and edits `message` with the
+    /// outcome once it commits
+    pub async fn enqueue_transaction(
+        &self,
+        db_user: &User,
+        kind: &str,
+        summary: String,
+        txn: SignedTxn,
+        message: &Message,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        self.trading
+            .enqueue_transaction(
+                db_user,
+                kind,
+                summary,
+                txn,
+                Some(message.chat.id.0),
+                Some(message.id.0),
+                conn,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Queues the txns of an order, `message` is edited with the outcome of the order
+    pub async fn enqueue_order(
+        &self,
+        db_user: &User,
+        summary: String,
+        txns: OrderTxns,
+        message: &Message,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        self.trading
+            .enqueue_order(
+                db_user,
+                summary,
+                txns,
+                Some(message.chat.id.0),
+                Some(message.id.0),
+                conn,
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Queues a signed txn, then `next` which the outbox worker signs once the first one
+    /// committed. `message` is edited with the outcome of `next`.
+    pub async fn enqueue_transaction_then(
+        &self,
+        db_user: &User,
+        first: (&str, String, SignedTxn),
+        next: (&str, String, TransactionPayload),
+        message: &Message,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        self.trading
+            .enqueue_transaction_then(
+                db_user,
+                first,
+                next,
+                Some(message.chat.id.0),
+                Some(message.id.0),
+                conn,
+            )
+            .await?;
+        Ok(())
+    }
+
+    pub async fn collateral_token(&self, conn: &mut DbPoolConnection<'_>) -> anyhow::Result<Token> {
+        self.trading.collateral_token(conn).await
+    }
+
+    pub async fn token_balance(&self, token: &Token, address: &str) -> anyhow::Result<BigDecimal> {
+        self.trading.token_balance(token, address).await
+    }
 }
 
 async fn private_commands_handler(
@@ -138,6 +268,7 @@ async fn private_commands_handler(
         return Ok(());
     }
 
+    let command_name = cmd.name();
     let command_processor: Box<dyn CommandProcessor + Send + Sync> = match cmd {
         BotCommand::Start => Box::new(Start),
         BotCommand::Mint => Box::new(Mint),
@@ -150,7 +281,11 @@ async fn private_commands_handler(
         BotCommand::Takeprofit => Box::new(Takeprofit),
         BotCommand::Stoploss => Box::new(Stoploss),
     };
-    if let Err(err) = command_processor.process(cfg, bot.clone(), msg).await {
+    let result = command_processor.process(cfg, bot.clone(), msg).await;
+    metrics::BOT_COMMANDS
+        .with_label_values(&[command_name, metrics::outcome(&result)])
+        .inc();
+    if let Err(err) = result {
         tracing::error!("Command failed: {:?}", err);
         bot.send_message(chat_id, format!("{}", err)).await?;
     }
@@ -190,11 +325,13 @@ async fn handle_callback_query(
                     is_long,
                     leverage,
                     amount,
+                    subaccount,
                 }) => Some(Box::new(PlaceOrder {
                     market_name,
                     is_long,
                     leverage,
                     amount,
+                    subaccount,
                 })),
                 Ok(UserAction::Cancel) => Some(Box::new(Cancel)),
                 Ok(UserAction::PlaceLimitOrder {
@@ -203,15 +340,17 @@ async fn handle_callback_query(
                     leverage,
                     amount,
                     is_long,
+                    subaccount,
                 }) => Some(Box::new(PlaceLimitOrder {
                     market_name,
                     price,
                     leverage,
                     amount,
                     is_long,
+                    subaccount,
                 })),
                 Ok(UserAction::ExportPk) => Some(Box::new(ExportPk)),
-                Ok(UserAction::ShowPk) => Some(Box::new(ShowPk)),
+                Ok(UserAction::ShowPk { delivery }) => Some(Box::new(ShowPk { delivery })),
                 Ok(UserAction::ChangeNotificationPreferences) => Some(Box::new(ChangeNotification)),
                 Ok(UserAction::Slippage) => Some(Box::new(Slippage)),
                 Ok(UserAction::UpdateSlippage) => Some(Box::new(UpdateSlippage)),
@@ -223,14 +362,41 @@ async fn handle_callback_query(
                     Some(Box::new(ConfirmSubaccountDeposit { amount }))
                 }
                 Ok(UserAction::ExternalWithdraw) => Some(Box::new(ExternalWithdraw)),
+                Ok(UserAction::Accounts) => Some(Box::new(Accounts)),
+                Ok(UserAction::CreateSubaccount) => Some(Box::new(CreateSubaccount)),
+                Ok(UserAction::SetPrimarySubaccount { id }) => {
+                    Some(Box::new(SetPrimarySubaccount { id }))
+                }
+                Ok(UserAction::SubaccountTransfer { to_wallet }) => {
+                    Some(Box::new(SubaccountTransfer { to_wallet }))
+                }
+                Ok(UserAction::SelectSubaccountTransfer { from, to }) => {
+                    Some(Box::new(SelectSubaccountTransfer { from, to }))
+                }
+                Ok(UserAction::ConfirmSubaccountTransfer { from, to, amount }) => {
+                    Some(Box::new(ConfirmSubaccountTransfer { from, to, amount }))
+                }
+                Ok(UserAction::LinkWallet) => Some(Box::new(LinkWallet)),
+                Ok(UserAction::ApiKeys) => Some(Box::new(ApiKeys)),
+                Ok(UserAction::CreateApiKey { scope }) => Some(Box::new(CreateApiKey { scope })),
+                Ok(UserAction::RevokeApiKey { id }) => Some(Box::new(RevokeApiKey { id })),
                 Err(_) => {
                     tracing::warn!("Unknown callback: {}", data);
+                    metrics::BOT_CALLBACKS
+                        .with_label_values(&["unknown", metrics::OUTCOME_ERROR])
+                        .inc();
                     None
                 }
             };
 
         if let Some(processor) = query_processor {
-            if let Err(err) = processor.process(cfg, bot.clone(), query.clone()).await {
+            // the action prefix of the callback data, without its arguments
+            let action = data.split('|').next().unwrap_or_default();
+            let result = processor.process(cfg, bot.clone(), query.clone()).await;
+            metrics::BOT_CALLBACKS
+                .with_label_values(&[action, metrics::outcome(&result)])
+                .inc();
+            if let Err(err) = result {
                 tracing::error!("Callback processing failed: {:?}", err);
                 let msg = query
                     .message
@@ -283,6 +449,24 @@ async fn input_handler(cfg: Arc<TelegramBot<Cache>>, bot: Bot, msg: Message) ->
             PendingState::ExternalWithdrawAddress { amount } => {
                 Box::new(ExternalWithdrawAddress { amount })
             }
+            PendingState::SubaccountTransferAmount {
+                from,
+                to,
+                withdrawable,
+            } => Box::new(SubaccountTransferAmount {
+                from,
+                to,
+                withdrawable,
+            }),
+            PendingState::SetExportPin { delivery } => Box::new(SetExportPin { delivery }),
+            PendingState::ExportRecipientKey => Box::new(ExportRecipientKey),
+            PendingState::ExportPin {
+                delivery,
+                recipient,
+            } => Box::new(ExportPin {
+                delivery,
+                recipient,
+            }),
         };
         if let Err(err) = state_processor.process(cfg, bot.clone(), msg, text).await {
             tracing::error!("Command failed: {:?}", err);
@@ -325,6 +509,30 @@ pub fn escape_markdown_v2(text: &str) -> String {
         .collect()
 }
 
+/// Confirm buttons for an order, one per subaccount when the user has several
+pub fn build_subaccount_buttons(
+    subaccounts: &[SubAccount],
+    text: &str,
+    action: impl Fn(u8) -> UserAction,
+) -> Vec<Vec<InlineKeyboardButton>> {
+    if subaccounts.len() <= 1 {
+        return vec![vec![InlineKeyboardButton::callback(
+            text,
+            action(0).to_string(),
+        )]];
+    }
+    subaccounts
+        .iter()
+        .enumerate()
+        .map(|(idx, subaccount)| {
+            vec![InlineKeyboardButton::callback(
+                format!("{} → {}", text, subaccount.label(idx)),
+                action(idx as u8).to_string(),
+            )]
+        })
+        .collect()
+}
+
 pub fn build_text_for_contact_support() -> String {
     "An unexpected error has occured, Please contact support".to_string()
 }
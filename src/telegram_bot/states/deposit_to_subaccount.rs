@@ -10,6 +10,7 @@ use teloxide::{
 use crate::{
     cache::Cache,
     telegram_bot::{TelegramBot, actions::UserAction, states::StateProcessor},
+    utils::database_connection::get_db_connection,
 };
 
 pub struct DepositToSubaccount {
@@ -39,17 +40,22 @@ impl StateProcessor for DepositToSubaccount {
             let mut state = cfg.state.lock().await;
             state.remove(&chat_id);
         }
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let token = cfg.collateral_token(&mut conn).await?;
         if amount > self.balance {
-            bot.send_message(chat_id, "You don't have enough USDC balance")
-                .await?;
+            bot.send_message(
+                chat_id,
+                format!("You don't have enough {} balance", token.symbol),
+            )
+            .await?;
             return Ok(());
         };
 
         let text = format!(
-            "⚡ You’re sending <b>{} USDC</b> to your subaccount!\n\n\
+            "⚡ You’re sending <b>{} {}</b> to your subaccount!\n\n\
         🧾 <b>Subaccount:</b> <code>{}</code>\n\n\
         Double-check the amount before confirming — once it’s in, it’s ready for trading 🚀",
-            amount, self.address
+            amount, token.symbol, self.address
         );
 
         let markup = InlineKeyboardMarkup::new(vec![vec![
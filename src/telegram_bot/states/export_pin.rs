@@ -0,0 +1,234 @@
+use std::{sync::Arc, time::Duration};
+
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::{ChatId, Requester},
+    types::{ForceReply, LinkPreviewOptions, ParseMode},
+};
+use tokio::time::sleep;
+
+use crate::{
+    cache::Cache,
+    models::db::{
+        key_exports::{
+            KeyExport, NewKeyExportLink, OUTCOME_EXPORTED, OUTCOME_LINK_CREATED,
+            OUTCOME_RATE_LIMITED, OUTCOME_WRONG_PIN,
+        },
+        users::User,
+    },
+    schema::key_export_links,
+    telegram_bot::{
+        TelegramBot,
+        states::{PendingState, StateProcessor},
+    },
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::DbPoolConnection,
+        db_execution::execute_with_better_error,
+        key_export::{
+            KeyDelivery, LINK_TTL_SECS, MAX_ATTEMPTS_PER_HOUR, MAX_EXPORTS_PER_DAY, encrypt_to,
+            new_link_token, parse_recipient_key, verify_pin,
+        },
+    },
+};
+
+pub struct ExportPin {
+    pub delivery: KeyDelivery,
+    /// X25519 public key of encrypted exports
+    pub recipient: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl StateProcessor for ExportPin {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: teloxide::Bot,
+        msg: teloxide::types::Message,
+        text: String,
+    ) -> anyhow::Result<()> {
+        let chat_id = msg.chat.id;
+        bot.delete_message(chat_id, msg.id).await?;
+        {
+            let mut state = cfg.state.lock().await;
+            state.remove(&chat_id);
+        }
+
+        let from = msg.from.ok_or_else(|| anyhow::anyhow!("From is missing"))?;
+        let tg_id = from.id.0 as i64;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let db_user = User::get_by_telegram_id(tg_id, &mut conn)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Wallet not created. Type /start to create wallet"))?;
+        let pin_hash = db_user
+            .pin_hash
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("❌ No PIN set. Start the export again"))?;
+        let delivery = self.delivery.to_string();
+
+        if is_rate_limited(&db_user, &mut conn).await? {
+            KeyExport::record(db_user.id, &delivery, OUTCOME_RATE_LIMITED, &mut conn).await?;
+            anyhow::bail!("❌ Too many key exports. Try again later");
+        }
+        if !verify_pin(text.trim(), pin_hash)? {
+            KeyExport::record(db_user.id, &delivery, OUTCOME_WRONG_PIN, &mut conn).await?;
+            anyhow::bail!("❌ Wrong PIN. Start the export again");
+        }
+
+        match self.delivery {
+            KeyDelivery::Chat => {
+                let private_key = cfg
+                    .aptos_client
+                    .export_private_key(&db_user.address)
+                    .await?;
+                KeyExport::record(db_user.id, &delivery, OUTCOME_EXPORTED, &mut conn).await?;
+                let text = format!(
+                    "🔑 Your Private Key \\(keep it safe\\)\\:\n\n`{}`\n\n⚠️ This message will be deleted automatically in 30 seconds\\.",
+                    private_key
+                );
+                let sent_message = bot
+                    .send_message(chat_id, text)
+                    .parse_mode(ParseMode::MarkdownV2)
+                    .await?;
+                delete_after(&bot, chat_id, sent_message.id, 30);
+            }
+            KeyDelivery::Encrypted => {
+                let recipient = parse_recipient_key(
+                    self.recipient
+                        .as_deref()
+                        .ok_or_else(|| anyhow::anyhow!("Recipient key is missing"))?,
+                )?;
+                let private_key = cfg
+                    .aptos_client
+                    .export_private_key(&db_user.address)
+                    .await?;
+                let ciphertext = encrypt_to(&recipient, private_key.as_bytes())?;
+                KeyExport::record(db_user.id, &delivery, OUTCOME_EXPORTED, &mut conn).await?;
+                bot.send_message(
+                    chat_id,
+                    format!(
+                        "🔐 Your private key, encrypted to your key:\n\n<code>{}</code>\n\nIt is hex of the ephemeral X25519 public key (32 bytes), the AES-256-GCM nonce (12 bytes) and the ciphertext. The AES key is SHA3-256 of the shared secret, the ephemeral public key and your public key.",
+                        ciphertext
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+            }
+            KeyDelivery::Link => {
+                let public_url = cfg
+                    .config
+                    .server_config
+                    .public_url
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("❌ One-time links are not enabled"))?;
+                let (token, token_hash) = new_link_token();
+                let query = diesel::insert_into(key_export_links::table).values(NewKeyExportLink {
+                    token_hash,
+                    user_id: db_user.id,
+                    expires_at: chrono::Utc::now().naive_utc()
+                        + chrono::Duration::seconds(LINK_TTL_SECS),
+                });
+                execute_with_better_error(&mut conn, vec![query]).await?;
+                KeyExport::record(db_user.id, &delivery, OUTCOME_LINK_CREATED, &mut conn).await?;
+                let sent_message = bot
+                    .send_message(
+                        chat_id,
+                        format!(
+                            "🔗 Open this link to see your private key. It works once and expires in {} minutes:\n\n{}/api/v1/key-export/{}",
+                            LINK_TTL_SECS / 60,
+                            public_url.trim_end_matches('/'),
+                            token
+                        ),
+                    )
+                    // a preview would fetch the link before the user does
+                    .link_preview_options(LinkPreviewOptions {
+                        is_disabled: true,
+                        url: None,
+                        prefer_small_media: false,
+                        prefer_large_media: false,
+                        show_above_text: false,
+                    })
+                    .await?;
+                delete_after(&bot, chat_id, sent_message.id, LINK_TTL_SECS as u64);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Asks for the recipient key of encrypted exports, then for the PIN
+pub async fn start_export(
+    cfg: &TelegramBot<Cache>,
+    bot: &teloxide::Bot,
+    chat_id: ChatId,
+    delivery: KeyDelivery,
+) -> anyhow::Result<()> {
+    if delivery != KeyDelivery::Encrypted {
+        return ask_export_pin(cfg, bot, chat_id, delivery, None).await;
+    }
+    bot.send_message(
+        chat_id,
+        "Reply with the hex encoded X25519 public key to encrypt your private key to:",
+    )
+    .reply_markup(ForceReply::new().selective())
+    .await?;
+    let mut state = cfg.state.lock().await;
+    state.insert(chat_id, PendingState::ExportRecipientKey);
+    Ok(())
+}
+
+pub async fn ask_export_pin(
+    cfg: &TelegramBot<Cache>,
+    bot: &teloxide::Bot,
+    chat_id: ChatId,
+    delivery: KeyDelivery,
+    recipient: Option<String>,
+) -> anyhow::Result<()> {
+    bot.send_message(chat_id, "🔒 Reply with your PIN to export your key:")
+        .reply_markup(ForceReply::new().selective())
+        .await?;
+    let mut state = cfg.state.lock().await;
+    state.insert(
+        chat_id,
+        PendingState::ExportPin {
+            delivery,
+            recipient,
+        },
+    );
+    Ok(())
+}
+
+async fn is_rate_limited(db_user: &User, conn: &mut DbPoolConnection<'_>) -> anyhow::Result<bool> {
+    let now = chrono::Utc::now().naive_utc();
+    let attempts = KeyExport::count_since(
+        db_user.id,
+        now - chrono::Duration::hours(1),
+        &[OUTCOME_WRONG_PIN, OUTCOME_EXPORTED, OUTCOME_LINK_CREATED],
+        conn,
+    )
+    .await?;
+    let exports = KeyExport::count_since(
+        db_user.id,
+        now - chrono::Duration::days(1),
+        &[OUTCOME_EXPORTED, OUTCOME_LINK_CREATED],
+        conn,
+    )
+    .await?;
+    Ok(attempts >= MAX_ATTEMPTS_PER_HOUR || exports >= MAX_EXPORTS_PER_DAY)
+}
+
+fn delete_after(
+    bot: &teloxide::Bot,
+    chat_id: ChatId,
+    message_id: teloxide::types::MessageId,
+    secs: u64,
+) {
+    let bot = bot.clone();
+    tokio::spawn(async move {
+        sleep(Duration::from_secs(secs)).await;
+        if let Err(e) = bot.delete_message(chat_id, message_id).await {
+            tracing::error!("Failed to delete private key temporary message: {:?}", e);
+        }
+    });
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use teloxide::prelude::Requester;
+
+use crate::{
+    cache::Cache,
+    telegram_bot::{
+        TelegramBot,
+        states::{StateProcessor, export_pin::ask_export_pin},
+    },
+    utils::key_export::{KeyDelivery, parse_recipient_key},
+};
+
+pub struct ExportRecipientKey;
+
+#[async_trait::async_trait]
+impl StateProcessor for ExportRecipientKey {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: teloxide::Bot,
+        msg: teloxide::types::Message,
+        text: String,
+    ) -> anyhow::Result<()> {
+        let chat_id = msg.chat.id;
+        if let Err(err) = parse_recipient_key(&text) {
+            bot.send_message(chat_id, format!("{}, try again", err))
+                .await?;
+            return Ok(());
+        }
+        ask_export_pin(
+            &cfg,
+            &bot,
+            chat_id,
+            KeyDelivery::Encrypted,
+            Some(text.trim().to_string()),
+        )
+        .await
+    }
+}
@@ -1,4 +1,4 @@
-use std::{str::FromStr, sync::Arc};
+use std::sync::Arc;
 
 use anyhow::Context;
 use bigdecimal::BigDecimal;
@@ -40,54 +40,46 @@ impl StateProcessor for ExternalWithdrawAddress {
             state.remove(&chat_id);
         }
 
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let token = cfg.collateral_token(&mut conn).await?;
         let processing_message = bot
             .send_message(
                 chat_id,
                 format!(
-                    "Processing your request to send <b>{} USDC</b> to wallet <code>{}</code>",
+                    "Processing your request to send <b>{} {}</b> to wallet <code>{}</code>",
                     self.amount.clone(),
+                    token.symbol,
                     text
                 ),
             )
             .parse_mode(ParseMode::Html)
             .await?;
 
-        let mut conn = get_db_connection(&cfg.pool).await?;
         let db_user = User::get_by_telegram_id(tg_id, &mut conn)
             .await?
             .ok_or_else(|| {
                 anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
             })?;
 
-        let scaled_amount = &self.amount * BigDecimal::from_str("1000000")?;
-        let amount_u64 = scaled_amount.with_scale(0).to_string().parse::<u64>()?;
-        let payload = transfer_fungible_asset(
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
-            &address.to_string(),
-            amount_u64,
-        )?;
-        let txn = cfg
-            .aptos_client
-            .sign_txn_with_turnkey_and_fee_payer(&db_user.address, &db_user.public_key, payload)
-            .await?;
-
-        let txn_hash = cfg.aptos_client.submit_transaction_and_wait(txn).await?;
-
-        tracing::info!(
-            "{} transferred usdc to {}: https://explorer.aptoslabs.com/txn/{}?network=decibel",
-            db_user.address,
-            text,
-            txn_hash.clone()
-        );
+        let amount_u64 = token.to_base_units(&self.amount)?;
+        let payload = transfer_fungible_asset(&token.address, &address.to_string(), amount_u64)?;
+        let txn = cfg.sign_txn(&db_user, payload, &mut conn).await?;
 
-        bot.edit_message_text(
-            chat_id,
-            processing_message.id,
-            format!("✅ Txn completed! <a href='https://explorer.aptoslabs.com/txn/{}?network=decibel'>View Txn</a>", txn_hash),
+        cfg.enqueue_transaction(
+            &db_user,
+            "withdraw",
+            format!(
+                "Sent {} {} to <code>{}</code>",
+                self.amount, token.symbol, text
+            ),
+            txn,
+            &processing_message,
+            &mut conn,
         )
-        .parse_mode(ParseMode::Html)
         .await?;
 
+        tracing::info!("{} queued transfer to {}", db_user.address, text);
+
         Ok(())
     }
 }
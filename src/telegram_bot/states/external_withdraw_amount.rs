@@ -9,6 +9,7 @@ use crate::{
         TelegramBot,
         states::{PendingState, StateProcessor},
     },
+    utils::database_connection::get_db_connection,
 };
 
 pub struct ExternalWithdrawAmount {
@@ -39,9 +40,14 @@ impl StateProcessor for ExternalWithdrawAmount {
             state.remove(&chat_id);
         }
 
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let token = cfg.collateral_token(&mut conn).await?;
         if amount > self.balance {
-            bot.send_message(chat_id, "You don't have enough USDC balance")
-                .await?;
+            bot.send_message(
+                chat_id,
+                format!("You don't have enough {} balance", token.symbol),
+            )
+            .await?;
             return Ok(());
         };
 
@@ -57,10 +63,10 @@ impl StateProcessor for ExternalWithdrawAmount {
 
         let text = format!(
             "🌐 <b>Withdraw to External Wallet</b>\n\n\
-            Amount to send: <b>{} USDC</b>\n\n\
+            Amount to send: <b>{} {}</b>\n\n\
             Please enter the destination wallet address where you want to send the funds:\n\
             ⚠️ Double-check the address — once submitted, the transaction will be sent automatically and cannot be reversed.",
-            amount
+            amount, token.symbol
         );
 
         bot.send_message(chat_id, text)
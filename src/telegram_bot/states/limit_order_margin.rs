@@ -2,11 +2,11 @@ use crate::{
     cache::Cache,
     models::db::users::User,
     telegram_bot::{TelegramBot, actions::UserAction, states::StateProcessor},
-    utils::{database_connection::get_db_connection, view_requests::view_fa_balance_request},
+    utils::database_connection::get_db_connection,
 };
 use anyhow::Context;
 use bigdecimal::BigDecimal;
-use std::{str::FromStr, sync::Arc};
+use std::sync::Arc;
 use teloxide::{
     payloads::SendMessageSetters,
     prelude::*,
@@ -51,32 +51,30 @@ impl StateProcessor for LimitOrderMargin {
             .ok_or_else(|| {
                 anyhow::anyhow!("Wallet not created yet. Type /start to create wallet")
             })?;
-        let request = view_fa_balance_request(
-            "0x6555ba01030b366f91c999ac943325096495b339d81e216a2af45e1023609f02",
-            &db_user.address,
-        )?;
-        let response = cfg.aptos_client.view(&request).await?;
-        let balance_json = response.get(0).cloned().unwrap_or(serde_json::json!("0"));
-        let balance_str = serde_json::from_value::<String>(balance_json)?;
-        let balance = BigDecimal::from_str(&balance_str)?;
-        let divisor = BigDecimal::from(10u64.pow(6));
-        let usdc = balance / divisor;
+        let token = cfg.collateral_token(&mut conn).await?;
+        let usdc = cfg.token_balance(&token, &db_user.address).await?;
 
         if usdc < amount {
             bot.send_message(
                 chat_id,
-                format!("Insufficient balance, Available: {}USDC", usdc),
+                format!("Insufficient balance, Available: {}{}", usdc, token.symbol),
             )
             .await?;
             return Ok(());
         };
         let text = format!(
-            "You are placing <b>{}</b> limit order at price <b>{}</b> with margin <b>{} USDC</b>and Leverage <b>{}x</b>",
+            "You are placing <b>{}</b> limit order at price <b>{}</b> with margin <b>{} {}</b>and Leverage <b>{}x</b>",
             self.market_name.clone(),
             self.price,
             amount,
+            token.symbol,
             self.leverage
         );
+        let subaccounts = cfg.subaccounts(&db_user, &mut conn).await?;
+        let subaccount = subaccounts
+            .iter()
+            .position(|subaccount| subaccount.is_primary())
+            .unwrap_or_default() as u8;
         let kb = InlineKeyboardMarkup::new(vec![
             vec![
                 InlineKeyboardButton::callback(
@@ -87,6 +85,7 @@ impl StateProcessor for LimitOrderMargin {
                         price: self.price.clone(),
                         leverage: self.leverage,
                         amount: amount.clone(),
+                        subaccount,
                     }
                     .to_string(),
                 ),
@@ -98,6 +97,7 @@ impl StateProcessor for LimitOrderMargin {
                         price: self.price.clone(),
                         leverage: self.leverage,
                         amount: amount.clone(),
+                        subaccount,
                     }
                     .to_string(),
                 ),
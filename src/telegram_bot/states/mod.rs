@@ -1,14 +1,18 @@
 pub mod custom_slippage;
 pub mod deposit_to_subaccount;
+pub mod export_pin;
+pub mod export_recipient_key;
 pub mod external_withdraw_address;
 pub mod external_withdraw_amount;
 pub mod limit_order_margin;
 pub mod order_margin;
 pub mod order_pair;
+pub mod set_export_pin;
+pub mod subaccount_transfer_amount;
 
 use bigdecimal::BigDecimal;
 
-use crate::{cache::Cache, telegram_bot::TelegramBot};
+use crate::{cache::Cache, telegram_bot::TelegramBot, utils::key_export::KeyDelivery};
 use std::sync::Arc;
 
 #[derive(Debug, Clone)]
@@ -34,6 +38,19 @@ pub enum PendingState {
     ExternalWithdrawAddress {
         amount: BigDecimal,
     },
+    SubaccountTransferAmount {
+        from: u8,
+        to: Option<u8>,
+        withdrawable: BigDecimal,
+    },
+    SetExportPin {
+        delivery: KeyDelivery,
+    },
+    ExportRecipientKey,
+    ExportPin {
+        delivery: KeyDelivery,
+        recipient: Option<String>,
+    },
 }
 
 #[async_trait::async_trait]
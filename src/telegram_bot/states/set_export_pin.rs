@@ -0,0 +1,75 @@
+use std::sync::Arc;
+
+use diesel::{ExpressionMethods, query_dsl::methods::FilterDsl};
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::{ChatId, Requester},
+    types::ForceReply,
+};
+
+use crate::{
+    cache::Cache,
+    schema::users,
+    telegram_bot::{
+        TelegramBot,
+        states::{PendingState, StateProcessor, export_pin::start_export},
+    },
+    utils::{
+        database_connection::get_db_connection,
+        db_execution::execute_with_better_error,
+        key_export::{KeyDelivery, hash_pin, is_valid_pin},
+    },
+};
+
+pub struct SetExportPin {
+    pub delivery: KeyDelivery,
+}
+
+#[async_trait::async_trait]
+impl StateProcessor for SetExportPin {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: teloxide::Bot,
+        msg: teloxide::types::Message,
+        text: String,
+    ) -> anyhow::Result<()> {
+        let chat_id = msg.chat.id;
+        bot.delete_message(chat_id, msg.id).await?;
+        let pin = text.trim();
+        if !is_valid_pin(pin) {
+            bot.send_message(chat_id, "The PIN must be 4 to 8 digits, try again")
+                .await?;
+            return Ok(());
+        }
+
+        let from = msg.from.ok_or_else(|| anyhow::anyhow!("From is missing"))?;
+        let tg_id = from.id.0 as i64;
+
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let query = diesel::update(users::table.filter(users::tg_id.eq(Some(tg_id))))
+            .set(users::pin_hash.eq(Some(hash_pin(pin)?)));
+        execute_with_better_error(&mut conn, vec![query]).await?;
+
+        bot.send_message(chat_id, "✅ PIN saved").await?;
+        start_export(&cfg, &bot, chat_id, self.delivery).await
+    }
+}
+
+/// First export of the user, the PIN guarding every later export is set here
+pub async fn ask_new_pin(
+    cfg: &TelegramBot<Cache>,
+    bot: &teloxide::Bot,
+    chat_id: ChatId,
+    delivery: KeyDelivery,
+) -> anyhow::Result<()> {
+    bot.send_message(
+        chat_id,
+        "🔒 Set a PIN of 4 to 8 digits first. You will need it for every key export",
+    )
+    .reply_markup(ForceReply::new().selective())
+    .await?;
+    let mut state = cfg.state.lock().await;
+    state.insert(chat_id, PendingState::SetExportPin { delivery });
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use bigdecimal::BigDecimal;
+use teloxide::{
+    payloads::SendMessageSetters,
+    prelude::Requester,
+    types::{InlineKeyboardButton, InlineKeyboardMarkup, ParseMode},
+};
+
+use crate::{
+    cache::Cache,
+    telegram_bot::{TelegramBot, actions::UserAction, states::StateProcessor},
+    utils::database_connection::get_db_connection,
+};
+
+pub struct SubaccountTransferAmount {
+    pub from: u8,
+    pub to: Option<u8>,
+    pub withdrawable: BigDecimal,
+}
+
+#[async_trait::async_trait]
+impl StateProcessor for SubaccountTransferAmount {
+    async fn process(
+        &self,
+        cfg: Arc<TelegramBot<Cache>>,
+        bot: teloxide::Bot,
+        msg: teloxide::types::Message,
+        text: String,
+    ) -> anyhow::Result<()> {
+        let chat_id = msg.chat.id;
+        let amount = match text.parse::<BigDecimal>() {
+            Ok(num) if num > BigDecimal::from(0) => num,
+            _ => {
+                bot.send_message(chat_id, "Please enter a valid number")
+                    .await?;
+                return Ok(());
+            }
+        };
+        {
+            let mut state = cfg.state.lock().await;
+            state.remove(&chat_id);
+        }
+        let mut conn = get_db_connection(&cfg.pool).await?;
+        let token = cfg.collateral_token(&mut conn).await?;
+        if amount > self.withdrawable {
+            bot.send_message(
+                chat_id,
+                format!(
+                    "❌ Only {} {} is withdrawable, the rest backs your open positions",
+                    self.withdrawable, token.symbol
+                ),
+            )
+            .await?;
+            return Ok(());
+        };
+
+        let destination = match self.to {
+            Some(to) => format!("account #{}", to + 1),
+            None => "your wallet".to_string(),
+        };
+        let text = format!(
+            "⚡ You're moving <b>{} {}</b> from account #{} to {}.\n\n\
+            Double-check the amount before confirming.",
+            amount,
+            token.symbol,
+            self.from + 1,
+            destination
+        );
+        let markup = InlineKeyboardMarkup::new(vec![vec![
+            InlineKeyboardButton::callback(
+                "✅ Confirm",
+                UserAction::ConfirmSubaccountTransfer {
+                    from: self.from,
+                    to: self.to,
+                    amount,
+                }
+                .to_string(),
+            ),
+            InlineKeyboardButton::callback("❌ Cancel", UserAction::Cancel.to_string()),
+        ]]);
+        bot.send_message(chat_id, text)
+            .parse_mode(ParseMode::Html)
+            .reply_markup(markup)
+            .await?;
+        Ok(())
+    }
+}
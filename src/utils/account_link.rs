@@ -0,0 +1,237 @@
+use std::str::FromStr;
+
+use aptos_crypto::HashValue;
+use aptos_sdk::types::account_address::AccountAddress;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use rand::Rng;
+use uuid::Uuid;
+
+use crate::{
+    models::db::{
+        link_codes::NewLinkCode,
+        users::{CUSTODY_CUSTODIAL, User},
+    },
+    schema::{trading_delegations, users},
+    utils::{database_utils::DbPoolConnection, trading::Trading},
+};
+
+/// How long a link code can be redeemed
+pub const LINK_CODE_TTL_SECS: i64 = 600;
+
+/// Without look-alike characters, codes are typed by hand
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 8;
+
+/// Random link code and the hash stored in place of it
+pub fn new_link_code() -> (String, String) {
+    let mut rng = rand::thread_rng();
+    let code = (0..CODE_LENGTH)
+        .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+        .collect::<String>();
+    let code_hash = hash_link_code(&code);
+    (code, code_hash)
+}
+
+pub fn hash_link_code(code: &str) -> String {
+    HashValue::sha3_256_of(code.trim().to_uppercase().as_bytes()).to_hex()
+}
+
+/// Outcome of linking a wallet to a Telegram user
+pub struct LinkedUser {
+    pub user: User,
+    /// The wallet had delegated trading to the bot wallet of the merged user, it has to
+    /// delegate to the bot wallet of `user` again
+    pub redelegate: bool,
+}
+
+/// Makes `tg_user` hold `wallet_address` too, using up the link code `code_hash` in the same
+/// DB transaction. When the wallet already has its own user, the two are merged: the one whose
+/// bot wallet holds funds or txns is kept and the other row points to it through
+/// `merged_into`. Linking is refused when both of them do, rather than stranding either.
+pub async fn link_wallet(
+    trading: &Trading,
+    code_hash: &str,
+    tg_user: User,
+    wallet_address: &str,
+    wallet_user: Option<User>,
+    conn: &mut DbPoolConnection<'_>,
+) -> anyhow::Result<LinkedUser> {
+    let code_hash = code_hash.to_string();
+    let tg_user_id = tg_user.id;
+    let connected_wallet = wallet_address.to_string();
+    match tg_user.connected_wallet.as_deref() {
+        Some(connected_wallet) if is_same_address(connected_wallet, wallet_address) => {
+            use_code(&code_hash, tg_user_id, conn).await?;
+            return Ok(LinkedUser {
+                user: tg_user,
+                redelegate: false,
+            });
+        }
+        Some(_) => anyhow::bail!("Telegram account is already linked to another wallet"),
+        None => {}
+    }
+
+    let Some(wallet_user) = wallet_user else {
+        conn.transaction(|conn| {
+            Box::pin(async move {
+                use_code(&code_hash, tg_user_id, conn).await?;
+                diesel::update(users::table.find(tg_user_id))
+                    .set(users::connected_wallet.eq(Some(connected_wallet)))
+                    .execute(conn)
+                    .await?;
+                Ok::<_, anyhow::Error>(())
+            })
+        })
+        .await?;
+        return Ok(LinkedUser {
+            user: User {
+                connected_wallet: Some(wallet_address.to_string()),
+                ..tg_user
+            },
+            redelegate: false,
+        });
+    };
+    if wallet_user.tg_id.is_some() {
+        anyhow::bail!("Wallet is already linked to another Telegram account");
+    }
+
+    let tg_user_has_funds = trading.has_custodial_state(&tg_user, conn).await?;
+    let wallet_user_has_funds = trading.has_custodial_state(&wallet_user, conn).await?;
+    match (tg_user_has_funds, wallet_user_has_funds) {
+        (true, true) => anyhow::bail!(
+            "Both accounts hold funds or pending txns. Withdraw everything from the trading wallet of one of them, then link again"
+        ),
+        (false, true) => merge_into_wallet_user(code_hash, tg_user, wallet_user, conn).await,
+        _ => merge_into_tg_user(code_hash, tg_user, wallet_address, wallet_user, conn).await,
+    }
+}
+
+/// Addresses are compared parsed, the same wallet may be written with or without leading zeros
+fn is_same_address(a: &str, b: &str) -> bool {
+    match (AccountAddress::from_str(a), AccountAddress::from_str(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Moves the wallet to `tg_user`, the bot wallet of `wallet_user` is left unused
+async fn merge_into_tg_user(
+    code_hash: String,
+    tg_user: User,
+    wallet_address: &str,
+    wallet_user: User,
+    conn: &mut DbPoolConnection<'_>,
+) -> anyhow::Result<LinkedUser> {
+    let tg_user_id = tg_user.id;
+    let wallet_user_id = wallet_user.id;
+    let connected_wallet = wallet_address.to_string();
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            use_code(&code_hash, tg_user_id, conn).await?;
+            // The connected wallet is unique, so it leaves the merged user first
+            diesel::update(users::table.find(wallet_user_id))
+                .set((
+                    users::connected_wallet.eq(None::<String>),
+                    users::custody_mode.eq(CUSTODY_CUSTODIAL),
+                    users::merged_into.eq(Some(tg_user_id)),
+                ))
+                .execute(conn)
+                .await?;
+            diesel::update(
+                trading_delegations::table
+                    .filter(trading_delegations::user_id.eq(wallet_user_id))
+                    .filter(trading_delegations::revoked_at.is_null()),
+            )
+            .set(trading_delegations::revoked_at.eq(chrono::Utc::now().naive_utc()))
+            .execute(conn)
+            .await?;
+            diesel::update(users::table.find(tg_user_id))
+                .set(users::connected_wallet.eq(Some(connected_wallet)))
+                .execute(conn)
+                .await?;
+            Ok::<_, anyhow::Error>(())
+        })
+    })
+    .await?;
+    tracing::info!("Merged user {} into {}", wallet_user_id, tg_user_id);
+
+    Ok(LinkedUser {
+        redelegate: wallet_user.is_delegated(),
+        user: User {
+            connected_wallet: Some(wallet_address.to_string()),
+            ..tg_user
+        },
+    })
+}
+
+/// Moves the Telegram account and its settings to `wallet_user`, whose bot wallet is in use,
+/// the bot wallet of `tg_user` is left unused
+async fn merge_into_wallet_user(
+    code_hash: String,
+    tg_user: User,
+    wallet_user: User,
+    conn: &mut DbPoolConnection<'_>,
+) -> anyhow::Result<LinkedUser> {
+    let tg_user_id = tg_user.id;
+    let wallet_user_id = wallet_user.id;
+    let merged = User {
+        tg_id: tg_user.tg_id,
+        tg_username: tg_user.tg_username.clone(),
+        slippage: tg_user.slippage,
+        degen_mode: tg_user.degen_mode,
+        pin_hash: tg_user.pin_hash.clone().or(wallet_user.pin_hash.clone()),
+        ..wallet_user
+    };
+    let (tg_id, tg_username, slippage, degen_mode, pin_hash) = (
+        merged.tg_id,
+        merged.tg_username.clone(),
+        merged.slippage,
+        merged.degen_mode,
+        merged.pin_hash.clone(),
+    );
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            use_code(&code_hash, tg_user_id, conn).await?;
+            // The Telegram id is unique, so it leaves the merged user first
+            diesel::update(users::table.find(tg_user_id))
+                .set((
+                    users::tg_id.eq(None::<i64>),
+                    users::tg_username.eq(None::<String>),
+                    users::merged_into.eq(Some(wallet_user_id)),
+                ))
+                .execute(conn)
+                .await?;
+            diesel::update(users::table.find(wallet_user_id))
+                .set((
+                    users::tg_id.eq(tg_id),
+                    users::tg_username.eq(tg_username),
+                    users::slippage.eq(slippage),
+                    users::degen_mode.eq(degen_mode),
+                    users::pin_hash.eq(pin_hash),
+                ))
+                .execute(conn)
+                .await?;
+            Ok::<_, anyhow::Error>(())
+        })
+    })
+    .await?;
+    tracing::info!("Merged user {} into {}", tg_user_id, wallet_user_id);
+
+    Ok(LinkedUser {
+        user: merged,
+        redelegate: false,
+    })
+}
+
+/// Marks the link code of `user_id` used, failing when another request used it first
+async fn use_code(
+    code_hash: &str,
+    user_id: Uuid,
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<()> {
+    match NewLinkCode::redeem(code_hash, conn).await? {
+        Some(code_user_id) if code_user_id == user_id => Ok(()),
+        _ => anyhow::bail!("Code is unknown, used or expired"),
+    }
+}
@@ -0,0 +1,133 @@
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, KeyInit, Payload},
+};
+use aptos_crypto::HashValue;
+use diesel_async::RunQueryDsl;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    models::db::api_keys::{ApiKey, NewApiKey, SCOPE_READ, SCOPE_TRADE},
+    schema::api_keys,
+    utils::database_utils::DbPoolConnection,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+/// Unix time in milliseconds the request was signed at
+pub const API_TIMESTAMP_HEADER: &str = "x-api-timestamp";
+/// Hex HMAC-SHA256 of `timestamp + METHOD + path?query + body`, keyed with the key secret
+pub const API_SIGNATURE_HEADER: &str = "x-api-signature";
+
+const KEY_ID_PREFIX: &str = "dk_";
+const MAX_NAME_LENGTH: usize = 64;
+
+/// Encrypts `secret` of `key_id` with AES-256-GCM keyed with SHA3-256 of the server secret.
+/// The key id is authenticated along, so a secret can't be moved to another key.
+fn encrypt_secret(server_secret: &str, key_id: &str, secret: &str) -> anyhow::Result<String> {
+    let nonce: [u8; 12] = rand::random();
+    let ciphertext = cipher(server_secret)
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: secret.as_bytes(),
+                aad: key_id.as_bytes(),
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt API key secret"))?;
+    Ok(hex::encode([nonce.as_slice(), &ciphertext].concat()))
+}
+
+fn decrypt_secret(server_secret: &str, api_key: &ApiKey) -> anyhow::Result<Vec<u8>> {
+    let encrypted = hex::decode(&api_key.encrypted_secret)?;
+    if encrypted.len() < 12 {
+        anyhow::bail!("API key has no secret");
+    }
+    let (nonce, ciphertext) = encrypted.split_at(12);
+    cipher(server_secret)
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: api_key.key_id.as_bytes(),
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("Failed to decrypt API key secret"))
+}
+
+fn cipher(server_secret: &str) -> Aes256Gcm {
+    let key = HashValue::sha3_256_of(server_secret.as_bytes());
+    Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.to_vec()))
+}
+
+/// Creates a key for the user and returns it with its secret, which is not shown again
+pub async fn create_api_key(
+    config: &Config,
+    user_id: Uuid,
+    name: &str,
+    scope: &str,
+    conn: &mut DbPoolConnection<'_>,
+) -> anyhow::Result<(ApiKey, String)> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LENGTH {
+        anyhow::bail!("Name must be 1 to {} characters", MAX_NAME_LENGTH);
+    }
+    if scope != SCOPE_READ && scope != SCOPE_TRADE {
+        anyhow::bail!("Scope must be {} or {}", SCOPE_READ, SCOPE_TRADE);
+    }
+    let max_keys = config.api_key_config.max_keys_per_user;
+    if ApiKey::get_by_user_id(user_id, conn).await?.len() >= max_keys {
+        anyhow::bail!(
+            "You already have {} API keys, revoke one to create another",
+            max_keys
+        );
+    }
+
+    let key_id = format!(
+        "{}{}",
+        KEY_ID_PREFIX,
+        hex::encode(rand::random::<[u8; 16]>())
+    );
+    let secret = hex::encode(rand::random::<[u8; 32]>());
+    let new_key = NewApiKey {
+        encrypted_secret: encrypt_secret(&config.api_key_config.secret, &key_id, &secret)?,
+        user_id,
+        key_id,
+        name: name.to_string(),
+        scope: scope.to_string(),
+        rate_limit_per_minute: i32::try_from(config.api_key_config.rate_limit_per_minute)?,
+    };
+    let api_key = diesel::insert_into(api_keys::table)
+        .values(new_key)
+        .get_result::<ApiKey>(conn)
+        .await?;
+    Ok((api_key, secret))
+}
+
+/// Fails unless `signature` is the hex HMAC of the request made with the secret of `api_key`
+pub fn verify_signature(
+    server_secret: &str,
+    api_key: &ApiKey,
+    timestamp: &str,
+    method: &str,
+    path_and_query: &str,
+    body: &[u8],
+    signature: &str,
+) -> anyhow::Result<()> {
+    let secret = decrypt_secret(server_secret, api_key)
+        .map_err(|_| anyhow::anyhow!("API key is no longer valid, create a new one"))?;
+    let signature =
+        hex::decode(signature).map_err(|_| anyhow::anyhow!("Signature is not valid hex"))?;
+
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(&secret)?;
+    mac.update(timestamp.as_bytes());
+    mac.update(method.as_bytes());
+    mac.update(path_and_query.as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| anyhow::anyhow!("Signature does not match"))
+}
@@ -1,238 +1,276 @@
 use anyhow::Context;
-use aptos_crypto::{SigningKey, ValidCryptoMaterialStringExt, ed25519::*, traits::signing_message};
+use aptos_crypto::{HashValue, ValidCryptoMaterialStringExt, ed25519::Ed25519PublicKey};
 use aptos_sdk::coin_client::TransferOptions;
 use aptos_sdk::rest_client::Client;
-use aptos_sdk::rest_client::aptos_api_types::ViewRequest;
+use aptos_sdk::rest_client::aptos_api_types::{
+    Event, PendingTransaction, Transaction, TransactionData, ViewRequest,
+};
+use aptos_sdk::rest_client::error::RestError;
 use aptos_sdk::transaction_builder::TransactionBuilder;
 use aptos_sdk::types::account_address::AccountAddress;
 use aptos_sdk::types::chain_id::ChainId;
-use aptos_sdk::types::transaction::SignedTransaction;
-use aptos_sdk::types::transaction::authenticator::{AccountAuthenticator, AuthenticationKey};
-use aptos_sdk::types::transaction::{RawTransactionWithData, TransactionPayload};
+use aptos_sdk::types::transaction::{
+    SignedTransaction, Transaction as OnChainTransaction, TransactionPayload,
+};
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::{str::FromStr, sync::Arc};
-use turnkey_client::generated::immutable::common::v1::{
-    AddressFormat, Curve, HashFunction, PathFormat, PayloadEncoding,
-};
-use turnkey_client::generated::{
-    CreateWalletIntent, ExportWalletAccountIntent, SignRawPayloadIntentV2, WalletAccountParams,
-};
-use turnkey_client::{TurnkeyClient, TurnkeyP256ApiKey};
-use turnkey_enclave_encrypt::{ExportClient, QuorumPublicKey};
 
-use crate::config::Config;
+use crate::{
+    config::Config,
+    signer::{Signer, new_signer},
+    utils::{
+        chain_client::{
+            ChainClient, GasPayer, LedgerInfo, Simulation, TransactionStatus, TxnRejected,
+            assemble_signed_txn, signing_message, simulation_txn,
+        },
+        sequence_numbers::{SequenceNumbers, is_sequence_number_error},
+        signing_policy::SigningPolicy,
+        sponsors::SponsorPool,
+        vm_status::explain_vm_status,
+    },
+};
 
 pub struct AptosClient {
     client: Client,
-    turnkey: TurnkeyClient<TurnkeyP256ApiKey>,
-    config: Arc<Config>,
+    signer: Arc<dyn Signer>,
+    sponsors: SponsorPool,
+    signing_policy: SigningPolicy,
     chain_id: ChainId,
+    sequence_numbers: SequenceNumbers,
 }
 
 impl AptosClient {
     pub async fn new(config: Arc<Config>) -> anyhow::Result<Self> {
         // aptos
-        let client = Client::new(url::Url::from_str(&config.aptos_base_url)?);
-        // turnkey
-        let turnkey_api_key = TurnkeyP256ApiKey::from_strings(
-            config.turnkey_config.api_private_key.clone(),
-            Some(config.turnkey_config.api_public_key.to_string()),
-        )
-        .context("Failed to get turnkey p256 api key")?;
-
-        let turnkey = TurnkeyClient::builder()
-            .api_key(turnkey_api_key)
-            .build()
-            .context("Failed to build turnkey client")?;
+        let client = Client::new(url::Url::from_str(&config.network().node_url)?);
+        let signer = new_signer(&config)?;
+        let sponsors = SponsorPool::new(&config.admin_config.sponsor_private_keys)
+            .context("Failed to load sponsor keys")?;
+        let signing_policy = SigningPolicy::new(&config).context("Invalid signing policy")?;
 
         let chain_id = client.get_index().await?.inner().chain_id;
+        if chain_id != config.network().chain_id {
+            anyhow::bail!(
+                "Node {} reports chain id {}, network profile `{}` expects {}",
+                config.network().node_url,
+                chain_id,
+                config.network,
+                config.network().chain_id
+            );
+        }
 
         Ok(Self {
             client,
-            turnkey,
-            config,
+            signer,
+            sponsors,
+            signing_policy,
             chain_id: ChainId::new(chain_id),
+            sequence_numbers: SequenceNumbers::default(),
         })
     }
 
-    pub async fn create_new_wallet_on_turnkey(
-        self: &Arc<Self>,
-        wallet_name: &str,
-    ) -> anyhow::Result<(String, String, String)> {
-        let create_wallet_result = self
-            .turnkey
-            .create_wallet(
-                self.config.turnkey_config.organization_id.clone(),
-                self.turnkey.current_timestamp(),
-                CreateWalletIntent {
-                    wallet_name: wallet_name.into(),
-                    accounts: vec![WalletAccountParams {
-                        curve: Curve::Ed25519,
-                        path_format: PathFormat::Bip32,
-                        path: "m/44'/60'/0'/0".to_string(),
-                        address_format: AddressFormat::Aptos,
-                    }],
-                    mnemonic_length: None,
-                },
-            )
-            .await?;
-
-        assert_eq!(create_wallet_result.addresses.len(), 1);
-
-        let wallet_address = create_wallet_result
-            .addresses
-            .first()
-            .context("Failed to get first wallet address")?;
-        let wallet_id = create_wallet_result.wallet_id;
-        let private_key = Self::export_private_key(&Arc::clone(&self), &wallet_address).await?;
-        let public_key = Self::get_public_key_str_from_private_key_str(&private_key)?;
-        Ok((wallet_id, wallet_address.clone(), public_key))
+    async fn submit(&self, txn: &SignedTransaction) -> anyhow::Result<PendingTransaction> {
+        match self.client.submit(txn).await {
+            Ok(pending_transaction) => Ok(pending_transaction.into_inner()),
+            Err(err) => {
+                if is_sequence_number_error(&err.to_string()) {
+                    tracing::warn!("Resyncing sequence number of {}: {}", txn.sender(), err);
+                    self.sequence_numbers.resync(txn.sender());
+                }
+                Err(err.into())
+            }
+        }
     }
+}
 
-    pub async fn export_private_key(
-        self: &Arc<Self>,
-        wallet_address: &str,
-    ) -> anyhow::Result<String> {
-        let mut export_client = ExportClient::new(&QuorumPublicKey::production_signer());
-        let export_wallet_result = self
-            .turnkey
-            .export_wallet_account(
-                self.config.turnkey_config.organization_id.clone(),
-                self.turnkey.current_timestamp(),
-                ExportWalletAccountIntent {
-                    address: wallet_address.into(),
-                    target_public_key: export_client.target_public_key()?,
-                },
-            )
-            .await?;
-
-        let export_bundle = export_wallet_result.export_bundle;
-        let private_key_bytes = export_client.decrypt_private_key(
-            export_bundle,
-            self.config.turnkey_config.organization_id.clone(),
-        )?;
-
-        let private_key = hex::encode(&private_key_bytes);
-        Ok(private_key)
+#[async_trait::async_trait]
+impl ChainClient for AptosClient {
+    async fn create_new_wallet(
+        &self,
+        wallet_name: &str,
+    ) -> anyhow::Result<(String, String, String)> {
+        self.signer.create_wallet(wallet_name).await
     }
 
-    fn get_public_key_str_from_private_key_str(private_key: &str) -> anyhow::Result<String> {
-        let mut seed = [0u8; 32];
-        let seed_bytes = hex::decode(private_key).context("Failed to decode private key string")?; // Remove the 0x prefix
-        seed[..seed_bytes.len()].copy_from_slice(&seed_bytes);
-        let sender_key = Ed25519PrivateKey::try_from(seed_bytes.as_slice())?;
-
-        let sender_public_key = Ed25519PublicKey::from(&sender_key);
-        let sender_public_key_str = sender_public_key.to_string();
-        Ok(sender_public_key_str)
+    async fn export_private_key(&self, wallet_address: &str) -> anyhow::Result<String> {
+        self.signer.export_private_key(wallet_address).await
     }
 
-    pub async fn sign_txn_with_turnkey_and_fee_payer(
+    async fn sign_txn(
         &self,
         sender_address: &str,
         sender_public_key: &str,
         payload: TransactionPayload,
-    ) -> anyhow::Result<SignedTransaction> {
+        gas_payer: GasPayer,
+    ) -> anyhow::Result<(SignedTransaction, u64)> {
         let options = TransferOptions::default();
         let sender = AccountAddress::from_hex_literal(sender_address)?;
-
-        let sequence_number = self.client.get_account_sequence_number(sender).await?;
-        let raw_txn = TransactionBuilder::new(
-            payload,
-            SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + options.timeout_secs,
-            self.chain_id.clone(),
-        )
-        .sender(sender)
-        .sequence_number(*sequence_number.inner())
-        .build();
-
-        let mut seed = [0u8; 32];
-        let seed_bytes = hex::decode(&self.config.admin_config.sponsor_private_key)?;
-        seed[..seed_bytes.len()].copy_from_slice(&seed_bytes);
-
-        let sponsor_key = Ed25519PrivateKey::try_from(seed_bytes.as_slice())?;
-        let sponsor_address =
-            AuthenticationKey::ed25519(&Ed25519PublicKey::from(&sponsor_key)).account_address();
-
-        let message =
-            RawTransactionWithData::new_fee_payer(raw_txn.clone(), vec![], sponsor_address);
-        let sponsor_signature = sponsor_key.sign(&message).unwrap();
-        let sponsor_authenticator =
-            AccountAuthenticator::ed25519(Ed25519PublicKey::from(&sponsor_key), sponsor_signature);
-
-        let signing_message = signing_message(&message)?;
-        let txn_sign_result = self
-            .turnkey
-            .sign_raw_payload(
-                self.config.turnkey_config.organization_id.clone(),
-                self.turnkey.current_timestamp(),
-                SignRawPayloadIntentV2 {
-                    sign_with: sender.to_string(),
-                    payload: hex::encode(signing_message),
-                    encoding: PayloadEncoding::Hexadecimal,
-                    hash_function: HashFunction::NotApplicable,
-                },
-            )
+        self.signing_policy.check(sender, &payload)?;
+        let sender_public_key = Ed25519PublicKey::from_encoded_string(sender_public_key)
+            .context("Failed to parse sender public key")?;
+        let sponsor = match gas_payer {
+            GasPayer::Sponsor => Some(self.sponsors.next()),
+            GasPayer::Sender => None,
+        };
+        let expiration_timestamp_secs =
+            SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + options.timeout_secs;
+        let build_raw_txn = |payload: TransactionPayload, sequence_number: u64| {
+            TransactionBuilder::new(payload, expiration_timestamp_secs, self.chain_id.clone())
+                .sender(sender)
+                .sequence_number(sequence_number)
+                .build()
+        };
+
+        let _sender_lock = self.sequence_numbers.lock_sender(sender).await;
+        // simulated with the number the node expects next, a locally allocated one is ahead of
+        // it while other txns of the sender are in flight and would be SEQUENCE_NUMBER_TOO_NEW
+        let on_chain = *self
+            .client
+            .get_account_sequence_number(sender)
+            .await?
+            .inner();
+        let simulation = self
+            .simulate_transaction(&simulation_txn(
+                build_raw_txn(payload.clone(), on_chain),
+                &sender_public_key,
+                sponsor,
+            ))
             .await?;
+        if let Some(vm_status) = simulation.vm_status {
+            tracing::warn!("Simulation failed for {}: {}", sender, vm_status);
+            return Err(TxnRejected(format!("❌ {}", explain_vm_status(&vm_status))).into());
+        }
 
-        let r_hex = format!("{:0>64}", txn_sign_result.r);
-        let s_hex = format!("{:0>64}", txn_sign_result.s);
+        let sequence_number = self.sequence_numbers.allocate(sender, on_chain);
+        let raw_txn = build_raw_txn(payload, sequence_number);
+        let message = signing_message(&raw_txn, sponsor);
+        let signed_txn =
+            self.signer
+                .sign_message(sender, &message)
+                .await
+                .and_then(|sender_signature| {
+                    assemble_signed_txn(
+                        raw_txn,
+                        sender_public_key,
+                        sender_signature,
+                        sponsor,
+                        &message,
+                    )
+                });
+        match signed_txn {
+            Ok(signed_txn) => Ok((signed_txn, simulation.gas_fee)),
+            Err(err) => {
+                self.sequence_numbers.release(sender, sequence_number);
+                Err(err)
+            }
+        }
+    }
 
-        let txn_signature_hex = format!("{}{}", r_hex, s_hex);
+    fn sponsor_addresses(&self) -> Vec<AccountAddress> {
+        self.sponsors.addresses()
+    }
 
-        assert_eq!(txn_signature_hex.len(), 128);
+    async fn view(&self, request: &ViewRequest) -> anyhow::Result<Vec<serde_json::Value>> {
+        let response = self.client.view(request, None).await?;
+        Ok(response.into_inner())
+    }
 
-        let sig_bytes = hex::decode(txn_signature_hex).unwrap();
+    async fn simulate_transaction(&self, txn: &SignedTransaction) -> anyhow::Result<Simulation> {
+        let simulation_response = self.client.simulate(txn).await?;
+        let simulation_result = simulation_response
+            .inner()
+            .first()
+            .cloned()
+            .context("Simulation returned no result")?;
+        let mut vm_status: Option<String> = None;
+        if !simulation_result.info.success {
+            vm_status = Some(simulation_result.info.vm_status)
+        }
+        Ok(Simulation {
+            gas_fee: simulation_result.info.gas_used.0 * simulation_result.request.gas_unit_price.0,
+            vm_status,
+        })
+    }
 
-        assert_eq!(sig_bytes.len(), 64);
+    async fn submit_transaction_and_wait_with_events(
+        &self,
+        txn: SignedTransaction,
+    ) -> anyhow::Result<(String, Vec<Event>)> {
+        let pending_transaction = self.submit(&txn).await?;
 
-        let sender_signature = Ed25519Signature::try_from(sig_bytes.as_slice())?;
+        let committed_transaction = self
+            .client
+            .wait_for_transaction(&pending_transaction)
+            .await?;
 
-        let sender_authenticator: AccountAuthenticator = AccountAuthenticator::ed25519(
-            Ed25519PublicKey::from_encoded_string(sender_public_key).unwrap(),
-            sender_signature,
-        );
+        let events = match committed_transaction.into_inner() {
+            Transaction::UserTransaction(user_txn) => user_txn.events,
+            _ => vec![],
+        };
 
-        let signed_txn = SignedTransaction::new_fee_payer(
-            raw_txn,
-            sender_authenticator.clone(),
-            vec![],
-            vec![],
-            sponsor_address,
-            sponsor_authenticator,
-        );
+        Ok((pending_transaction.hash.to_string(), events))
+    }
 
-        Ok(signed_txn)
+    async fn submit_transaction(&self, txn: &SignedTransaction) -> anyhow::Result<String> {
+        let pending_transaction = self.submit(txn).await?;
+        Ok(pending_transaction.hash.to_string())
     }
 
-    pub async fn view(&self, request: &ViewRequest) -> anyhow::Result<Vec<serde_json::Value>> {
-        let response = self.client.view(request, None).await?;
-        Ok(response.into_inner())
+    async fn transaction_status(&self, txn_hash: &str) -> anyhow::Result<TransactionStatus> {
+        let hash = HashValue::from_str(txn_hash.trim_start_matches("0x"))?;
+        match self.client.get_transaction_by_hash(hash).await {
+            Ok(response) => Ok(match response.into_inner() {
+                Transaction::UserTransaction(user_txn) if user_txn.info.success => {
+                    TransactionStatus::Committed {
+                        gas_fee: user_txn.info.gas_used.0 * user_txn.request.gas_unit_price.0,
+                    }
+                }
+                Transaction::UserTransaction(user_txn) => {
+                    TransactionStatus::Failed(user_txn.info.vm_status)
+                }
+                _ => TransactionStatus::Pending,
+            }),
+            Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => {
+                Ok(TransactionStatus::NotFound)
+            }
+            Err(err) => Err(err.into()),
+        }
     }
 
-    pub async fn simulate_transaction(
+    async fn committed_transaction(
         &self,
-        txn: &SignedTransaction,
-    ) -> anyhow::Result<Option<String>> {
-        let simulation_response = self.client.simulate(txn).await?;
-        let simulation_result = simulation_response.inner()[0].clone();
-        let mut err: Option<String> = None;
-        if !simulation_result.info.success {
-            err = Some(simulation_result.info.vm_status)
+        txn_hash: &str,
+    ) -> anyhow::Result<Option<SignedTransaction>> {
+        let hash = HashValue::from_str(txn_hash.trim_start_matches("0x"))?;
+        match self.client.get_transaction_by_hash_bcs(hash).await {
+            Ok(response) => Ok(match response.into_inner() {
+                TransactionData::OnChain(txn) if txn.info.status().is_success() => {
+                    match txn.transaction {
+                        OnChainTransaction::UserTransaction(signed_txn) => Some(signed_txn),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }),
+            Err(RestError::Api(err)) if err.status_code.as_u16() == 404 => Ok(None),
+            Err(err) => Err(err.into()),
         }
-        Ok(err)
     }
 
-    pub async fn submit_transaction_and_wait(
-        &self,
-        txn: SignedTransaction,
-    ) -> anyhow::Result<String> {
-        let pending_transaction = self.client.submit(&txn).await?;
+    fn release_sequence_number(&self, txn: &SignedTransaction) {
+        self.sequence_numbers
+            .release(txn.sender(), txn.sequence_number());
+    }
 
-        self.client
-            .wait_for_transaction(pending_transaction.inner())
-            .await?;
+    fn resync_sequence_number(&self, sender: AccountAddress) {
+        self.sequence_numbers.resync(sender);
+    }
 
-        Ok(pending_transaction.inner().hash.to_string())
+    async fn ledger_info(&self) -> anyhow::Result<LedgerInfo> {
+        let index = self.client.get_index().await?.into_inner();
+        Ok(LedgerInfo {
+            chain_id: index.chain_id,
+            ledger_version: index.ledger_version.0,
+        })
     }
 }
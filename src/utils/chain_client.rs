@@ -0,0 +1,260 @@
+use std::{ops::Deref, sync::Arc};
+
+use aptos_crypto::ed25519::{Ed25519PublicKey, Ed25519Signature};
+use aptos_sdk::{
+    rest_client::aptos_api_types::{Event, ViewRequest},
+    types::{
+        account_address::AccountAddress,
+        transaction::{
+            RawTransaction, RawTransactionWithData, SignedTransaction, TransactionPayload,
+            authenticator::{AccountAuthenticator, TransactionAuthenticator},
+        },
+    },
+};
+
+use crate::{signer::SigningMessage, utils::sponsors::Sponsor};
+
+/// Everything the bot, http server and workers need from the chain. Implemented by
+/// `AptosClient` against a node and by `ChainSimulator` in memory for tests.
+#[async_trait::async_trait]
+pub trait ChainClient: Send + Sync + 'static {
+    /// Creates a custodial wallet, returning its wallet id, address and public key
+    async fn create_new_wallet(
+        &self,
+        wallet_name: &str,
+    ) -> anyhow::Result<(String, String, String)>;
+
+    async fn export_private_key(&self, wallet_address: &str) -> anyhow::Result<String>;
+
+    /// Signs `payload` for the sender, failing with `TxnRejected` if the txn does not pass
+    /// simulation or the signing policy. Returns the
+    /// gas fee in octas the simulation charged. The txn gets a locally allocated sequence
+    /// number, which has to be given back with `release_sequence_number` if it is not
+    /// submitted.
+    async fn sign_txn(
+        &self,
+        sender_address: &str,
+        sender_public_key: &str,
+        payload: TransactionPayload,
+        gas_payer: GasPayer,
+    ) -> anyhow::Result<(SignedTransaction, u64)>;
+
+    /// Fee payer accounts of sponsored txns
+    fn sponsor_addresses(&self) -> Vec<AccountAddress>;
+
+    async fn view(&self, request: &ViewRequest) -> anyhow::Result<Vec<serde_json::Value>>;
+
+    async fn simulate_transaction(&self, txn: &SignedTransaction) -> anyhow::Result<Simulation>;
+
+    async fn submit_transaction_and_wait_with_events(
+        &self,
+        txn: SignedTransaction,
+    ) -> anyhow::Result<(String, Vec<Event>)>;
+
+    async fn submit_transaction_and_wait(&self, txn: SignedTransaction) -> anyhow::Result<String> {
+        let (txn_hash, _) = self.submit_transaction_and_wait_with_events(txn).await?;
+        Ok(txn_hash)
+    }
+
+    /// Submits without waiting for the txn to commit, returning its hash
+    async fn submit_transaction(&self, txn: &SignedTransaction) -> anyhow::Result<String>;
+
+    async fn transaction_status(&self, txn_hash: &str) -> anyhow::Result<TransactionStatus>;
+
+    /// User txn with `txn_hash` that committed successfully, `None` while it is pending, when
+    /// it failed or is unknown
+    async fn committed_transaction(
+        &self,
+        txn_hash: &str,
+    ) -> anyhow::Result<Option<SignedTransaction>>;
+
+    /// Gives back the sequence number of a signed txn that will never be submitted
+    fn release_sequence_number(&self, txn: &SignedTransaction);
+
+    /// Drops the locally allocated sequence numbers of `sender`, e.g. after its txn expired
+    fn resync_sequence_number(&self, sender: AccountAddress);
+
+    /// Chain id and head version reported by the node
+    async fn ledger_info(&self) -> anyhow::Result<LedgerInfo>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasPayer {
+    /// One of the sponsor accounts pays as fee payer
+    Sponsor,
+    Sender,
+}
+
+impl GasPayer {
+    /// Who paid for an already signed txn, so it can be re-signed the same way
+    pub fn of(txn: &SignedTransaction) -> Self {
+        match txn.authenticator_ref() {
+            TransactionAuthenticator::FeePayer { .. } => Self::Sponsor,
+            _ => Self::Sender,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    /// Gas fee in octas
+    pub gas_fee: u64,
+    /// Set when the txn would fail
+    pub vm_status: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct LedgerInfo {
+    pub chain_id: u8,
+    pub ledger_version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    Pending,
+    /// `gas_fee` in octas is what the txn was charged on chain
+    Committed {
+        gas_fee: u64,
+    },
+    Failed(String),
+    /// Not in mempool nor on chain, it either expired or was never submitted
+    NotFound,
+}
+
+/// A txn refused before it was signed: it fails simulation, or the signing policy or the
+/// delegation does not allow it. The message is meant for the user.
+#[derive(Debug)]
+pub struct TxnRejected(pub String);
+
+impl std::fmt::Display for TxnRejected {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TxnRejected {}
+
+/// Txn returned by `Trading::sign_txn`. Its sequence number is given back when it is dropped
+/// before being queued or submitted, e.g. when the handler that signed it timed out.
+pub struct SignedTxn {
+    txn: Option<SignedTransaction>,
+    aptos_client: Arc<dyn ChainClient>,
+    sponsored_gas_fee: Option<u64>,
+}
+
+impl SignedTxn {
+    pub fn new(txn: SignedTransaction, aptos_client: Arc<dyn ChainClient>) -> Self {
+        Self {
+            txn: Some(txn),
+            aptos_client,
+            sponsored_gas_fee: None,
+        }
+    }
+
+    /// Marks the txn as sponsored, `gas_fee` being counted against the user's daily quota
+    pub fn sponsored(mut self, gas_fee: u64) -> Self {
+        self.sponsored_gas_fee = Some(gas_fee);
+        self
+    }
+
+    pub fn sponsored_gas_fee(&self) -> Option<u64> {
+        self.sponsored_gas_fee
+    }
+
+    /// Hands the txn over to whoever submits it, its sequence number is theirs from now on
+    pub fn into_inner(mut self) -> SignedTransaction {
+        self.txn
+            .take()
+            .expect("SignedTxn is only emptied when consumed")
+    }
+}
+
+impl Deref for SignedTxn {
+    type Target = SignedTransaction;
+
+    fn deref(&self) -> &SignedTransaction {
+        self.txn
+            .as_ref()
+            .expect("SignedTxn is only emptied when consumed")
+    }
+}
+
+impl Drop for SignedTxn {
+    fn drop(&mut self) {
+        if let Some(txn) = self.txn.take() {
+            tracing::info!(
+                "Releasing sequence number {} of {}, its txn was never submitted",
+                txn.sequence_number(),
+                txn.sender()
+            );
+            self.aptos_client.release_sequence_number(&txn);
+        }
+    }
+}
+
+/// `raw_txn` with dummy signatures, to simulate it before anything is signed
+pub fn simulation_txn(
+    raw_txn: RawTransaction,
+    sender_public_key: &Ed25519PublicKey,
+    sponsor: Option<&Sponsor>,
+) -> SignedTransaction {
+    match sponsor {
+        Some(sponsor) => SignedTransaction::new_fee_payer(
+            raw_txn,
+            AccountAuthenticator::ed25519(
+                sender_public_key.clone(),
+                Ed25519Signature::dummy_signature(),
+            ),
+            vec![],
+            vec![],
+            sponsor.address,
+            AccountAuthenticator::ed25519(
+                sponsor.public_key.clone(),
+                Ed25519Signature::dummy_signature(),
+            ),
+        ),
+        None => SignedTransaction::new(
+            raw_txn,
+            sender_public_key.clone(),
+            Ed25519Signature::dummy_signature(),
+        ),
+    }
+}
+
+/// What the sender signs, the fee payer message when a sponsor pays the gas
+pub fn signing_message(raw_txn: &RawTransaction, sponsor: Option<&Sponsor>) -> SigningMessage {
+    match sponsor {
+        Some(sponsor) => SigningMessage::FeePayer(RawTransactionWithData::new_fee_payer(
+            raw_txn.clone(),
+            vec![],
+            sponsor.address,
+        )),
+        None => SigningMessage::Transaction(raw_txn.clone()),
+    }
+}
+
+/// Puts the sender signature over `message` on `raw_txn`, along with the sponsor's own
+pub fn assemble_signed_txn(
+    raw_txn: RawTransaction,
+    sender_public_key: Ed25519PublicKey,
+    sender_signature: Ed25519Signature,
+    sponsor: Option<&Sponsor>,
+    message: &SigningMessage,
+) -> anyhow::Result<SignedTransaction> {
+    Ok(match (sponsor, message) {
+        (Some(sponsor), SigningMessage::FeePayer(fee_payer_message)) => {
+            SignedTransaction::new_fee_payer(
+                raw_txn,
+                AccountAuthenticator::ed25519(sender_public_key, sender_signature),
+                vec![],
+                vec![],
+                sponsor.address,
+                AccountAuthenticator::ed25519(
+                    sponsor.public_key.clone(),
+                    sponsor.sign(fee_payer_message)?,
+                ),
+            )
+        }
+        _ => SignedTransaction::new(raw_txn, sender_public_key, sender_signature),
+    })
+}
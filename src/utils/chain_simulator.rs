@@ -0,0 +1,706 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Mutex, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use aptos_crypto::{HashValue, ValidCryptoMaterialStringExt, ed25519::Ed25519PublicKey};
+use aptos_sdk::{
+    bcs,
+    rest_client::aptos_api_types::{Event, ViewRequest},
+    transaction_builder::TransactionBuilder,
+    types::{
+        account_address::AccountAddress,
+        chain_id::ChainId,
+        transaction::{
+            SignedTransaction, TransactionPayload, authenticator::TransactionAuthenticator,
+        },
+    },
+};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+
+use crate::{
+    signer::{Signer, generate_private_key, local::LocalSigner},
+    utils::{
+        chain_client::{
+            ChainClient, GasPayer, LedgerInfo, Simulation, TransactionStatus, TxnRejected,
+            assemble_signed_txn, signing_message, simulation_txn,
+        },
+        sequence_numbers::{SequenceNumbers, is_sequence_number_error},
+        signing_policy::SigningPolicy,
+        sponsors::SponsorPool,
+        vm_status::explain_vm_status,
+    },
+};
+
+const TXN_TIMEOUT_SECS: u64 = 30;
+/// Gas fee in octas charged for every txn, to the sponsor or the sender
+const GAS_FEE: u64 = 1_000;
+/// APT given to each sponsor on creation
+const SPONSOR_FUNDING: u64 = 10_000_000_000;
+
+/// Open position of a subaccount on one market
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Size in market units, negative for shorts
+    pub size: i128,
+    /// Price of the last fill in market units
+    pub last_price: u64,
+}
+
+/// In-memory stand-in for a node running the Decibel contracts. Txns commit as soon as they
+/// are submitted and only the entry functions the bot builds are understood: fungible asset
+/// transfers, the faucet mint and the `dex_accounts` subaccount, margin and order functions.
+/// Orders fill immediately at their limit price.
+pub struct ChainSimulator {
+    contract_address: AccountAddress,
+    collateral_metadata: AccountAddress,
+    chain_id: ChainId,
+    signer: LocalSigner,
+    sponsors: SponsorPool,
+    signing_policy: Option<SigningPolicy>,
+    state: Mutex<ChainState>,
+    sequence_numbers: SequenceNumbers,
+}
+
+#[derive(Debug, Clone, Default)]
+struct ChainState {
+    /// Primary store balances keyed by owner and FA metadata address
+    balances: HashMap<(AccountAddress, AccountAddress), u64>,
+    /// Subaccounts created on top of the primary one, in creation order
+    subaccounts: HashMap<AccountAddress, Vec<AccountAddress>>,
+    /// Collateral keyed by subaccount and FA metadata address
+    collateral: HashMap<(AccountAddress, AccountAddress), u64>,
+    /// Keyed by subaccount and market address
+    positions: HashMap<(AccountAddress, AccountAddress), Position>,
+    /// Owner to the wallet it delegated trading to
+    delegations: HashMap<AccountAddress, AccountAddress>,
+    sequence_numbers: HashMap<AccountAddress, u64>,
+    transactions: HashMap<String, TransactionStatus>,
+    /// Txns that committed successfully keyed by hash
+    committed_txns: HashMap<String, SignedTransaction>,
+}
+
+impl ChainSimulator {
+    /// Creates the simulator with `sponsor_count` funded sponsors
+    pub fn new(
+        contract_address: &str,
+        collateral_metadata: &str,
+        sponsor_count: usize,
+    ) -> anyhow::Result<Self> {
+        let sponsor_keys = (0..sponsor_count)
+            .map(|_| Ok(hex::encode(generate_private_key()?.to_bytes())))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let sponsors = SponsorPool::new(&sponsor_keys)?;
+
+        let mut state = ChainState::default();
+        for sponsor in sponsors.addresses() {
+            state
+                .balances
+                .insert((sponsor, AccountAddress::TEN), SPONSOR_FUNDING);
+        }
+
+        Ok(Self {
+            contract_address: AccountAddress::from_str(contract_address)?,
+            collateral_metadata: AccountAddress::from_str(collateral_metadata)?,
+            chain_id: ChainId::test(),
+            signer: LocalSigner::new(&[])?,
+            sponsors,
+            signing_policy: None,
+            state: Mutex::new(state),
+            sequence_numbers: SequenceNumbers::default(),
+        })
+    }
+
+    /// Checks payloads against `signing_policy` before signing, like `AptosClient` does
+    pub fn with_signing_policy(mut self, signing_policy: SigningPolicy) -> Self {
+        self.signing_policy = Some(signing_policy);
+        self
+    }
+
+    /// Credits `amount` base units of `fa` to the primary store of `owner`, APT being `0xa`
+    pub fn fund(&self, owner: &str, fa: &str, amount: u64) -> anyhow::Result<()> {
+        let key = (
+            AccountAddress::from_str(owner)?,
+            AccountAddress::from_str(fa)?,
+        );
+        *self.state().balances.entry(key).or_default() += amount;
+        Ok(())
+    }
+
+    pub fn balance(&self, owner: &str, fa: &str) -> anyhow::Result<u64> {
+        let key = (
+            AccountAddress::from_str(owner)?,
+            AccountAddress::from_str(fa)?,
+        );
+        Ok(self.state().balances.get(&key).copied().unwrap_or_default())
+    }
+
+    /// Subaccounts of `owner`, the primary one first
+    pub fn subaccounts(&self, owner: &str) -> anyhow::Result<Vec<String>> {
+        let owner = AccountAddress::from_str(owner)?;
+        Ok(self
+            .state()
+            .subaccounts_of(owner)
+            .iter()
+            .map(|subaccount| subaccount.to_standard_string())
+            .collect())
+    }
+
+    pub fn collateral(&self, subaccount: &str, fa: &str) -> anyhow::Result<u64> {
+        let key = (
+            AccountAddress::from_str(subaccount)?,
+            AccountAddress::from_str(fa)?,
+        );
+        Ok(self
+            .state()
+            .collateral
+            .get(&key)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    pub fn position(&self, subaccount: &str, market: &str) -> anyhow::Result<Option<Position>> {
+        let key = (
+            AccountAddress::from_str(subaccount)?,
+            AccountAddress::from_str(market)?,
+        );
+        Ok(self.state().positions.get(&key).cloned())
+    }
+
+    /// Sequence number of the next txn `sender` can commit
+    pub fn sequence_number(&self, sender: &str) -> anyhow::Result<u64> {
+        Ok(self
+            .state()
+            .sequence_number(AccountAddress::from_str(sender)?))
+    }
+
+    fn state(&self) -> MutexGuard<'_, ChainState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn submit(&self, txn: &SignedTransaction) -> anyhow::Result<(String, Vec<Event>)> {
+        let result = {
+            let mut state = self.state();
+            state.commit(self.contract_address, self.collateral_metadata, txn)
+        };
+        let result = match result {
+            Ok(result) => result,
+            Err(vm_status) => {
+                if is_sequence_number_error(&vm_status) {
+                    tracing::warn!(
+                        "Resyncing sequence number of {}: {}",
+                        txn.sender(),
+                        vm_status
+                    );
+                    self.sequence_numbers.resync(txn.sender());
+                }
+                anyhow::bail!("Transaction rejected: {}", vm_status);
+            }
+        };
+
+        let txn_hash = format!("0x{}", txn.committed_hash().to_hex());
+        let (status, events) = match result {
+            Ok(events) => (TransactionStatus::Committed { gas_fee: GAS_FEE }, events),
+            Err(vm_status) => (TransactionStatus::Failed(vm_status), vec![]),
+        };
+        let events = events
+            .into_iter()
+            .map(|(typ, data)| to_event(typ, data))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        {
+            let mut state = self.state();
+            if matches!(status, TransactionStatus::Committed { .. }) {
+                state.committed_txns.insert(txn_hash.clone(), txn.clone());
+            }
+            state.transactions.insert(txn_hash.clone(), status);
+        }
+        Ok((txn_hash, events))
+    }
+}
+
+#[async_trait::async_trait]
+impl ChainClient for ChainSimulator {
+    async fn create_new_wallet(
+        &self,
+        wallet_name: &str,
+    ) -> anyhow::Result<(String, String, String)> {
+        self.signer.create_wallet(wallet_name).await
+    }
+
+    async fn export_private_key(&self, wallet_address: &str) -> anyhow::Result<String> {
+        self.signer.export_private_key(wallet_address).await
+    }
+
+    async fn sign_txn(
+        &self,
+        sender_address: &str,
+        sender_public_key: &str,
+        payload: TransactionPayload,
+        gas_payer: GasPayer,
+    ) -> anyhow::Result<(SignedTransaction, u64)> {
+        let sender = AccountAddress::from_str(sender_address)?;
+        if let Some(signing_policy) = &self.signing_policy {
+            signing_policy.check(sender, &payload)?;
+        }
+        let sender_public_key = Ed25519PublicKey::from_encoded_string(sender_public_key)
+            .context("Failed to parse sender public key")?;
+        let sponsor = match gas_payer {
+            GasPayer::Sponsor => Some(self.sponsors.next()),
+            GasPayer::Sender => None,
+        };
+        let expiration_timestamp_secs =
+            SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() + TXN_TIMEOUT_SECS;
+        let build_raw_txn = |payload: TransactionPayload, sequence_number: u64| {
+            TransactionBuilder::new(payload, expiration_timestamp_secs, self.chain_id.clone())
+                .sender(sender)
+                .sequence_number(sequence_number)
+                .build()
+        };
+
+        // same flow as `AptosClient::sign_txn`, so tests exercise its sequence number handling
+        let on_chain = self.state().sequence_number(sender);
+        let simulation = self
+            .simulate_transaction(&simulation_txn(
+                build_raw_txn(payload.clone(), on_chain),
+                &sender_public_key,
+                sponsor,
+            ))
+            .await?;
+        if let Some(vm_status) = simulation.vm_status {
+            tracing::warn!("Simulation failed for {}: {}", sender, vm_status);
+            return Err(TxnRejected(format!("❌ {}", explain_vm_status(&vm_status))).into());
+        }
+
+        let sequence_number = self.sequence_numbers.allocate(sender, on_chain);
+        let raw_txn = build_raw_txn(payload, sequence_number);
+        let message = signing_message(&raw_txn, sponsor);
+        let signed_txn =
+            self.signer
+                .sign_message(sender, &message)
+                .await
+                .and_then(|sender_signature| {
+                    assemble_signed_txn(
+                        raw_txn,
+                        sender_public_key,
+                        sender_signature,
+                        sponsor,
+                        &message,
+                    )
+                });
+        match signed_txn {
+            Ok(signed_txn) => Ok((signed_txn, simulation.gas_fee)),
+            Err(err) => {
+                self.sequence_numbers.release(sender, sequence_number);
+                Err(err)
+            }
+        }
+    }
+
+    fn sponsor_addresses(&self) -> Vec<AccountAddress> {
+        self.sponsors.addresses()
+    }
+
+    async fn view(&self, request: &ViewRequest) -> anyhow::Result<Vec<serde_json::Value>> {
+        let module = request.function.module.name.as_str();
+        let function = request.function.name.as_str();
+        let address_arg = |idx: usize| -> anyhow::Result<AccountAddress> {
+            let value = request
+                .arguments
+                .get(idx)
+                .and_then(|value| value.as_str())
+                .with_context(|| format!("Missing address argument {} of {}", idx, function))?;
+            Ok(AccountAddress::from_str(value)?)
+        };
+
+        let state = self.state();
+        match (module, function) {
+            ("primary_fungible_store", "balance") => {
+                let key = (address_arg(0)?, address_arg(1)?);
+                let balance = state.balances.get(&key).copied().unwrap_or_default();
+                Ok(vec![json!(balance.to_string())])
+            }
+            ("dex_accounts", "primary_subaccount") => Ok(vec![json!(
+                primary_subaccount(address_arg(0)?).to_standard_string()
+            )]),
+            _ => anyhow::bail!("View function {}::{} is not simulated", module, function),
+        }
+    }
+
+    async fn simulate_transaction(&self, txn: &SignedTransaction) -> anyhow::Result<Simulation> {
+        // runs against a copy, like the node the simulated txn leaves no trace
+        let mut state = self.state().clone();
+        let vm_status = match state.commit(self.contract_address, self.collateral_metadata, txn) {
+            Ok(Ok(_)) => None,
+            Ok(Err(vm_status)) | Err(vm_status) => Some(vm_status),
+        };
+        Ok(Simulation {
+            gas_fee: GAS_FEE,
+            vm_status,
+        })
+    }
+
+    async fn submit_transaction_and_wait_with_events(
+        &self,
+        txn: SignedTransaction,
+    ) -> anyhow::Result<(String, Vec<Event>)> {
+        let (txn_hash, events) = self.submit(&txn)?;
+        if let Some(TransactionStatus::Failed(vm_status)) = self.state().transactions.get(&txn_hash)
+        {
+            anyhow::bail!("Transaction {} failed: {}", txn_hash, vm_status);
+        }
+        Ok((txn_hash, events))
+    }
+
+    async fn submit_transaction(&self, txn: &SignedTransaction) -> anyhow::Result<String> {
+        let (txn_hash, _) = self.submit(txn)?;
+        Ok(txn_hash)
+    }
+
+    async fn transaction_status(&self, txn_hash: &str) -> anyhow::Result<TransactionStatus> {
+        Ok(self
+            .state()
+            .transactions
+            .get(txn_hash)
+            .cloned()
+            .unwrap_or(TransactionStatus::NotFound))
+    }
+
+    async fn committed_transaction(
+        &self,
+        txn_hash: &str,
+    ) -> anyhow::Result<Option<SignedTransaction>> {
+        Ok(self.state().committed_txns.get(txn_hash).cloned())
+    }
+
+    fn release_sequence_number(&self, txn: &SignedTransaction) {
+        self.sequence_numbers
+            .release(txn.sender(), txn.sequence_number());
+    }
+
+    fn resync_sequence_number(&self, sender: AccountAddress) {
+        self.sequence_numbers.resync(sender);
+    }
+
+    async fn ledger_info(&self) -> anyhow::Result<LedgerInfo> {
+        Ok(LedgerInfo {
+            chain_id: self.chain_id.id(),
+            ledger_version: self.state().transactions.len() as u64,
+        })
+    }
+}
+
+/// Result of running a payload: the emitted events as type and data, or the vm status
+type ExecutionResult = Result<Vec<(String, serde_json::Value)>, String>;
+
+impl ChainState {
+    fn sequence_number(&self, sender: AccountAddress) -> u64 {
+        self.sequence_numbers
+            .get(&sender)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn subaccounts_of(&self, owner: AccountAddress) -> Vec<AccountAddress> {
+        let mut subaccounts = vec![primary_subaccount(owner)];
+        subaccounts.extend(self.subaccounts.get(&owner).into_iter().flatten());
+        subaccounts
+    }
+
+    fn owner_of(&self, subaccount: AccountAddress) -> Option<AccountAddress> {
+        self.subaccounts
+            .iter()
+            .find(|(_, subaccounts)| subaccounts.contains(&subaccount))
+            .map(|(owner, _)| *owner)
+    }
+
+    fn owns(&self, sender: AccountAddress, subaccount: AccountAddress) -> bool {
+        primary_subaccount(sender) == subaccount || self.owner_of(subaccount) == Some(sender)
+    }
+
+    fn can_trade(&self, sender: AccountAddress, subaccount: AccountAddress) -> bool {
+        self.owns(sender, subaccount)
+            || self.delegations.iter().any(|(owner, delegate)| {
+                *delegate == sender && self.subaccounts_of(*owner).contains(&subaccount)
+            })
+    }
+
+    /// Checks the txn can be included and runs it. The outer error means the txn was rejected
+    /// before execution, the inner one that it committed as failed.
+    fn commit(
+        &mut self,
+        contract: AccountAddress,
+        collateral_metadata: AccountAddress,
+        txn: &SignedTransaction,
+    ) -> Result<ExecutionResult, String> {
+        let sender = txn.sender();
+        let expected = self.sequence_number(sender);
+        if txn.sequence_number() < expected {
+            return Err("SEQUENCE_NUMBER_TOO_OLD".to_string());
+        }
+        if txn.sequence_number() > expected {
+            return Err("SEQUENCE_NUMBER_TOO_NEW".to_string());
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| err.to_string())?
+            .as_secs();
+        if txn.expiration_timestamp_secs() <= now {
+            return Err("TRANSACTION_EXPIRED".to_string());
+        }
+        let gas_payer = match txn.authenticator_ref() {
+            TransactionAuthenticator::FeePayer {
+                fee_payer_address, ..
+            } => *fee_payer_address,
+            _ => sender,
+        };
+        let gas_balance = self
+            .balances
+            .entry((gas_payer, AccountAddress::TEN))
+            .or_default();
+        if *gas_balance < GAS_FEE {
+            return Err("INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE".to_string());
+        }
+
+        // aborted txns roll back their writes but still use up the sequence number and gas
+        let snapshot = self.clone();
+        let result = self.execute(contract, collateral_metadata, sender, txn.payload());
+        if result.is_err() {
+            *self = snapshot;
+        }
+        *self
+            .balances
+            .entry((gas_payer, AccountAddress::TEN))
+            .or_default() -= GAS_FEE;
+        self.sequence_numbers.insert(sender, expected + 1);
+        Ok(result)
+    }
+
+    fn execute(
+        &mut self,
+        contract: AccountAddress,
+        collateral_metadata: AccountAddress,
+        sender: AccountAddress,
+        payload: &TransactionPayload,
+    ) -> ExecutionResult {
+        let TransactionPayload::EntryFunction(entry_function) = payload else {
+            return Err("FEATURE_UNDER_GATING".to_string());
+        };
+        let module = entry_function.module();
+        let function = entry_function.function().as_str();
+        let args = entry_function.args();
+
+        if *module.address() == AccountAddress::ONE {
+            return match (module.name().as_str(), function) {
+                ("primary_fungible_store", "transfer") => {
+                    let fa: AccountAddress = arg(args, 0)?;
+                    let to: AccountAddress = arg(args, 1)?;
+                    let amount: u64 = arg(args, 2)?;
+                    self.withdraw(sender, fa, amount)?;
+                    self.deposit(to, fa, amount);
+                    Ok(vec![])
+                }
+                _ => Err("FUNCTION_RESOLUTION_FAILURE".to_string()),
+            };
+        }
+        if *module.address() != contract {
+            return Err("LINKER_ERROR".to_string());
+        }
+
+        match (module.name().as_str(), function) {
+            ("usdc", "mint") => {
+                let receiver: AccountAddress = arg(args, 0)?;
+                let amount: u64 = arg(args, 1)?;
+                self.deposit(receiver, collateral_metadata, amount);
+                Ok(vec![])
+            }
+            ("dex_accounts", "create_new_subaccount") => {
+                let subaccounts = self.subaccounts.entry(sender).or_default();
+                let seed = format!("subaccount_{}", subaccounts.len() + 1);
+                let subaccount = derive_address(sender, seed.as_bytes());
+                subaccounts.push(subaccount);
+                Ok(vec![(
+                    format!(
+                        "{}::dex_accounts::SubaccountCreatedEvent",
+                        contract.to_hex_literal()
+                    ),
+                    json!({
+                        "owner": sender.to_standard_string(),
+                        "subaccount": subaccount.to_standard_string(),
+                    }),
+                )])
+            }
+            ("dex_accounts", "deposit_to_subaccount_at") => {
+                let subaccount: AccountAddress = arg(args, 0)?;
+                let fa: AccountAddress = arg(args, 1)?;
+                let amount: u64 = arg(args, 2)?;
+                if !self.owns(sender, subaccount) {
+                    return Err(dex_abort("ESUBACCOUNT_NOT_FOUND", 0x60001));
+                }
+                self.withdraw(sender, fa, amount)?;
+                *self.collateral.entry((subaccount, fa)).or_default() += amount;
+                Ok(vec![])
+            }
+            ("dex_accounts", "withdraw_from_subaccount") => {
+                let subaccount: AccountAddress = arg(args, 0)?;
+                let fa: AccountAddress = arg(args, 1)?;
+                let amount: u64 = arg(args, 2)?;
+                if !self.owns(sender, subaccount) {
+                    return Err(dex_abort("ESUBACCOUNT_NOT_FOUND", 0x60001));
+                }
+                let collateral = self.collateral.entry((subaccount, fa)).or_default();
+                if *collateral < amount {
+                    return Err(dex_abort("EINSUFFICIENT_BALANCE", 0x10002));
+                }
+                *collateral -= amount;
+                self.deposit(sender, fa, amount);
+                Ok(vec![])
+            }
+            ("dex_accounts", "place_order_to_subaccount") => {
+                let subaccount: AccountAddress = arg(args, 0)?;
+                let market: AccountAddress = arg(args, 1)?;
+                let price: u64 = arg(args, 2)?;
+                let size: u64 = arg(args, 3)?;
+                let is_buy: bool = arg(args, 4)?;
+                if !self.can_trade(sender, subaccount) {
+                    return Err(dex_abort("ENOT_AUTHORIZED", 0x50003));
+                }
+                if size == 0 {
+                    return Err(dex_abort("EORDER_SIZE_TOO_SMALL", 0x10004));
+                }
+                if price == 0 {
+                    return Err(dex_abort("EINVALID_PRICE", 0x10005));
+                }
+                let collateral = self
+                    .collateral
+                    .get(&(subaccount, collateral_metadata))
+                    .copied()
+                    .unwrap_or_default();
+                if collateral == 0 {
+                    return Err(dex_abort("EINSUFFICIENT_MARGIN", 0x10006));
+                }
+                let position = self.positions.entry((subaccount, market)).or_default();
+                position.size += if is_buy {
+                    size as i128
+                } else {
+                    -(size as i128)
+                };
+                position.last_price = price;
+                Ok(vec![
+                    (
+                        format!("{}::dex_accounts::TradeEvent", contract.to_hex_literal()),
+                        json!({
+                            "subaccount": subaccount.to_standard_string(),
+                            "market": market.to_standard_string(),
+                            "price": price.to_string(),
+                            "size": size.to_string(),
+                            "is_buy": is_buy,
+                        }),
+                    ),
+                    (
+                        format!(
+                            "{}::dex_accounts::PositionUpdateEvent",
+                            contract.to_hex_literal()
+                        ),
+                        json!({
+                            "subaccount": subaccount.to_standard_string(),
+                            "market": market.to_standard_string(),
+                            "size": position.size.to_string(),
+                            "last_price": position.last_price.to_string(),
+                        }),
+                    ),
+                ])
+            }
+            ("dex_accounts", "cancel_order_to_subaccount") => {
+                let subaccount: AccountAddress = arg(args, 0)?;
+                if !self.can_trade(sender, subaccount) {
+                    return Err(dex_abort("ENOT_AUTHORIZED", 0x50003));
+                }
+                // orders fill as they are placed, there is no book to cancel from
+                Err(dex_abort("EORDER_NOT_FOUND", 0x60007))
+            }
+            ("dex_accounts", "delegate_trading_to") => {
+                let delegate: AccountAddress = arg(args, 0)?;
+                self.delegations.insert(sender, delegate);
+                Ok(vec![])
+            }
+            ("dex_accounts", "revoke_delegation") => {
+                let delegate: AccountAddress = arg(args, 0)?;
+                if self.delegations.get(&sender) == Some(&delegate) {
+                    self.delegations.remove(&sender);
+                }
+                Ok(vec![])
+            }
+            _ => Err("FUNCTION_RESOLUTION_FAILURE".to_string()),
+        }
+    }
+
+    fn withdraw(
+        &mut self,
+        owner: AccountAddress,
+        fa: AccountAddress,
+        amount: u64,
+    ) -> Result<(), String> {
+        if amount == 0 {
+            return Err(fa_abort("EAMOUNT_CANNOT_BE_ZERO", 0x10001));
+        }
+        let balance = self.balances.entry((owner, fa)).or_default();
+        if *balance < amount {
+            return Err(fa_abort("EINSUFFICIENT_BALANCE", 0x10004));
+        }
+        *balance -= amount;
+        Ok(())
+    }
+
+    fn deposit(&mut self, owner: AccountAddress, fa: AccountAddress, amount: u64) {
+        *self.balances.entry((owner, fa)).or_default() += amount;
+    }
+}
+
+fn arg<T: DeserializeOwned>(args: &[Vec<u8>], idx: usize) -> Result<T, String> {
+    let bytes = args
+        .get(idx)
+        .ok_or_else(|| "NUMBER_OF_ARGUMENTS_MISMATCH".to_string())?;
+    bcs::from_bytes(bytes).map_err(|_| "FAILED_TO_DESERIALIZE_ARGUMENT".to_string())
+}
+
+fn fa_abort(reason: &str, code: u64) -> String {
+    format!(
+        "Move abort in 0x1::fungible_asset: {}(0x{:x}): simulated",
+        reason, code
+    )
+}
+
+fn dex_abort(reason: &str, code: u64) -> String {
+    format!(
+        "Move abort in dex_accounts: {}(0x{:x}): simulated",
+        reason, code
+    )
+}
+
+fn primary_subaccount(owner: AccountAddress) -> AccountAddress {
+    derive_address(owner, b"primary_subaccount")
+}
+
+fn derive_address(owner: AccountAddress, seed: &[u8]) -> AccountAddress {
+    let mut bytes = owner.to_vec();
+    bytes.extend_from_slice(seed);
+    AccountAddress::from_bytes(HashValue::sha3_256_of(&bytes).to_vec())
+        .expect("sha3-256 hashes are valid addresses")
+}
+
+fn to_event(typ: String, data: serde_json::Value) -> anyhow::Result<Event> {
+    let event = serde_json::from_value(json!({
+        "guid": {
+            "creation_number": "0",
+            "account_address": AccountAddress::ZERO.to_hex_literal(),
+        },
+        "sequence_number": "0",
+        "type": typ,
+        "data": data,
+    }))?;
+    Ok(event)
+}
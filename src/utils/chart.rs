@@ -0,0 +1,115 @@
+use std::io::Cursor;
+
+use anyhow::Context;
+use chrono::{TimeZone, Utc};
+use image::{ImageFormat, RgbImage};
+use plotters::prelude::*;
+
+use crate::utils::decibel_api::Candlestick;
+
+const WIDTH: u32 = 700;
+const HEIGHT: u32 = 500;
+
+/// Candle intervals Decibel serves
+pub const INTERVALS: [&str; 11] = [
+    "1m", "5m", "15m", "30m", "1h", "2h", "4h", "8h", "1d", "3d", "1w",
+];
+
+/// Window drawn when none is asked for, 24 hours
+pub const DEFAULT_RANGE_MILLIS: i64 = 86400 * 1000;
+/// Widest window a chart may cover, 90 days
+pub const MAX_RANGE_MILLIS: i64 = 90 * 86400 * 1000;
+/// Most candles a chart may draw, about one per pixel of its width
+pub const MAX_CANDLES: i64 = WIDTH as i64;
+
+/// Milliseconds in a duration such as `30m`, `24h`, `7d` or `1w`
+pub fn parse_duration_millis(duration: &str) -> Option<i64> {
+    let unit_index = duration.find(|c: char| !c.is_ascii_digit())?;
+    let (amount, unit) = duration.split_at(unit_index);
+    let amount = amount.parse::<i64>().ok().filter(|amount| *amount > 0)?;
+    let unit_millis = match unit {
+        "m" => 60 * 1000,
+        "h" => 3600 * 1000,
+        "d" => 86400 * 1000,
+        "w" => 7 * 86400 * 1000,
+        _ => return None,
+    };
+    amount.checked_mul(unit_millis)
+}
+
+/// PNG candlestick chart of `candles`, blocking so it belongs on a blocking thread
+pub fn render_candlestick_png(
+    market_name: &str,
+    interval: &str,
+    candles: &[Candlestick],
+) -> anyhow::Result<Vec<u8>> {
+    let first = candles.first().context("No candles to draw")?;
+    let last = candles.last().context("No candles to draw")?;
+
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&RGBColor(37, 37, 37))?;
+
+        let (header, chart_area) = root.split_vertically(50);
+        header.draw_text(
+            market_name,
+            &("monospace", 24).into_font().color(&WHITE),
+            (640 - 40, 25),
+        )?;
+
+        let y_min = candles.iter().map(|c| c.low).fold(f64::INFINITY, f64::min);
+        let y_max = candles
+            .iter()
+            .map(|c| c.high)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let x_min = first.open_time;
+        let x_max = last.open_time.max(x_min + 1);
+
+        let mut chart = ChartBuilder::on(&chart_area)
+            .margin(20)
+            .caption("", ("monospace", 20).into_font().color(&WHITE))
+            .x_label_area_size(30)
+            .right_y_label_area_size(40)
+            .build_cartesian_2d(x_min..x_max, y_min..y_max)?;
+
+        chart
+            .configure_mesh()
+            .x_labels(10)
+            .x_label_formatter(&|ts| {
+                let Some(dt) = Utc.timestamp_millis_opt(*ts as i64).single() else {
+                    return String::new();
+                };
+                match interval {
+                    "1d" | "3d" | "1w" => dt.format("%m-%d").to_string(),
+                    _ => dt.format("%H:%M").to_string(),
+                }
+            })
+            .y_label_formatter(&|v| format!("${:.2}", v))
+            .axis_style(&WHITE.mix(0.8))
+            .x_label_style(("monospace", 12).into_font().color(&WHITE))
+            .y_label_style(("monospace", 12).into_font().color(&WHITE))
+            .set_all_tick_mark_size(3)
+            .draw()?;
+
+        chart.draw_series(candles.iter().map(|candle| {
+            CandleStick::new(
+                candle.open_time,
+                candle.open,
+                candle.high,
+                candle.low,
+                candle.close,
+                RGBColor(0, 174, 0).filled(),
+                RGBColor(249, 70, 57).filled(),
+                10,
+            )
+        }))?;
+
+        root.present()?;
+    }
+
+    let image = RgbImage::from_raw(WIDTH, HEIGHT, buffer).context("Invalid chart buffer")?;
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
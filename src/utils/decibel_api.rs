@@ -0,0 +1,165 @@
+use bigdecimal::{BigDecimal, RoundingMode, Zero};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountOverview {
+    #[serde(default)]
+    pub perp_equity_balance: BigDecimal,
+    #[serde(default)]
+    pub unrealized_pnl: BigDecimal,
+    #[serde(default)]
+    pub total_margin: BigDecimal,
+    #[serde(default)]
+    pub usdc_cross_withdrawable_balance: Option<BigDecimal>,
+}
+
+impl AccountOverview {
+    /// Collateral that can leave the subaccount without touching open-position margin, rounded
+    /// down to the `decimals` of the collateral token
+    pub fn withdrawable(&self, decimals: i32) -> BigDecimal {
+        let withdrawable = self
+            .usdc_cross_withdrawable_balance
+            .clone()
+            .unwrap_or_else(|| &self.perp_equity_balance - &self.total_margin);
+        withdrawable
+            .max(BigDecimal::zero())
+            .with_scale_round(decimals.into(), RoundingMode::Down)
+    }
+}
+
+pub async fn fetch_account_overview(
+    client: &Client,
+    decibel_url: &str,
+    subaccount: &str,
+) -> anyhow::Result<AccountOverview> {
+    let url = format!(
+        "{}/api/v1/account_overviews?user={}",
+        decibel_url, subaccount
+    );
+    let overview = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<AccountOverview>()
+        .await?;
+    Ok(overview)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Position {
+    pub market: String,
+    /// Negative for shorts
+    #[serde(default)]
+    pub size: f64,
+    #[serde(default)]
+    pub entry_price: f64,
+    #[serde(default)]
+    pub user_leverage: Option<f64>,
+    #[serde(default)]
+    pub estimated_liquidation_price: Option<f64>,
+    #[serde(default)]
+    pub unrealized_funding: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenOrder {
+    pub market: String,
+    pub order_id: String,
+    #[serde(default)]
+    pub price: f64,
+    #[serde(default)]
+    pub orig_size: f64,
+    #[serde(default)]
+    pub remaining_size: f64,
+    #[serde(default)]
+    pub is_buy: bool,
+    #[serde(default)]
+    pub is_reduce_only: bool,
+}
+
+pub async fn fetch_positions(
+    client: &Client,
+    decibel_url: &str,
+    subaccount: &str,
+) -> anyhow::Result<Vec<Position>> {
+    let url = format!(
+        "{}/api/v1/account_positions?user={}",
+        decibel_url, subaccount
+    );
+    let positions = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<Position>>()
+        .await?;
+    Ok(positions)
+}
+
+pub async fn fetch_open_orders(
+    client: &Client,
+    decibel_url: &str,
+    subaccount: &str,
+) -> anyhow::Result<Vec<OpenOrder>> {
+    let url = format!("{}/api/v1/open_orders?user={}", decibel_url, subaccount);
+    let orders = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<OpenOrder>>()
+        .await?;
+    Ok(orders)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Candlestick {
+    #[serde(rename = "t")]
+    pub open_time: u64,
+    #[serde(rename = "T")]
+    pub close_time: u64,
+    #[serde(rename = "o")]
+    pub open: f64,
+    #[serde(rename = "h")]
+    pub high: f64,
+    #[serde(rename = "l")]
+    pub low: f64,
+    #[serde(rename = "c")]
+    pub close: f64,
+    #[serde(rename = "v")]
+    pub volume: f64,
+    #[serde(rename = "i")]
+    pub interval: String,
+}
+
+/// Candles of `market` between `start` and `end`, in unix millis
+pub async fn fetch_candlesticks(
+    client: &Client,
+    decibel_url: &str,
+    market: &str,
+    interval: &str,
+    start: i64,
+    end: i64,
+) -> anyhow::Result<Vec<Candlestick>> {
+    let url = format!(
+        "{}/api/v1/candlesticks?market={}&interval={}&startTime={}&endTime={}",
+        decibel_url, market, interval, start, end
+    );
+    let candles = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Vec<Candlestick>>()
+        .await?;
+    Ok(candles)
+}
+
+/// Fails unless the API answers with a success status
+pub async fn ping(client: &Client, decibel_url: &str) -> anyhow::Result<()> {
+    let url = format!("{}/api/v1/markets", decibel_url);
+    client.get(url).send().await?.error_for_status()?;
+    Ok(())
+}
@@ -61,6 +61,25 @@ pub fn delegate_trading_to(
     Ok(payload)
 }
 
+pub fn revoke_delegation(
+    contract_address: &str,
+    wallet_address: &str,
+) -> anyhow::Result<TransactionPayload> {
+    let module = ModuleId::new(
+        AccountAddress::from_hex_literal(contract_address)?,
+        Identifier::new("dex_accounts")?,
+    );
+    let delegate = AccountAddress::from_hex_literal(wallet_address)?;
+
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        module,
+        Identifier::new("revoke_delegation")?,
+        vec![],
+        vec![bcs::to_bytes(&delegate)?],
+    ));
+    Ok(payload)
+}
+
 pub fn mint(
     contract_address: &str,
     wallet_address: &str,
@@ -128,6 +147,29 @@ pub fn place_order_to_subaccount(
     Ok(payload)
 }
 
+pub fn cancel_order_to_subaccount(
+    contract_addr: &str,
+    subaccount: &str,
+    order_id: u128,
+    market: &str,
+) -> anyhow::Result<TransactionPayload> {
+    let module = ModuleId::new(
+        AccountAddress::from_str(contract_addr)?,
+        Identifier::new("dex_accounts")?,
+    );
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        module,
+        Identifier::new("cancel_order_to_subaccount")?,
+        vec![],
+        vec![
+            bcs::to_bytes(&AccountAddress::from_str(subaccount)?)?,
+            bcs::to_bytes(&order_id)?,
+            bcs::to_bytes(&AccountAddress::from_str(market)?)?,
+        ],
+    ));
+    Ok(payload)
+}
+
 pub fn deposit_to_subaccount_at(
     contract_addr: &str,
     subaccount: &str,
@@ -150,3 +192,40 @@ pub fn deposit_to_subaccount_at(
     ));
     Ok(payload)
 }
+
+pub fn create_new_subaccount(contract_addr: &str) -> anyhow::Result<TransactionPayload> {
+    let module = ModuleId::new(
+        AccountAddress::from_str(contract_addr)?,
+        Identifier::new("dex_accounts")?,
+    );
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        module,
+        Identifier::new("create_new_subaccount")?,
+        vec![],
+        vec![],
+    ));
+    Ok(payload)
+}
+
+pub fn withdraw_from_subaccount(
+    contract_addr: &str,
+    subaccount: &str,
+    fa_addr: &str,
+    amount: u64,
+) -> anyhow::Result<TransactionPayload> {
+    let module = ModuleId::new(
+        AccountAddress::from_str(contract_addr)?,
+        Identifier::new("dex_accounts")?,
+    );
+    let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+        module,
+        Identifier::new("withdraw_from_subaccount")?,
+        vec![],
+        vec![
+            bcs::to_bytes(&AccountAddress::from_str(subaccount)?)?,
+            bcs::to_bytes(&AccountAddress::from_str(fa_addr)?)?,
+            bcs::to_bytes(&amount)?,
+        ],
+    ));
+    Ok(payload)
+}
@@ -0,0 +1,117 @@
+use std::{fmt, str::FromStr};
+
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, KeyInit},
+};
+use aptos_crypto::{HashValue, x25519};
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier, password_hash::SaltString};
+
+/// Failed PIN attempts and exports counted per user over the last hour
+pub const MAX_ATTEMPTS_PER_HOUR: i64 = 5;
+/// Successful exports per user over the last day
+pub const MAX_EXPORTS_PER_DAY: i64 = 3;
+/// How long a one-time export link stays valid
+pub const LINK_TTL_SECS: i64 = 600;
+
+/// How an exported key reaches the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyDelivery {
+    /// Chat message deleted after 30 seconds
+    Chat,
+    /// Ciphertext only the holder of a user supplied X25519 key can open
+    Encrypted,
+    /// One-time link served by the HTTP server
+    Link,
+}
+
+impl fmt::Display for KeyDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            KeyDelivery::Chat => "chat",
+            KeyDelivery::Encrypted => "encrypted",
+            KeyDelivery::Link => "link",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl FromStr for KeyDelivery {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chat" => Ok(KeyDelivery::Chat),
+            "encrypted" => Ok(KeyDelivery::Encrypted),
+            "link" => Ok(KeyDelivery::Link),
+            _ => Err(()),
+        }
+    }
+}
+
+/// 4 to 8 digits
+pub fn is_valid_pin(pin: &str) -> bool {
+    (4..=8).contains(&pin.len()) && pin.chars().all(|c| c.is_ascii_digit())
+}
+
+pub fn hash_pin(pin: &str) -> anyhow::Result<String> {
+    let salt: [u8; 16] = rand::random();
+    let salt = SaltString::encode_b64(&salt)
+        .map_err(|err| anyhow::anyhow!("Failed to encode PIN salt: {}", err))?;
+    let hash = Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map_err(|err| anyhow::anyhow!("Failed to hash PIN: {}", err))?;
+    Ok(hash.to_string())
+}
+
+pub fn verify_pin(pin: &str, pin_hash: &str) -> anyhow::Result<bool> {
+    let pin_hash = PasswordHash::new(pin_hash)
+        .map_err(|err| anyhow::anyhow!("Failed to parse PIN hash: {}", err))?;
+    Ok(Argon2::default()
+        .verify_password(pin.as_bytes(), &pin_hash)
+        .is_ok())
+}
+
+pub fn parse_recipient_key(public_key: &str) -> anyhow::Result<x25519::PublicKey> {
+    let bytes = hex::decode(public_key.trim().trim_start_matches("0x"))
+        .map_err(|_| anyhow::anyhow!("❌ The public key must be hex encoded"))?;
+    x25519::PublicKey::try_from(bytes.as_slice())
+        .map_err(|_| anyhow::anyhow!("❌ The public key must be a 32 byte X25519 key"))
+}
+
+/// Encrypts `plaintext` to `recipient` with an ephemeral X25519 key exchange and AES-256-GCM.
+/// The AES key is SHA3-256(shared secret | ephemeral public key | recipient public key), the
+/// result is hex of ephemeral public key | nonce | ciphertext.
+pub fn encrypt_to(recipient: &x25519::PublicKey, plaintext: &[u8]) -> anyhow::Result<String> {
+    let seed: [u8; 32] = rand::random();
+    let ephemeral = x25519::PrivateKey::try_from(seed.as_slice())?;
+    let ephemeral_public_key = ephemeral.public_key();
+    let shared_secret = ephemeral.diffie_hellman(recipient);
+
+    let key = HashValue::sha3_256_of(
+        &[
+            shared_secret.as_slice(),
+            ephemeral_public_key.as_slice(),
+            recipient.as_slice(),
+        ]
+        .concat(),
+    );
+    let nonce: [u8; 12] = rand::random();
+    let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key.to_vec()))
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|_| anyhow::anyhow!("Failed to encrypt private key"))?;
+    Ok(hex::encode(
+        [ephemeral_public_key.as_slice(), &nonce, &ciphertext].concat(),
+    ))
+}
+
+/// Random link token and the hash stored in place of it
+pub fn new_link_token() -> (String, String) {
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    let token_hash = hash_link_token(&token);
+    (token, token_hash)
+}
+
+pub fn hash_link_token(token: &str) -> String {
+    HashValue::sha3_256_of(token.as_bytes()).to_hex()
+}
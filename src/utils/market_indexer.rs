@@ -4,20 +4,33 @@ use tokio::time::sleep;
 
 use crate::{
     cache::{AssetContext, ICache, Market},
-    utils::shutdown_utils,
+    utils::{
+        push::{MarkPriceTick, PushEvent, PushHub},
+        shutdown_utils,
+    },
 };
 
+/// Mark prices are refreshed this often
+const ASSET_CONTEXTS_INTERVAL: Duration = Duration::from_secs(5);
+/// Markets are refreshed once every this many asset context refreshes
+const MARKETS_EVERY: u64 = 60;
+
 pub struct MarketIndexer<TCache: ICache> {
     decibel_url: String,
     cache: Arc<TCache>,
+    push_hub: Arc<PushHub>,
 }
 
 impl<TCache: ICache> MarketIndexer<TCache>
 where
     TCache: ICache + Send + Sync + 'static,
 {
-    pub fn new(decibel_url: String, cache: Arc<TCache>) -> Self {
-        Self { decibel_url, cache }
+    pub fn new(decibel_url: String, cache: Arc<TCache>, push_hub: Arc<PushHub>) -> Self {
+        Self {
+            decibel_url,
+            cache,
+            push_hub,
+        }
     }
 
     pub async fn start(&self) -> anyhow::Result<()> {
@@ -27,15 +40,22 @@ where
 
         tokio::select! {
             _ = async {
+                let mut refreshes: u64 = 0;
                 loop {
                     if cancel_token.is_cancelled() {
                         break;
                     }
 
-                    if let Err(e) = self.fetch_and_store_markets(&client).await {
+                    if refreshes.is_multiple_of(MARKETS_EVERY)
+                        && let Err(e) = self.fetch_and_store_markets(&client).await
+                    {
                         tracing::error!("Failed to fetch and store markets: {e:#}");
                     }
-                    sleep(Duration::from_secs(5 * 60)).await;
+                    if let Err(e) = self.fetch_and_store_asset_contexts(&client).await {
+                        tracing::error!("Failed to fetch and store asset contexts: {e:#}");
+                    }
+                    refreshes += 1;
+                    sleep(ASSET_CONTEXTS_INTERVAL).await;
                 }
             } => {},
             _ = cancel_token.cancelled() => {
@@ -62,7 +82,22 @@ where
             .json::<Vec<AssetContext>>()
             .await?;
 
+        // only moved prices are pushed to clients
+        let mut ticks = vec![];
+        for asset_context in &asset_contexts {
+            let previous = self.cache.get_asset_context(&asset_context.market).await;
+            if previous.is_none_or(|previous| previous.mark_price != asset_context.mark_price) {
+                ticks.push(MarkPriceTick {
+                    market: asset_context.market.clone(),
+                    mark_price: asset_context.mark_price.to_string(),
+                    oracle_price: asset_context.oracle_price.to_string(),
+                });
+            }
+        }
         self.cache.set_asset_contexts(asset_contexts).await;
+        for tick in ticks {
+            self.push_hub.publish(PushEvent::MarkPrice(tick));
+        }
         Ok(())
     }
 }
@@ -0,0 +1,188 @@
+use std::{
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use prometheus::{
+    Encoder, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder,
+    register_histogram_vec, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+};
+
+use crate::{
+    config::Config,
+    utils::{
+        chain_client::ChainClient, database_utils::ArcDbPool,
+        starting_version::get_latest_version_from_db,
+    },
+};
+
+pub const OUTCOME_OK: &str = "ok";
+pub const OUTCOME_ERROR: &str = "error";
+
+/// Buckets for calls to remote services, from 10ms to 30s
+const LATENCY_BUCKETS: &[f64] = &[0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+pub static BOT_COMMANDS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "bot_commands_total",
+        "Bot commands handled, by command and outcome",
+        &["command", "outcome"]
+    )
+    .expect("Failed to register bot_commands_total")
+});
+
+pub static BOT_CALLBACKS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "bot_callbacks_total",
+        "Bot callback queries handled, by action and outcome",
+        &["action", "outcome"]
+    )
+    .expect("Failed to register bot_callbacks_total")
+});
+
+pub static TRANSACTION_SUBMIT_SECONDS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "transaction_submit_seconds",
+        "Latency of submitting outbox txns to the node, by kind and outcome",
+        &["kind", "outcome"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .expect("Failed to register transaction_submit_seconds")
+});
+
+pub static TRANSACTION_FAILURES: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "transaction_failures_total",
+        "Outbox txns that ended up failed, by kind",
+        &["kind"]
+    )
+    .expect("Failed to register transaction_failures_total")
+});
+
+pub static TURNKEY_CALL_SECONDS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "turnkey_call_seconds",
+        "Latency of Turnkey API calls, by operation and outcome",
+        &["operation", "outcome"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .expect("Failed to register turnkey_call_seconds")
+});
+
+pub static DB_POOL_CONNECTIONS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "db_pool_connections",
+        "Connections held by the DB pool, by state",
+        &["state"]
+    )
+    .expect("Failed to register db_pool_connections")
+});
+
+pub static CACHE_AGE_SECONDS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "cache_age_seconds",
+        "Seconds since a cached dataset was last refreshed",
+        &["dataset"]
+    )
+    .expect("Failed to register cache_age_seconds")
+});
+
+pub static INDEXER_LAG_VERSIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "indexer_lag_versions",
+        "Chain head version minus the last version the indexer processed"
+    )
+    .expect("Failed to register indexer_lag_versions")
+});
+
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "http_requests_total",
+        "HTTP requests served, by method, route and status",
+        &["method", "route", "status"]
+    )
+    .expect("Failed to register http_requests_total")
+});
+
+pub static HTTP_REQUEST_SECONDS: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "http_request_seconds",
+        "Latency of HTTP requests, by method and route",
+        &["method", "route"],
+        LATENCY_BUCKETS.to_vec()
+    )
+    .expect("Failed to register http_request_seconds")
+});
+
+pub fn outcome<T, E>(result: &Result<T, E>) -> &'static str {
+    match result {
+        Ok(_) => OUTCOME_OK,
+        Err(_) => OUTCOME_ERROR,
+    }
+}
+
+/// Awaits a Turnkey API call, observing its latency
+pub async fn time_turnkey<T, E>(
+    operation: &str,
+    call: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let started = Instant::now();
+    let result = call.await;
+    TURNKEY_CALL_SECONDS
+        .with_label_values(&[operation, outcome(&result)])
+        .observe(started.elapsed().as_secs_f64());
+    result
+}
+
+/// Chain head version minus the last version the indexer processed, `None` before the indexer
+/// stored any progress
+pub async fn indexer_lag(
+    config: &Config,
+    pool: ArcDbPool,
+    aptos_client: &dyn ChainClient,
+) -> anyhow::Result<Option<i64>> {
+    let Some(last_success_version) =
+        get_latest_version_from_db(&config.stream_config, pool).await?
+    else {
+        return Ok(None);
+    };
+    let ledger_info = aptos_client.ledger_info().await?;
+    Ok(Some(
+        (ledger_info.ledger_version as i64 - last_success_version).max(0),
+    ))
+}
+
+/// Updates the gauges sampled at scrape time
+pub async fn refresh_gauges(
+    config: &Config,
+    pool: ArcDbPool,
+    aptos_client: &dyn ChainClient,
+    cache_ages: Vec<(&'static str, Option<Duration>)>,
+) {
+    let state = pool.state();
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["idle"])
+        .set(state.idle_connections as i64);
+    DB_POOL_CONNECTIONS
+        .with_label_values(&["in_use"])
+        .set(state.connections.saturating_sub(state.idle_connections) as i64);
+
+    for (dataset, age) in cache_ages {
+        // -1 until the dataset is loaded for the first time
+        let age = age.map_or(-1, |age| age.as_secs() as i64);
+        CACHE_AGE_SECONDS.with_label_values(&[dataset]).set(age);
+    }
+
+    match indexer_lag(config, pool, aptos_client).await {
+        Ok(Some(lag)) => INDEXER_LAG_VERSIONS.set(lag),
+        Ok(None) => {}
+        Err(err) => tracing::warn!("Failed to get indexer lag: {err:#}"),
+    }
+}
+
+/// All registered metrics in the Prometheus text format
+pub fn render() -> anyhow::Result<String> {
+    let mut buffer = vec![];
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
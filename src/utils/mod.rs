@@ -1,10 +1,26 @@
+pub mod account_link;
+pub mod api_keys;
 pub mod aptos_client;
+pub mod chain_client;
+#[cfg(feature = "test-utils")]
+pub mod chain_simulator;
+pub mod chart;
 pub mod database_connection;
 pub mod database_utils;
 pub mod db_execution;
+pub mod decibel_api;
 pub mod decibel_transaction;
+pub mod key_export;
 pub mod market_indexer;
+pub mod metrics;
+pub mod orders;
 pub mod perps_math;
+pub mod push;
+pub mod sequence_numbers;
 pub mod shutdown_utils;
+pub mod signing_policy;
+pub mod sponsors;
 pub mod starting_version;
+pub mod trading;
 pub mod view_requests;
+pub mod vm_status;
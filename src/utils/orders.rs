@@ -0,0 +1,132 @@
+use std::str::FromStr;
+
+use aptos_sdk::types::transaction::TransactionPayload;
+use bigdecimal::{BigDecimal, Zero};
+
+use crate::{
+    cache::{AssetContext, Market},
+    utils::{
+        decibel_transaction::place_order_to_subaccount,
+        perps_math::{notional_price, position_size, position_value},
+    },
+};
+
+/// Market orders are limit orders priced this far through the mark price
+const MARKET_ORDER_SLIPPAGE: &str = "0.2";
+const MARKET_ORDER_TIME_IN_FORCE: u8 = 0;
+const LIMIT_ORDER_TIME_IN_FORCE: u8 = 2;
+/// Prices are sent with 8 decimals
+const PRICE_SCALE: &str = "100000000";
+/// Sizes are sent with 5 decimals
+const SIZE_SCALE: &str = "100000";
+
+/// Order as the user enters it, margin and leverage rather than size
+#[derive(Debug, Clone)]
+pub struct OrderParams {
+    pub is_long: bool,
+    pub leverage: u8,
+    /// Margin in collateral units
+    pub amount: BigDecimal,
+    /// `None` for market orders
+    pub limit_price: Option<BigDecimal>,
+}
+
+impl OrderParams {
+    pub fn validate(&self, market: &Market) -> anyhow::Result<()> {
+        if self.amount <= BigDecimal::zero() {
+            anyhow::bail!("❌ Amount must be greater than 0");
+        }
+        if self.leverage == 0 || self.leverage > market.max_leverage {
+            anyhow::bail!(
+                "❌ Leverage on {} must be between 1x and {}x",
+                market.market_name,
+                market.max_leverage
+            );
+        }
+        if self
+            .limit_price
+            .as_ref()
+            .is_some_and(|price| *price <= BigDecimal::zero())
+        {
+            anyhow::bail!("❌ Price must be greater than 0");
+        }
+        Ok(())
+    }
+
+    /// Text shown once the order commits, `price` being the limit or the mark price
+    pub fn summary(&self, market_name: &str, price: &BigDecimal, symbol: &str) -> String {
+        let order_type = if self.is_long { "LONG" } else { "SHORT" };
+        format!(
+            "Trade opened! <b>{} {} {}x</b> for <b>{} {}</b> at <b>${}</b>",
+            market_name, order_type, self.leverage, self.amount, symbol, price
+        )
+    }
+
+    /// `place_order_to_subaccount` payload with price and size quantized for the exchange
+    pub fn payload(
+        &self,
+        contract_address: &str,
+        subaccount: &str,
+        market: &Market,
+        asset_context: &AssetContext,
+    ) -> anyhow::Result<TransactionPayload> {
+        self.validate(market)?;
+        let (price, time_in_force) = match &self.limit_price {
+            Some(limit_price) => (
+                to_units(limit_price, PRICE_SCALE)?,
+                LIMIT_ORDER_TIME_IN_FORCE,
+            ),
+            None => (
+                market_order_price(&asset_context.mark_price, self.is_long)?,
+                MARKET_ORDER_TIME_IN_FORCE,
+            ),
+        };
+        let size = order_size(&self.amount, self.leverage, &asset_context.mark_price)?;
+        if size == 0 {
+            anyhow::bail!("❌ Order size is too small");
+        }
+
+        place_order_to_subaccount(
+            contract_address,
+            subaccount,
+            &market.market_addr,
+            price,
+            size,
+            self.is_long,
+            time_in_force,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+}
+
+/// Mark price moved by the slippage against the order, so it fills right away
+fn market_order_price(mark_price: &BigDecimal, is_long: bool) -> anyhow::Result<u64> {
+    let one = BigDecimal::from(1);
+    let slippage = BigDecimal::from_str(MARKET_ORDER_SLIPPAGE)?;
+    let adjusted_price = if is_long {
+        mark_price * (one + &slippage)
+    } else {
+        mark_price * (one - &slippage)
+    };
+    to_units(&adjusted_price.with_scale(2), PRICE_SCALE)
+}
+
+fn order_size(amount: &BigDecimal, leverage: u8, mark_price: &BigDecimal) -> anyhow::Result<u64> {
+    let notional_price = notional_price(amount, leverage);
+    let position_size = position_size(&notional_price, mark_price);
+    let order_size = position_value(&position_size, mark_price);
+    to_units(&order_size.with_scale(2), SIZE_SCALE)
+}
+
+fn to_units(value: &BigDecimal, scale: &str) -> anyhow::Result<u64> {
+    let scaled = value * BigDecimal::from_str(scale)?;
+    Ok(scaled.with_scale(0).to_string().parse::<u64>()?)
+}
@@ -0,0 +1,146 @@
+use std::str::FromStr;
+
+use aptos_sdk::types::account_address::AccountAddress;
+use serde::Serialize;
+use serde_json::json;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per subscriber, a subscriber that falls further behind skips ahead
+const CHANNEL_CAPACITY: usize = 1024;
+
+pub const TOPIC_MARK_PRICE: &str = "mark_price";
+pub const TOPIC_ORDERS: &str = "orders";
+pub const TOPIC_FILLS: &str = "fills";
+pub const TOPIC_POSITIONS: &str = "positions";
+pub const TOPICS: [&str; 4] = [TOPIC_MARK_PRICE, TOPIC_ORDERS, TOPIC_FILLS, TOPIC_POSITIONS];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkPriceTick {
+    pub market: String,
+    pub mark_price: String,
+    pub oracle_price: String,
+}
+
+/// Status change of an order txn queued in the outbox
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderUpdate {
+    /// Id of the queued txn
+    pub id: Uuid,
+    pub kind: String,
+    pub status: String,
+    pub txn_hash: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum PushEvent {
+    MarkPrice(MarkPriceTick),
+    Order {
+        user_id: Uuid,
+        update: OrderUpdate,
+    },
+    /// Contract event touching one of the user's subaccounts, passed through as emitted
+    Fill {
+        user_id: Uuid,
+        event: serde_json::Value,
+    },
+    Position {
+        user_id: Uuid,
+        event: serde_json::Value,
+    },
+}
+
+impl PushEvent {
+    pub fn topic(&self) -> &'static str {
+        match self {
+            PushEvent::MarkPrice(_) => TOPIC_MARK_PRICE,
+            PushEvent::Order { .. } => TOPIC_ORDERS,
+            PushEvent::Fill { .. } => TOPIC_FILLS,
+            PushEvent::Position { .. } => TOPIC_POSITIONS,
+        }
+    }
+
+    /// User the event belongs to, `None` for market data everyone may receive
+    pub fn user_id(&self) -> Option<Uuid> {
+        match self {
+            PushEvent::MarkPrice(_) => None,
+            PushEvent::Order { user_id, .. }
+            | PushEvent::Fill { user_id, .. }
+            | PushEvent::Position { user_id, .. } => Some(*user_id),
+        }
+    }
+
+    pub fn market(&self) -> Option<&str> {
+        match self {
+            PushEvent::MarkPrice(tick) => Some(&tick.market),
+            _ => None,
+        }
+    }
+
+    /// `{"topic": ..., "data": ...}` as sent to clients
+    pub fn to_message(&self) -> serde_json::Value {
+        let data = match self {
+            PushEvent::MarkPrice(tick) => json!(tick),
+            PushEvent::Order { update, .. } => json!(update),
+            PushEvent::Fill { event, .. } | PushEvent::Position { event, .. } => event.clone(),
+        };
+        json!({ "topic": self.topic(), "data": data })
+    }
+
+    /// Fill or position update out of an event of type `typ` the contract emitted for
+    /// `user_id`. Events are told apart by their struct name, other events are skipped.
+    pub fn from_contract_event(
+        user_id: Uuid,
+        contract_address: &str,
+        typ: &str,
+        data: serde_json::Value,
+    ) -> Option<Self> {
+        let mut parts = typ.split("::");
+        let (Some(address), Some(_module), Some(name)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return None;
+        };
+        if AccountAddress::from_str(address).ok()?
+            != AccountAddress::from_str(contract_address).ok()?
+        {
+            return None;
+        }
+
+        let event = json!({ "type": typ, "data": data });
+        if name.ends_with("TradeEvent") || name.ends_with("FillEvent") {
+            Some(PushEvent::Fill { user_id, event })
+        } else if name.starts_with("Position") {
+            Some(PushEvent::Position { user_id, event })
+        } else {
+            None
+        }
+    }
+}
+
+/// Fans out market and account updates from the workers to the WebSocket clients
+pub struct PushHub {
+    sender: broadcast::Sender<PushEvent>,
+}
+
+impl PushHub {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: PushEvent) {
+        // fails only when no client is connected
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PushEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for PushHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
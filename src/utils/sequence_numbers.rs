@@ -0,0 +1,79 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use aptos_sdk::types::account_address::AccountAddress;
+use tokio::sync::OwnedMutexGuard;
+
+/// Hands out sequence numbers locally so several txns of one sender can be in flight at once.
+/// Numbers of txns that are never submitted are given back and handed out again first, so the
+/// txns signed after them are not stuck behind a gap.
+#[derive(Default)]
+pub struct SequenceNumbers {
+    accounts: Mutex<HashMap<AccountAddress, AccountSequence>>,
+    signing: Mutex<HashMap<AccountAddress, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+#[derive(Default)]
+struct AccountSequence {
+    /// One past the highest number handed out
+    next: u64,
+    /// Handed out, then given back before their txn was submitted
+    released: BTreeSet<u64>,
+}
+
+impl SequenceNumbers {
+    /// Held while a txn of `sender` is simulated, numbered and signed, so txns signed at the
+    /// same time read the on-chain number and take theirs one after the other
+    pub async fn lock_sender(&self, sender: AccountAddress) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut signing = self.signing.lock().unwrap_or_else(|err| err.into_inner());
+            Arc::clone(signing.entry(sender).or_default())
+        };
+        lock.lock_owned().await
+    }
+
+    /// Next sequence number of `sender`, `on_chain` being the one the node expects next. Numbers
+    /// below it are used up, whether by txns of this server or by ones sent from elsewhere.
+    pub fn allocate(&self, sender: AccountAddress, on_chain: u64) -> u64 {
+        let mut accounts = self.accounts();
+        let account = accounts.entry(sender).or_default();
+        account.released = account.released.split_off(&on_chain);
+        if let Some(sequence_number) = account.released.pop_first() {
+            return sequence_number;
+        }
+        let sequence_number = account.next.max(on_chain);
+        account.next = sequence_number + 1;
+        sequence_number
+    }
+
+    /// Gives back `sequence_number` when the txn using it was never submitted
+    pub fn release(&self, sender: AccountAddress, sequence_number: u64) {
+        let mut accounts = self.accounts();
+        let Some(account) = accounts.get_mut(&sender) else {
+            return;
+        };
+        if sequence_number >= account.next {
+            return;
+        }
+        account.released.insert(sequence_number);
+        while account.next > 0 && account.released.remove(&(account.next - 1)) {
+            account.next -= 1;
+        }
+    }
+
+    /// Forgets the numbers handed out, the next txn of `sender` starts from the node's number.
+    /// Only meant for submit errors, txns still in flight would otherwise get reused numbers.
+    pub fn resync(&self, sender: AccountAddress) {
+        self.accounts().remove(&sender);
+    }
+
+    fn accounts(&self) -> MutexGuard<'_, HashMap<AccountAddress, AccountSequence>> {
+        self.accounts.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+pub fn is_sequence_number_error(vm_status: &str) -> bool {
+    vm_status.contains("SEQUENCE_NUMBER_TOO_OLD") || vm_status.contains("SEQUENCE_NUMBER_TOO_NEW")
+}
@@ -0,0 +1,132 @@
+use std::str::FromStr;
+
+use anyhow::Context;
+use aptos_sdk::{
+    bcs,
+    types::{account_address::AccountAddress, transaction::TransactionPayload},
+};
+
+use crate::{
+    config::{ArgLimit, Config, SigningPolicyConfig},
+    utils::chain_client::TxnRejected,
+};
+
+/// Placeholder for the network's contract address in allowed function patterns
+const CONTRACT_PLACEHOLDER: &str = "contract";
+
+/// Checked before the server signs anything, so a buggy handler can only build txns the
+/// policy allows
+#[derive(Debug, Clone)]
+pub struct SigningPolicy {
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    address: AccountAddress,
+    module: String,
+    /// `None` allows every function of the module
+    function: Option<String>,
+    arg_limits: Vec<ArgLimit>,
+}
+
+impl SigningPolicy {
+    pub fn new(config: &Config) -> anyhow::Result<Self> {
+        Self::from_config(
+            &config.signing_policy,
+            &config.network().contract_address,
+            config.network().testnet,
+        )
+    }
+
+    pub fn from_config(
+        policy: &SigningPolicyConfig,
+        contract_address: &str,
+        testnet: bool,
+    ) -> anyhow::Result<Self> {
+        let rules = policy
+            .allowed_functions
+            .iter()
+            .filter(|allowed| testnet || !allowed.testnet_only)
+            .map(|allowed| {
+                let mut parts = allowed.function.split("::");
+                let (Some(address), Some(module), Some(function), None) =
+                    (parts.next(), parts.next(), parts.next(), parts.next())
+                else {
+                    anyhow::bail!(
+                        "Allowed function `{}` must be <address>::<module>::<function>",
+                        allowed.function
+                    );
+                };
+                let address = match address {
+                    CONTRACT_PLACEHOLDER => AccountAddress::from_str(contract_address)?,
+                    address => AccountAddress::from_str(address)
+                        .with_context(|| format!("Invalid address in `{}`", allowed.function))?,
+                };
+                Ok(Rule {
+                    address,
+                    module: module.to_string(),
+                    function: (function != "*").then(|| function.to_string()),
+                    arg_limits: allowed.arg_limits.clone(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Fails unless `payload` calls an allowed entry function within its argument limits
+    pub fn check(
+        &self,
+        sender: AccountAddress,
+        payload: &TransactionPayload,
+    ) -> anyhow::Result<()> {
+        if let Err(reason) = self.evaluate(payload) {
+            tracing::warn!("Signing policy rejected txn of {}: {}", sender, reason);
+            return Err(TxnRejected("❌ This transaction is not allowed".to_string()).into());
+        }
+        Ok(())
+    }
+
+    fn evaluate(&self, payload: &TransactionPayload) -> Result<(), String> {
+        let TransactionPayload::EntryFunction(entry_function) = payload else {
+            return Err("only entry function payloads are signed".to_string());
+        };
+        let module = entry_function.module();
+        let function = entry_function.function().as_str();
+        let name = format!(
+            "{}::{}::{}",
+            module.address().to_hex_literal(),
+            module.name(),
+            function
+        );
+
+        let rule = self
+            .rules
+            .iter()
+            .find(|rule| {
+                rule.address == *module.address()
+                    && rule.module == module.name().as_str()
+                    && rule
+                        .function
+                        .as_deref()
+                        .is_none_or(|allowed| allowed == function)
+            })
+            .ok_or_else(|| format!("{} is not allowed", name))?;
+
+        for limit in &rule.arg_limits {
+            let arg = entry_function
+                .args()
+                .get(limit.index)
+                .ok_or_else(|| format!("{} is missing argument {}", name, limit.index))?;
+            let value = bcs::from_bytes::<u64>(arg)
+                .map_err(|_| format!("argument {} of {} is not a u64", limit.index, name))?;
+            if value > limit.max {
+                return Err(format!(
+                    "argument {} of {} is {}, above the limit of {}",
+                    limit.index, name, value, limit.max
+                ));
+            }
+        }
+        Ok(())
+    }
+}
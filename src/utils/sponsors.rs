@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use aptos_crypto::{
+    SigningKey,
+    ed25519::{Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature},
+};
+use aptos_sdk::types::{account_address::AccountAddress, transaction::RawTransactionWithData};
+
+use crate::signer::{account_address, parse_private_key};
+
+/// Fee payer account paying gas for sponsored txns
+pub struct Sponsor {
+    pub address: AccountAddress,
+    pub public_key: Ed25519PublicKey,
+    private_key: Ed25519PrivateKey,
+}
+
+impl Sponsor {
+    pub fn sign(&self, message: &RawTransactionWithData) -> anyhow::Result<Ed25519Signature> {
+        Ok(self.private_key.sign(message)?)
+    }
+}
+
+/// Hands out sponsors round-robin so sponsored txns are spread over several fee payers
+pub struct SponsorPool {
+    sponsors: Vec<Sponsor>,
+    next: AtomicUsize,
+}
+
+impl SponsorPool {
+    pub fn new(private_keys: &[String]) -> anyhow::Result<Self> {
+        if private_keys.is_empty() {
+            anyhow::bail!("At least one sponsor private key is required");
+        }
+        let sponsors = private_keys
+            .iter()
+            .map(|private_key| {
+                let private_key = parse_private_key(private_key)?;
+                let public_key = Ed25519PublicKey::from(&private_key);
+                Ok(Sponsor {
+                    address: account_address(&public_key),
+                    public_key,
+                    private_key,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self {
+            sponsors,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn next(&self) -> &Sponsor {
+        let idx = self.next.fetch_add(1, Ordering::Relaxed) % self.sponsors.len();
+        &self.sponsors[idx]
+    }
+
+    pub fn addresses(&self) -> Vec<AccountAddress> {
+        self.sponsors
+            .iter()
+            .map(|sponsor| sponsor.address)
+            .collect()
+    }
+}
@@ -0,0 +1,428 @@
+use std::{str::FromStr, sync::Arc};
+
+use aptos_sdk::types::transaction::TransactionPayload;
+use bigdecimal::{BigDecimal, Zero};
+use reqwest::Client;
+use uuid::Uuid;
+
+use crate::{
+    cache::{AssetContext, Market},
+    config::Config,
+    models::db::{
+        gas_usage::GasUsage,
+        subaccounts::{NewSubAccount, SubAccount},
+        tokens::Token,
+        trading_delegations::TradingDelegation,
+        transaction_outbox::{NewOutboxTransaction, OutboxTransaction},
+        users::User,
+    },
+    schema::{subaccounts, transaction_outbox},
+    utils::{
+        chain_client::{ChainClient, GasPayer, SignedTxn, TxnRejected},
+        database_utils::DbPoolConnection,
+        db_execution::execute_with_better_error,
+        decibel_api::fetch_account_overview,
+        decibel_transaction::deposit_to_subaccount_at,
+        orders::OrderParams,
+        view_requests::{view_fa_balance_request, view_primary_subaccount},
+    },
+};
+
+/// Entry functions a delegated bot wallet may call on the user's subaccounts
+const DELEGATED_FUNCTIONS: [&str; 2] = ["place_order_to_subaccount", "cancel_order_to_subaccount"];
+
+/// Txns placing an order, returned by `Trading::place_order`
+pub enum OrderTxns {
+    Order(SignedTxn),
+    /// Margin deposit of a limit order, then the order payload to sign once it committed
+    DepositThenOrder(SignedTxn, TransactionPayload),
+}
+
+impl OrderTxns {
+    /// Hash of the order txn, unknown until it is signed
+    pub fn order_txn_hash(&self) -> Option<String> {
+        match self {
+            Self::Order(txn) => Some(format!("0x{}", txn.committed_hash().to_hex())),
+            Self::DepositThenOrder(..) => None,
+        }
+    }
+}
+
+/// Subaccounts, signing and order placement shared by the bot and the HTTP API
+pub struct Trading {
+    config: Arc<Config>,
+    aptos_client: Arc<dyn ChainClient>,
+}
+
+impl Trading {
+    pub fn new(config: Arc<Config>, aptos_client: Arc<dyn ChainClient>) -> Self {
+        Self {
+            config,
+            aptos_client,
+        }
+    }
+
+    /// Subaccounts of the user ordered by creation, registering the on-chain primary
+    /// subaccount on first use
+    pub async fn subaccounts(
+        &self,
+        db_user: &User,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<Vec<SubAccount>> {
+        let subaccounts = SubAccount::get_subaccounts_by_user_id(db_user.id, conn).await?;
+        if !subaccounts.is_empty() {
+            return Ok(subaccounts);
+        }
+
+        let request = view_primary_subaccount(
+            &self.config.network().contract_address,
+            db_user.trading_account(),
+        )?;
+        let response = self.aptos_client.view(&request).await?;
+        let address = response
+            .first()
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| anyhow::anyhow!("Primary subaccount not found"))?;
+        let new_subaccount = NewSubAccount::to_db_subaccount(db_user.id, address.to_string(), true);
+        let query = diesel::insert_into(subaccounts::table)
+            .values(new_subaccount)
+            .on_conflict_do_nothing();
+        execute_with_better_error(conn, vec![query]).await?;
+
+        Ok(SubAccount::get_subaccounts_by_user_id(db_user.id, conn).await?)
+    }
+
+    /// Subaccount used for trades and deposits unless the user picks another one
+    pub async fn primary_subaccount(
+        &self,
+        db_user: &User,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SubAccount> {
+        let subaccounts = self.subaccounts(db_user, conn).await?;
+        subaccounts
+            .iter()
+            .find(|subaccount| subaccount.is_primary())
+            .or(subaccounts.first())
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Primary subaccount not found"))
+    }
+
+    /// Subaccount picked on an order button, `idx` being its position in `subaccounts`
+    pub async fn subaccount_at(
+        &self,
+        db_user: &User,
+        idx: u8,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SubAccount> {
+        self.subaccounts(db_user, conn)
+            .await?
+            .get(idx as usize)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Subaccount not found. Open /settings to manage them"))
+    }
+
+    /// Subaccount of the user with `address`, the primary one when `None`
+    pub async fn subaccount_by_address(
+        &self,
+        db_user: &User,
+        address: Option<&str>,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SubAccount> {
+        let Some(address) = address else {
+            return self.primary_subaccount(db_user, conn).await;
+        };
+        self.subaccounts(db_user, conn)
+            .await?
+            .into_iter()
+            .find(|subaccount| subaccount.address == address)
+            .ok_or_else(|| anyhow::anyhow!("Subaccount {} not found", address))
+    }
+
+    /// Validates and quantizes the order, then signs it. Limit orders of custodial users
+    /// first move their margin from the bot wallet to the subaccount, the order is signed by
+    /// the outbox worker once that deposit committed.
+    pub async fn place_order(
+        &self,
+        db_user: &User,
+        subaccount: &SubAccount,
+        market: &Market,
+        asset_context: &AssetContext,
+        order: &OrderParams,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<OrderTxns> {
+        let payload = order.payload(
+            &self.config.network().contract_address,
+            &subaccount.address,
+            market,
+            asset_context,
+        )?;
+
+        // delegated users fund the subaccount from their own wallet
+        if order.limit_price.is_some() && !db_user.is_delegated() {
+            let token = self.collateral_token(conn).await?;
+            let amount = token.to_base_units(&order.amount)?;
+            let deposit = deposit_to_subaccount_at(
+                &self.config.network().contract_address,
+                &subaccount.address,
+                &token.address,
+                amount,
+            )?;
+            let deposit = self.sign_txn(db_user, deposit, conn).await?;
+            return Ok(OrderTxns::DepositThenOrder(deposit, payload));
+        }
+
+        Ok(OrderTxns::Order(
+            self.sign_txn(db_user, payload, conn).await?,
+        ))
+    }
+
+    /// Queues the txns of `Trading::place_order`, returning the id of the order txn
+    pub async fn enqueue_order(
+        &self,
+        db_user: &User,
+        summary: String,
+        txns: OrderTxns,
+        chat_id: Option<i64>,
+        message_id: Option<i32>,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<Uuid> {
+        match txns {
+            OrderTxns::Order(txn) => {
+                self.enqueue_transaction(db_user, "order", summary, txn, chat_id, message_id, conn)
+                    .await
+            }
+            OrderTxns::DepositThenOrder(deposit, payload) => {
+                self.enqueue_transaction_then(
+                    db_user,
+                    (
+                        "deposit",
+                        "Moving the order margin to your subaccount".to_string(),
+                        deposit,
+                    ),
+                    ("order", summary, payload),
+                    chat_id,
+                    message_id,
+                    conn,
+                )
+                .await
+            }
+        }
+    }
+
+    /// Signs `payload` for the user, sponsoring gas until the user's daily quota is used up
+    pub async fn sign_txn(
+        &self,
+        db_user: &User,
+        payload: TransactionPayload,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SignedTxn> {
+        let quota = self.config.admin_config.gas_sponsorship.daily_quota;
+        if GasUsage::get_spent_today(db_user.id, conn).await? < i64::try_from(quota)? {
+            let (txn, gas_fee) = self
+                .sign_txn_paid_by(db_user, payload.clone(), GasPayer::Sponsor, conn)
+                .await?;
+            // checked again as it is counted, a concurrent txn may have used up the quota.
+            // Otherwise `txn` is dropped and its sequence number goes to the txn signed below.
+            if GasUsage::try_record(db_user.id, gas_fee, quota, conn).await? {
+                return Ok(txn.sponsored(gas_fee));
+            }
+        }
+
+        let (txn, _) = self
+            .sign_txn_paid_by(db_user, payload, GasPayer::Sender, conn)
+            .await?;
+        Ok(txn)
+    }
+
+    /// Signs `payload` again for a queued txn that expired, with the same gas payer. Its gas
+    /// was counted against the user's quota when it was first signed.
+    pub async fn resign_txn(
+        &self,
+        db_user: &User,
+        payload: TransactionPayload,
+        gas_payer: GasPayer,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<SignedTxn> {
+        let (txn, _) = self
+            .sign_txn_paid_by(db_user, payload, gas_payer, conn)
+            .await?;
+        Ok(txn)
+    }
+
+    async fn sign_txn_paid_by(
+        &self,
+        db_user: &User,
+        payload: TransactionPayload,
+        gas_payer: GasPayer,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<(SignedTxn, u64)> {
+        if db_user.is_delegated() {
+            Self::check_delegation(db_user, &payload, conn).await?;
+        }
+        let (txn, gas_fee) = self
+            .aptos_client
+            .sign_txn(&db_user.address, &db_user.public_key, payload, gas_payer)
+            .await
+            .map_err(|err| {
+                let rejection = err
+                    .downcast_ref::<TxnRejected>()
+                    .map(|TxnRejected(msg)| msg.clone());
+                match (gas_payer, rejection) {
+                    (GasPayer::Sender, Some(msg)) => TxnRejected(format!(
+                        "{}\n\nYour free gas for today is used up, add APT to your wallet to keep trading",
+                        msg
+                    ))
+                    .into(),
+                    _ => err,
+                }
+            })?;
+        Ok((SignedTxn::new(txn, Arc::clone(&self.aptos_client)), gas_fee))
+    }
+
+    /// A delegated bot wallet holds no funds of the user, it may only place and cancel orders
+    /// while the delegation is active
+    async fn check_delegation(
+        db_user: &User,
+        payload: &TransactionPayload,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        if TradingDelegation::get_active(db_user.id, conn)
+            .await?
+            .is_none()
+        {
+            return Err(TxnRejected(
+                "❌ Trading delegation was revoked. Delegate trading again from the terminal"
+                    .to_string(),
+            )
+            .into());
+        }
+        let is_order = matches!(
+            payload,
+            TransactionPayload::EntryFunction(entry_function)
+                if DELEGATED_FUNCTIONS.contains(&entry_function.function().as_str())
+        );
+        if !is_order {
+            return Err(TxnRejected(
+                "❌ Your funds stay in your connected wallet. Move them from the terminal"
+                    .to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Queues a signed txn for the outbox worker. When the txn came from the bot, the worker
+    /// edits the message `message_id` of `chat_id` with the outcome once it commits.
+    pub async fn enqueue_transaction(
+        &self,
+        db_user: &User,
+        kind: &str,
+        summary: String,
+        txn: SignedTxn,
+        chat_id: Option<i64>,
+        message_id: Option<i32>,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<Uuid> {
+        let new_txn = NewOutboxTransaction::from_signed_txn(
+            db_user.id, kind, summary, &txn, chat_id, message_id,
+        )?;
+        let id = new_txn.id;
+        let query = diesel::insert_into(transaction_outbox::table).values(new_txn);
+        // dropping `txn` on failure gives its sequence number back
+        execute_with_better_error(conn, vec![query]).await?;
+        // the outbox worker submits it from now on
+        txn.into_inner();
+        Ok(id)
+    }
+
+    /// Queues `txn`, then `next` which the outbox worker signs once `txn` committed. For a
+    /// payload spending what `txn` brings in, it would not pass simulation before that. Only
+    /// `next` edits the bot message, it fails along with `txn`. Returns the id of `next`.
+    pub async fn enqueue_transaction_then(
+        &self,
+        db_user: &User,
+        (kind, summary, txn): (&str, String, SignedTxn),
+        (next_kind, next_summary, next_payload): (&str, String, TransactionPayload),
+        chat_id: Option<i64>,
+        message_id: Option<i32>,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<Uuid> {
+        let first =
+            NewOutboxTransaction::from_signed_txn(db_user.id, kind, summary, &txn, None, None)?;
+        let next = NewOutboxTransaction::waiting_on(
+            first.id,
+            db_user.id,
+            next_kind,
+            next_summary,
+            &next_payload,
+            chat_id,
+            message_id,
+        )?;
+        let id = next.id;
+        let queries = [first, next]
+            .into_iter()
+            .map(|new_txn| diesel::insert_into(transaction_outbox::table).values(new_txn))
+            .collect();
+        // dropping `txn` on failure gives its sequence number back
+        execute_with_better_error(conn, queries).await?;
+        // the outbox worker submits it from now on
+        txn.into_inner();
+        Ok(id)
+    }
+
+    /// Token used for deposits, withdrawals and margin on the active network
+    pub async fn collateral_token(&self, conn: &mut DbPoolConnection<'_>) -> anyhow::Result<Token> {
+        let chain_id = self.config.network().chain_id;
+        Token::get_collateral_token(chain_id.into(), conn)
+            .await?
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No collateral token is configured for chain id {}",
+                    chain_id
+                )
+            })
+    }
+
+    /// Wallet balance of `token` in human readable units
+    pub async fn token_balance(&self, token: &Token, address: &str) -> anyhow::Result<BigDecimal> {
+        let request = view_fa_balance_request(&token.address, address)?;
+        let response = self.aptos_client.view(&request).await?;
+        let balance_json = response.first().cloned().unwrap_or(serde_json::json!("0"));
+        let balance_str = serde_json::from_value::<String>(balance_json)?;
+        let balance = BigDecimal::from_str(&balance_str)?;
+        Ok(token.from_base_units(&balance))
+    }
+
+    /// Whether the bot wallet of the user holds collateral, its subaccounts hold equity or it
+    /// has txns in flight, all of which would be stranded if the user was merged away
+    pub async fn has_custodial_state(
+        &self,
+        db_user: &User,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<bool> {
+        if OutboxTransaction::has_open(db_user.id, conn).await? {
+            return Ok(true);
+        }
+        let token = self.collateral_token(conn).await?;
+        if self.token_balance(&token, &db_user.address).await? > BigDecimal::zero() {
+            return Ok(true);
+        }
+        if db_user.is_delegated() {
+            // its subaccounts belong to the connected wallet, which stays with the user
+            return Ok(false);
+        }
+        let client = Client::new();
+        for subaccount in SubAccount::get_subaccounts_by_user_id(db_user.id, conn).await? {
+            let overview = fetch_account_overview(
+                &client,
+                &self.config.network().decibel_url,
+                &subaccount.address,
+            )
+            .await?;
+            if overview.perp_equity_balance > BigDecimal::zero() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
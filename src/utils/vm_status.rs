@@ -0,0 +1,145 @@
+/// Parsed form of a `Move abort in <module>: <REASON>(<code>): <description>` vm status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MoveAbort {
+    pub module: String,
+    pub reason: Option<String>,
+    pub code: Option<u64>,
+}
+
+impl MoveAbort {
+    pub fn parse(vm_status: &str) -> Option<Self> {
+        let rest = vm_status.strip_prefix("Move abort in ")?;
+        let (module, rest) = rest.split_once(": ")?;
+        let abort = rest.split(": ").next().unwrap_or(rest).trim();
+
+        let (reason, code_str) = match abort.split_once('(') {
+            Some((reason, code)) => (Some(reason.to_string()), code.trim_end_matches(')')),
+            None => (None, abort),
+        };
+        let code = u64::from_str_radix(code_str.trim_start_matches("0x"), 16).ok();
+
+        Some(Self {
+            module: module.to_string(),
+            reason,
+            code,
+        })
+    }
+
+    /// Module name without the account address, e.g. `dex_accounts`
+    pub fn module_name(&self) -> &str {
+        self.module
+            .rsplit("::")
+            .next()
+            .unwrap_or(self.module.as_str())
+    }
+
+    /// Abort code with the error category stripped, as declared in the module
+    pub fn reason_code(&self) -> Option<u64> {
+        self.code.map(|code| code & 0xffff)
+    }
+}
+
+/// Turns a failed simulation `vm_status` into a message that can be shown to the user
+pub fn explain_vm_status(vm_status: &str) -> String {
+    if let Some(abort) = MoveAbort::parse(vm_status) {
+        if let Some(text) = explain_move_abort(&abort) {
+            return text.to_string();
+        }
+        return format!(
+            "Transaction rejected by {} ({})",
+            abort.module_name(),
+            abort
+                .reason
+                .clone()
+                .unwrap_or_else(|| format!("code {}", abort.code.unwrap_or_default()))
+        );
+    }
+
+    match vm_status {
+        s if s.contains("INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE") => {
+            "Not enough APT to pay for gas"
+        }
+        s if s.contains("SEQUENCE_NUMBER_TOO_OLD") || s.contains("SEQUENCE_NUMBER_TOO_NEW") => {
+            "Another transaction from your wallet is in progress, please try again"
+        }
+        s if s.contains("OUT_OF_GAS") => "Transaction ran out of gas",
+        s if s.contains("TRANSACTION_EXPIRED") => "Transaction expired, please try again",
+        _ => return format!("Transaction would fail: {}", vm_status),
+    }
+    .to_string()
+}
+
+fn explain_move_abort(abort: &MoveAbort) -> Option<&'static str> {
+    match abort.module_name() {
+        "fungible_asset" | "primary_fungible_store" => {
+            explain_fungible_asset_abort(abort.reason.as_deref(), abort.reason_code())
+        }
+        "dex_accounts" => explain_dex_accounts_abort(abort),
+        _ => None,
+    }
+}
+
+/// Codes are the error constants of the framework's `fungible_asset` module
+fn explain_fungible_asset_abort(reason: Option<&str>, code: Option<u64>) -> Option<&'static str> {
+    match (reason, code) {
+        (Some("EINSUFFICIENT_BALANCE"), _) | (None, Some(4)) => {
+            Some("Insufficient balance in your wallet")
+        }
+        (Some("EAMOUNT_CANNOT_BE_ZERO"), _) | (None, Some(1)) => {
+            Some("Amount must be greater than zero")
+        }
+        (Some("ESTORE_IS_FROZEN"), _) | (None, Some(3)) => Some("Your wallet store is frozen"),
+        _ => None,
+    }
+}
+
+/// Reason names of the `dex_accounts` error constants. Only matched by name, the numeric
+/// codes are not part of the contract's published interface, so a status without a reason
+/// name gets the generic message.
+const DEX_ACCOUNTS_ABORTS: [(&str, &str); 13] = [
+    (
+        "ESUBACCOUNT_NOT_FOUND",
+        "Subaccount not found or not active",
+    ),
+    (
+        "EINSUFFICIENT_BALANCE",
+        "Insufficient balance in your subaccount",
+    ),
+    ("ENOT_AUTHORIZED", "Trading is not delegated to this wallet"),
+    (
+        "EORDER_SIZE_TOO_SMALL",
+        "Order size is below the market minimum size",
+    ),
+    ("EINVALID_PRICE", "Order price is invalid for this market"),
+    (
+        "EINSUFFICIENT_MARGIN",
+        "Insufficient margin in your subaccount for this order",
+    ),
+    (
+        "EORDER_NOT_FOUND",
+        "Order not found, it may already be filled or cancelled",
+    ),
+    (
+        "EPRICE_NOT_MULTIPLE_OF_TICK_SIZE",
+        "Order price must be a multiple of the market tick size",
+    ),
+    (
+        "ESIZE_NOT_MULTIPLE_OF_LOT_SIZE",
+        "Order size must be a multiple of the market lot size",
+    ),
+    ("ELEVERAGE_TOO_HIGH", "Leverage exceeds the market maximum"),
+    (
+        "EMAX_OPEN_INTEREST_EXCEEDED",
+        "Market open interest limit reached",
+    ),
+    ("EMARKET_NOT_FOUND", "Market is not available for trading"),
+    ("EMARKET_HALTED", "Market is halted, try again later"),
+];
+
+fn explain_dex_accounts_abort(abort: &MoveAbort) -> Option<&'static str> {
+    let name = abort.reason.as_deref()?;
+    DEX_ACCOUNTS_ABORTS
+        .iter()
+        .find(|(reason, _)| name == *reason)
+        .map(|(_, text)| *text)
+}
@@ -1,21 +1,43 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Context;
+use aptos_indexer_processor_sdk::{
+    aptos_indexer_transaction_stream::{TransactionStream, TransactionStreamConfig},
+    aptos_protos::{
+        transaction::v1::{Event, Transaction, transaction::TxnData},
+        util::timestamp::Timestamp,
+    },
+    utils::convert::standardize_address,
+};
+use serde_json::json;
 
 use crate::{
-    config::Config,
+    config::{Config, StreamConfig},
+    models::db::{processor_status::ProcessorStatus, subaccounts::SubAccount},
     utils::{
-        database_connection::get_db_connection, database_utils::ArcDbPool,
+        database_connection::get_db_connection,
+        database_utils::ArcDbPool,
+        push::{PushEvent, PushHub},
+        shutdown_utils,
         starting_version::get_starting_version,
     },
 };
 
+/// Streams txns from the indexer gRPC and pushes the fills and position updates the contract
+/// emitted for users' subaccounts to their WebSocket clients, whoever sent the txn
 pub struct IndexerProcessor {
     db_pool: ArcDbPool,
     config: Arc<Config>,
+    push_hub: Arc<PushHub>,
 }
 
 impl IndexerProcessor {
-    pub fn new(db_pool: ArcDbPool, config: Arc<Config>) -> Self {
-        Self { db_pool, config }
+    pub fn new(db_pool: ArcDbPool, config: Arc<Config>, push_hub: Arc<PushHub>) -> Self {
+        Self {
+            db_pool,
+            config,
+            push_hub,
+        }
     }
 
     pub async fn start(&self) -> anyhow::Result<()> {
@@ -26,6 +48,145 @@ impl IndexerProcessor {
             starting_version
         );
 
+        let cancel_token = shutdown_utils::get_shutdown_token();
+        tokio::select! {
+            _ = cancel_token.cancelled() => Ok(()),
+            result = self.run(starting_version) => result,
+        }
+    }
+
+    async fn run(&self, starting_version: i64) -> anyhow::Result<()> {
+        let stream_config = &self.config.stream_config;
+        let mut stream =
+            TransactionStream::new(transaction_stream_config(stream_config, starting_version)?)
+                .await?;
+        while !stream.is_end_of_stream() {
+            let response = match stream.get_next_transaction_batch().await {
+                Ok(response) => response,
+                Err(err) => {
+                    tracing::warn!("Failed to get txns from the indexer: {:?}", err);
+                    stream.reconnect_to_grpc_with_retries().await?;
+                    continue;
+                }
+            };
+            self.process_batch(&response.transactions).await?;
+
+            let mut conn = get_db_connection(&self.db_pool).await?;
+            ProcessorStatus {
+                processor: stream_config.request_name_header.clone(),
+                last_success_version: response.end_version as i64,
+                last_transaction_timestamp: response
+                    .end_txn_timestamp
+                    .as_ref()
+                    .and_then(naive_datetime),
+            }
+            .upsert(&mut conn)
+            .await?;
+        }
+        tracing::info!("Events processor reached the ending version");
+        Ok(())
+    }
+
+    async fn process_batch(&self, transactions: &[Transaction]) -> anyhow::Result<()> {
+        let contract_address = &self.config.network().contract_address;
+        let events = transactions
+            .iter()
+            .filter_map(|txn| match &txn.txn_data {
+                Some(TxnData::User(user_txn)) => Some(&user_txn.events),
+                _ => None,
+            })
+            .flatten()
+            .filter_map(|event| contract_event(contract_address, event))
+            .collect::<Vec<_>>();
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let addresses = events
+            .iter()
+            .flat_map(|(_, _, addresses)| addresses.iter().cloned())
+            .collect::<Vec<_>>();
+        let mut conn = get_db_connection(&self.db_pool).await?;
+        let owners = SubAccount::get_by_addresses(&addresses, &mut conn)
+            .await?
+            .into_iter()
+            .map(|subaccount| (subaccount.address, subaccount.user_id))
+            .collect::<HashMap<_, _>>();
+
+        for (typ, data, addresses) in events {
+            let mut user_ids = addresses
+                .iter()
+                .filter_map(|address| owners.get(address))
+                .collect::<Vec<_>>();
+            // both sides of a trade may belong to the same user
+            user_ids.sort();
+            user_ids.dedup();
+            for user_id in user_ids {
+                if let Some(event) =
+                    PushEvent::from_contract_event(*user_id, contract_address, typ, data.clone())
+                {
+                    self.push_hub.publish(event);
+                }
+            }
+        }
         Ok(())
     }
 }
+
+/// Type, data and the standardized addresses in the data of an event emitted by the contract
+fn contract_event<'a>(
+    contract_address: &str,
+    event: &'a Event,
+) -> Option<(&'a str, serde_json::Value, Vec<String>)> {
+    let address = event.type_str.split("::").next()?;
+    if standardize_address(address) != standardize_address(contract_address) {
+        return None;
+    }
+    let data = serde_json::from_str(&event.data).ok()?;
+    let mut addresses = vec![];
+    collect_addresses(&data, &mut addresses);
+    Some((event.type_str.as_str(), data, addresses))
+}
+
+/// Subaccounts appear in event data as `0x`-prefixed strings, possibly nested in structs
+fn collect_addresses(value: &serde_json::Value, addresses: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s)
+            if s.len() > 2
+                && s.starts_with("0x")
+                && s[2..].chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            addresses.push(standardize_address(s));
+        }
+        serde_json::Value::Array(values) => {
+            values
+                .iter()
+                .for_each(|value| collect_addresses(value, addresses));
+        }
+        serde_json::Value::Object(fields) => {
+            fields
+                .values()
+                .for_each(|value| collect_addresses(value, addresses));
+        }
+        _ => {}
+    }
+}
+
+fn transaction_stream_config(
+    stream_config: &StreamConfig,
+    starting_version: i64,
+) -> anyhow::Result<TransactionStreamConfig> {
+    serde_json::from_value(json!({
+        "indexer_grpc_data_service_address": stream_config.indexer_grpc,
+        "starting_version": starting_version,
+        "request_ending_version": stream_config.ending_version,
+        "auth_token": stream_config.auth_token,
+        "request_name_header": stream_config.request_name_header,
+    }))
+    .context("Invalid stream config")
+}
+
+fn naive_datetime(timestamp: &Timestamp) -> Option<chrono::NaiveDateTime> {
+    chrono::DateTime::from_timestamp(timestamp.seconds, timestamp.nanos as u32)
+        .map(|datetime| datetime.naive_utc())
+}
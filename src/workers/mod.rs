@@ -1,4 +1,6 @@
 pub mod indexer_processor;
+pub mod sponsor_monitor;
+pub mod transaction_outbox;
 
 use std::{sync::Arc, time::Duration};
 
@@ -6,22 +8,49 @@ use tokio::time::sleep;
 use tokio_util::task::TaskTracker;
 
 use crate::{
+    cache::Cache,
     config::Config,
-    utils::{database_utils::ArcDbPool, shutdown_utils},
-    workers::indexer_processor::IndexerProcessor,
+    utils::{
+        chain_client::ChainClient, database_utils::ArcDbPool, market_indexer::MarketIndexer,
+        push::PushHub, shutdown_utils, trading::Trading,
+    },
+    workers::{
+        indexer_processor::IndexerProcessor, sponsor_monitor::SponsorMonitor,
+        transaction_outbox::TransactionOutbox,
+    },
 };
 
 pub struct Worker {
     pub indexer_processor: Arc<IndexerProcessor>,
+    pub transaction_outbox: Arc<TransactionOutbox>,
+    pub sponsor_monitor: Arc<SponsorMonitor>,
+    pub market_indexer: Arc<MarketIndexer<Cache>>,
 }
 
 impl Worker {
-    pub fn new(config: Arc<Config>, pool: ArcDbPool) -> Self {
+    pub fn new(
+        config: Arc<Config>,
+        pool: ArcDbPool,
+        aptos_client: Arc<dyn ChainClient>,
+        trading: Arc<Trading>,
+        market_indexer: Arc<MarketIndexer<Cache>>,
+        push_hub: Arc<PushHub>,
+    ) -> Self {
         Self {
             indexer_processor: Arc::new(IndexerProcessor::new(
                 Arc::clone(&pool),
                 Arc::clone(&config),
+                Arc::clone(&push_hub),
             )),
+            transaction_outbox: Arc::new(TransactionOutbox::new(
+                Arc::clone(&pool),
+                Arc::clone(&config),
+                Arc::clone(&aptos_client),
+                trading,
+                push_hub,
+            )),
+            sponsor_monitor: Arc::new(SponsorMonitor::new(Arc::clone(&config), aptos_client)),
+            market_indexer,
         }
     }
 
@@ -33,6 +62,15 @@ impl Worker {
         let ip_self = Arc::clone(self);
         tracker.spawn(async move { ip_self.indexer_processor.start().await });
 
+        let outbox_self = Arc::clone(self);
+        tracker.spawn(async move { outbox_self.transaction_outbox.start().await });
+
+        let monitor_self = Arc::clone(self);
+        tracker.spawn(async move { monitor_self.sponsor_monitor.start().await });
+
+        let market_self = Arc::clone(self);
+        tracker.spawn(async move { market_self.market_indexer.start().await });
+
         let cancel_token = shutdown_utils::get_shutdown_token();
         tokio::select! {
             _ = cancel_token.cancelled() => {
@@ -0,0 +1,104 @@
+use std::{collections::HashSet, str::FromStr, sync::Arc, time::Duration};
+
+use aptos_sdk::types::account_address::AccountAddress;
+use teloxide::{prelude::*, types::ParseMode};
+use tokio::{sync::Mutex, time::sleep};
+
+use crate::{
+    config::Config,
+    utils::{chain_client::ChainClient, shutdown_utils, view_requests::view_fa_balance_request},
+};
+
+const OCTAS_PER_APT: f64 = 100_000_000.0;
+
+/// Watches the APT balance of the sponsor accounts and alerts admins when one drops below
+/// `low_balance_threshold`. Each sponsor is reported once until it is topped up again.
+pub struct SponsorMonitor {
+    config: Arc<Config>,
+    aptos_client: Arc<dyn ChainClient>,
+    bot: Bot,
+    low_sponsors: Mutex<HashSet<AccountAddress>>,
+}
+
+impl SponsorMonitor {
+    pub fn new(config: Arc<Config>, aptos_client: Arc<dyn ChainClient>) -> Self {
+        let bot = config.bot_config.bot();
+        Self {
+            config,
+            aptos_client,
+            bot,
+            low_sponsors: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub async fn start(&self) -> anyhow::Result<()> {
+        tracing::info!("Starting sponsor monitor");
+        let interval =
+            Duration::from_secs(self.config.admin_config.gas_sponsorship.check_interval_secs);
+        let cancel_token = shutdown_utils::get_shutdown_token();
+        loop {
+            for sponsor in self.aptos_client.sponsor_addresses() {
+                if let Err(err) = self.check(sponsor).await {
+                    tracing::error!("Failed to check sponsor {}: {:?}", sponsor, err);
+                }
+            }
+            tokio::select! {
+                _ = cancel_token.cancelled() => break,
+                _ = sleep(interval) => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn check(&self, sponsor: AccountAddress) -> anyhow::Result<()> {
+        let request = view_fa_balance_request(
+            &AccountAddress::TEN.to_hex_literal(),
+            &sponsor.to_hex_literal(),
+        )?;
+        let response = self.aptos_client.view(&request).await?;
+        let balance = response
+            .first()
+            .and_then(|value| value.as_str())
+            .map(u64::from_str)
+            .transpose()?
+            .unwrap_or_default();
+
+        let threshold = self
+            .config
+            .admin_config
+            .gas_sponsorship
+            .low_balance_threshold;
+        let mut low_sponsors = self.low_sponsors.lock().await;
+        if balance >= threshold {
+            low_sponsors.remove(&sponsor);
+            return Ok(());
+        }
+        if !low_sponsors.insert(sponsor) {
+            return Ok(());
+        }
+
+        let text = format!(
+            "⚠️ Sponsor <code>{}</code> is running low on gas\n\nBalance: <b>{:.4} APT</b>, alert threshold: {:.4} APT",
+            sponsor.to_standard_string(),
+            balance as f64 / OCTAS_PER_APT,
+            threshold as f64 / OCTAS_PER_APT
+        );
+        tracing::warn!(
+            "Sponsor {} balance {} is below {}",
+            sponsor,
+            balance,
+            threshold
+        );
+        for chat_id in &self.config.admin_config.admin_chat_ids {
+            if let Err(err) = self
+                .bot
+                .send_message(ChatId(*chat_id), text.clone())
+                .parse_mode(ParseMode::Html)
+                .await
+            {
+                tracing::error!("Failed to alert admin {}: {:?}", chat_id, err);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,405 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use aptos_sdk::{bcs, types::transaction::TransactionPayload};
+use diesel::{ExpressionMethods, query_dsl::methods::FilterDsl};
+use teloxide::{
+    prelude::*,
+    types::{MessageId, ParseMode},
+};
+use tokio::time::sleep;
+
+use crate::{
+    config::Config,
+    models::db::{
+        gas_usage::GasUsage,
+        transaction_outbox::{
+            OutboxTransaction, STATUS_COMMITTED, STATUS_FAILED, STATUS_PENDING, STATUS_SUBMITTED,
+            STATUS_WAITING, expiration_of,
+        },
+        users::User,
+    },
+    schema::transaction_outbox,
+    utils::{
+        chain_client::{ChainClient, GasPayer, TransactionStatus},
+        database_connection::get_db_connection,
+        database_utils::{ArcDbPool, DbPoolConnection},
+        db_execution::execute_with_better_error,
+        metrics,
+        push::{OrderUpdate, PushEvent, PushHub},
+        sequence_numbers::is_sequence_number_error,
+        shutdown_utils,
+        trading::Trading,
+        vm_status::explain_vm_status,
+    },
+};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const BATCH_SIZE: i64 = 50;
+const MAX_ATTEMPTS: i32 = 3;
+/// How long a batch holds the txns it claimed, longer than a batch takes
+const LEASE_SECS: i64 = 120;
+/// Kinds of txns whose progress is pushed to the user's WebSocket clients
+const ORDER_KINDS: [&str; 2] = ["order", "cancel_order"];
+
+/// Submits txns queued by the bot and the API, signing the ones waiting on another txn once
+/// it committed, follows them until they commit and edits the
+/// user's "Processing…" message with the outcome. Txns the node rejected are submitted again
+/// and expired ones or ones with a stale sequence number are re-signed, up to `MAX_ATTEMPTS`
+/// in total. Status changes of order txns are pushed to WebSocket clients.
+pub struct TransactionOutbox {
+    db_pool: ArcDbPool,
+    config: Arc<Config>,
+    aptos_client: Arc<dyn ChainClient>,
+    trading: Arc<Trading>,
+    push_hub: Arc<PushHub>,
+    bot: Bot,
+}
+
+impl TransactionOutbox {
+    pub fn new(
+        db_pool: ArcDbPool,
+        config: Arc<Config>,
+        aptos_client: Arc<dyn ChainClient>,
+        trading: Arc<Trading>,
+        push_hub: Arc<PushHub>,
+    ) -> Self {
+        let bot = config.bot_config.bot();
+        Self {
+            db_pool,
+            config,
+            aptos_client,
+            trading,
+            push_hub,
+            bot,
+        }
+    }
+
+    pub async fn start(&self) -> anyhow::Result<()> {
+        tracing::info!("Starting transaction outbox");
+        let cancel_token = shutdown_utils::get_shutdown_token();
+        loop {
+            if let Err(err) = self.process_batch().await {
+                tracing::error!("Transaction outbox batch failed: {:?}", err);
+            }
+            tokio::select! {
+                _ = cancel_token.cancelled() => break,
+                _ = sleep(POLL_INTERVAL) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves every open txn one step: waiting ones are signed, pending ones submitted and
+    /// submitted ones confirmed. Only txns no other instance claimed are moved.
+    pub async fn process_batch(&self) -> anyhow::Result<()> {
+        let mut conn = get_db_connection(&self.db_pool).await?;
+        for txn in OutboxTransaction::claim_open(
+            BATCH_SIZE,
+            chrono::Duration::seconds(LEASE_SECS),
+            &mut conn,
+        )
+        .await?
+        {
+            let result = match txn.status.as_str() {
+                STATUS_WAITING => self.sign_waiting(&txn, &mut conn).await,
+                STATUS_PENDING => self.submit(&txn, &mut conn).await,
+                _ => self.confirm(&txn, &mut conn).await,
+            };
+            if let Err(err) = result {
+                tracing::error!("Outbox txn {} failed: {:?}", txn.id, err);
+            }
+            OutboxTransaction::unlock(txn.id, &mut conn).await?;
+        }
+        Ok(())
+    }
+
+    /// Signs a txn once the one it depends on committed, it spends what that one brought in
+    /// and would not pass simulation before. Fails along with the txn it depends on.
+    async fn sign_waiting(
+        &self,
+        txn: &OutboxTransaction,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        let depends_on = txn
+            .depends_on
+            .context("Waiting outbox txn depends on no txn")?;
+        let parent = OutboxTransaction::get_by_id(depends_on, conn)
+            .await?
+            .context("Outbox txn it depends on not found")?;
+        match parent.status.as_str() {
+            STATUS_COMMITTED => {}
+            STATUS_FAILED => {
+                let error = format!(
+                    "{} failed: {}",
+                    parent.summary,
+                    parent.error.as_deref().unwrap_or("unknown error")
+                );
+                let text = format!("❌ {}\n\n{}", txn.summary, error);
+                return self
+                    .finish(txn, STATUS_FAILED, Some(error), text, conn)
+                    .await;
+            }
+            _ => return Ok(()),
+        }
+
+        let db_user = User::get_by_id(txn.user_id, conn)
+            .await?
+            .context("Outbox txn user not found")?;
+        let payload = bcs::from_bytes::<TransactionPayload>(&txn.payload)?;
+        let signed_txn = match self.trading.sign_txn(&db_user, payload, conn).await {
+            Ok(signed_txn) => signed_txn,
+            Err(err) => {
+                let text = format!("❌ {}\n\n{}", txn.summary, err);
+                return self
+                    .finish(txn, STATUS_FAILED, Some(err.to_string()), text, conn)
+                    .await;
+            }
+        };
+
+        let query =
+            diesel::update(transaction_outbox::table.filter(transaction_outbox::id.eq(txn.id)))
+                .set((
+                    transaction_outbox::signed_txn.eq(Some(bcs::to_bytes(&*signed_txn)?)),
+                    transaction_outbox::expires_at.eq(Some(expiration_of(&signed_txn)?)),
+                    transaction_outbox::status.eq(STATUS_PENDING),
+                    transaction_outbox::sponsored_gas_fee.eq(signed_txn
+                        .sponsored_gas_fee()
+                        .map(i64::try_from)
+                        .transpose()?),
+                    transaction_outbox::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ));
+        execute_with_better_error(conn, vec![query]).await?;
+        // submitted by the next batch
+        signed_txn.into_inner();
+        Ok(())
+    }
+
+    async fn submit(
+        &self,
+        txn: &OutboxTransaction,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        let signed_txn = txn.signed_txn()?;
+        let started = Instant::now();
+        let result = self.aptos_client.submit_transaction(&signed_txn).await;
+        metrics::TRANSACTION_SUBMIT_SECONDS
+            .with_label_values(&[txn.kind.as_str(), metrics::outcome(&result)])
+            .observe(started.elapsed().as_secs_f64());
+        match result {
+            Ok(txn_hash) => {
+                let query = diesel::update(
+                    transaction_outbox::table.filter(transaction_outbox::id.eq(txn.id)),
+                )
+                .set((
+                    transaction_outbox::txn_hash.eq(Some(txn_hash.clone())),
+                    transaction_outbox::status.eq(STATUS_SUBMITTED),
+                    transaction_outbox::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ));
+                execute_with_better_error(conn, vec![query]).await?;
+                self.push_order_update(txn, STATUS_SUBMITTED, Some(txn_hash), None);
+                Ok(())
+            }
+            // the sequence number of the txn is taken or out of reach, only a new one helps
+            Err(err) if txn.is_expired() || is_sequence_number_error(&err.to_string()) => {
+                self.retry(txn, err.to_string(), conn).await
+            }
+            Err(err) => self.resubmit(txn, err.to_string(), conn).await,
+        }
+    }
+
+    async fn confirm(
+        &self,
+        txn: &OutboxTransaction,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        let txn_hash = txn
+            .txn_hash
+            .as_deref()
+            .context("Submitted outbox txn has no hash")?;
+        match self.aptos_client.transaction_status(txn_hash).await? {
+            TransactionStatus::Pending => Ok(()),
+            TransactionStatus::Committed { gas_fee } => {
+                if let Some(sponsored_gas_fee) = txn.sponsored_gas_fee {
+                    GasUsage::correct(
+                        txn.user_id,
+                        txn.created_at.date(),
+                        sponsored_gas_fee,
+                        gas_fee,
+                        conn,
+                    )
+                    .await?;
+                }
+                let text = format!(
+                    "✅ {} <a href='{}'>View Txn</a>",
+                    txn.summary,
+                    self.config.network().txn_url(txn_hash)
+                );
+                self.finish(txn, STATUS_COMMITTED, None, text, conn).await?;
+                Ok(())
+            }
+            TransactionStatus::Failed(vm_status) => {
+                let text = format!(
+                    "❌ {}\n\n{} <a href='{}'>View Txn</a>",
+                    txn.summary,
+                    explain_vm_status(&vm_status),
+                    self.config.network().txn_url(txn_hash)
+                );
+                self.finish(txn, STATUS_FAILED, Some(vm_status), text, conn)
+                    .await
+            }
+            TransactionStatus::NotFound if txn.is_expired() => {
+                self.retry(txn, "Transaction expired".to_string(), conn)
+                    .await
+            }
+            TransactionStatus::NotFound => Ok(()),
+        }
+    }
+
+    /// Leaves the txn pending so the next batch submits the same signed txn again, the node
+    /// may have accepted it even though the request failed
+    async fn resubmit(
+        &self,
+        txn: &OutboxTransaction,
+        error: String,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        tracing::warn!("Resubmitting outbox txn {}: {}", txn.id, error);
+        if txn.attempts + 1 >= MAX_ATTEMPTS {
+            return self.give_up(txn, error, conn).await;
+        }
+
+        let query =
+            diesel::update(transaction_outbox::table.filter(transaction_outbox::id.eq(txn.id)))
+                .set((
+                    transaction_outbox::attempts.eq(txn.attempts + 1),
+                    transaction_outbox::error.eq(Some(error)),
+                    transaction_outbox::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ));
+        execute_with_better_error(conn, vec![query]).await?;
+        Ok(())
+    }
+
+    /// Re-signs the payload of an expired txn, or of one whose sequence number the node
+    /// refused, with a fresh sequence number and expiration
+    async fn retry(
+        &self,
+        txn: &OutboxTransaction,
+        error: String,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        tracing::warn!("Retrying outbox txn {}: {}", txn.id, error);
+        if txn.attempts + 1 >= MAX_ATTEMPTS {
+            return self.give_up(txn, error, conn).await;
+        }
+
+        let db_user = User::get_by_id(txn.user_id, conn)
+            .await?
+            .context("Outbox txn user not found")?;
+        let payload = bcs::from_bytes::<TransactionPayload>(&txn.payload)?;
+        let expired_txn = txn.signed_txn()?;
+        // the expired txn never made it on chain, the new one can take its sequence number
+        self.aptos_client.release_sequence_number(&expired_txn);
+        let signed_txn = match self
+            .trading
+            .resign_txn(&db_user, payload, GasPayer::of(&expired_txn), conn)
+            .await
+        {
+            Ok(signed_txn) => signed_txn,
+            Err(err) => {
+                let text = format!("❌ {}\n\n{}", txn.summary, err);
+                return self
+                    .finish(txn, STATUS_FAILED, Some(err.to_string()), text, conn)
+                    .await;
+            }
+        };
+
+        let query =
+            diesel::update(transaction_outbox::table.filter(transaction_outbox::id.eq(txn.id)))
+                .set((
+                    transaction_outbox::signed_txn.eq(Some(bcs::to_bytes(&*signed_txn)?)),
+                    transaction_outbox::expires_at.eq(Some(expiration_of(&signed_txn)?)),
+                    transaction_outbox::txn_hash.eq(None::<String>),
+                    transaction_outbox::status.eq(STATUS_PENDING),
+                    transaction_outbox::attempts.eq(txn.attempts + 1),
+                    transaction_outbox::error.eq(Some(error)),
+                    transaction_outbox::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ));
+        execute_with_better_error(conn, vec![query]).await?;
+        // submitted by the next batch
+        signed_txn.into_inner();
+        Ok(())
+    }
+
+    /// Fails the txn after `MAX_ATTEMPTS`, giving its sequence number back since it never
+    /// made it on chain
+    async fn give_up(
+        &self,
+        txn: &OutboxTransaction,
+        error: String,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        if let Ok(signed_txn) = txn.signed_txn() {
+            self.aptos_client.release_sequence_number(&signed_txn);
+        }
+        let text = format!("❌ {}\n\n{}", txn.summary, error);
+        self.finish(txn, STATUS_FAILED, Some(error), text, conn)
+            .await
+    }
+
+    async fn finish(
+        &self,
+        txn: &OutboxTransaction,
+        status: &str,
+        error: Option<String>,
+        text: String,
+        conn: &mut DbPoolConnection<'_>,
+    ) -> anyhow::Result<()> {
+        if status == STATUS_FAILED {
+            metrics::TRANSACTION_FAILURES
+                .with_label_values(&[txn.kind.as_str()])
+                .inc();
+        }
+        let query =
+            diesel::update(transaction_outbox::table.filter(transaction_outbox::id.eq(txn.id)))
+                .set((
+                    transaction_outbox::status.eq(status),
+                    transaction_outbox::error.eq(error.clone()),
+                    transaction_outbox::updated_at.eq(chrono::Utc::now().naive_utc()),
+                ));
+        execute_with_better_error(conn, vec![query]).await?;
+        self.push_order_update(txn, status, txn.txn_hash.clone(), error);
+
+        if let (Some(chat_id), Some(message_id)) = (txn.chat_id, txn.message_id) {
+            self.bot
+                .edit_message_text(ChatId(chat_id), MessageId(message_id), text)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Ok(())
+    }
+    fn push_order_update(
+        &self,
+        txn: &OutboxTransaction,
+        status: &str,
+        txn_hash: Option<String>,
+        error: Option<String>,
+    ) {
+        if !ORDER_KINDS.contains(&txn.kind.as_str()) {
+            return;
+        }
+        self.push_hub.publish(PushEvent::Order {
+            user_id: txn.user_id,
+            update: OrderUpdate {
+                id: txn.id,
+                kind: txn.kind.clone(),
+                status: status.to_string(),
+                txn_hash,
+                error,
+            },
+        });
+    }
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use axum::http::StatusCode;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use pace_api::{
+    models::db::api_keys::SCOPE_READ, schema::api_keys, utils::api_keys::create_api_key,
+};
+
+use crate::common::TestApp;
+
+#[tokio::test]
+async fn api_key_rate_limit_errors_carry_the_request_id() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(0).await;
+    let mut conn = app.pool.get().await.unwrap();
+    let (api_key, secret) = create_api_key(&app.config, db_user.id, "bot", SCOPE_READ, &mut conn)
+        .await
+        .unwrap();
+    diesel::update(api_keys::table.find(api_key.id))
+        .set(api_keys::rate_limit_per_minute.eq(1))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    drop(conn);
+
+    let (status, body) = app
+        .request_with_api_key("/api/v1/me", &api_key.key_id, &secret)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let (status, body) = app
+        .request_with_api_key("/api/v1/me", &api_key.key_id, &secret)
+        .await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS, "{}", body);
+    assert!(body["request_id"].as_str().is_some(), "{}", body);
+}
+
+#[tokio::test]
+async fn unsigned_requests_leave_the_key_quota_alone() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(0).await;
+    let mut conn = app.pool.get().await.unwrap();
+    let (api_key, secret) = create_api_key(&app.config, db_user.id, "bot", SCOPE_READ, &mut conn)
+        .await
+        .unwrap();
+    diesel::update(api_keys::table.find(api_key.id))
+        .set(api_keys::rate_limit_per_minute.eq(1))
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    drop(conn);
+
+    let (status, body) = app
+        .request_with_api_key("/api/v1/me", &api_key.key_id, "not-the-secret")
+        .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", body);
+    let (status, body) = app
+        .request_with_api_key("/api/v1/me", &api_key.key_id, &secret)
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+}
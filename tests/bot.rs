@@ -0,0 +1,121 @@
+mod common;
+
+use bigdecimal::BigDecimal;
+use pace_api::telegram_bot::actions::{
+    CallbackQueryProcessor, confirm_subaccount_deposit::ConfirmSubaccountDeposit,
+    confirm_subaccount_transfer::ConfirmSubaccountTransfer,
+};
+use serde_json::json;
+use teloxide::types::CallbackQuery;
+
+use crate::common::{COLLATERAL_METADATA, TestApp, mock_api::message};
+
+/// 100 USDC in base units
+const WALLET_COLLATERAL: u64 = 100_000_000;
+
+/// Telegram id unused in the database, which may be kept between runs
+fn new_tg_id() -> i64 {
+    rand::random::<u32>().into()
+}
+
+/// Tap of `tg_id` on a button of a message the bot sent them
+fn callback_query(tg_id: i64) -> CallbackQuery {
+    serde_json::from_value(json!({
+        "id": "1",
+        "from": { "id": tg_id, "is_bot": false, "first_name": "Test" },
+        "chat_instance": "1",
+        "message": message(tg_id, 1, "Confirm"),
+        "data": "confirm",
+    }))
+    .expect("Invalid callback query")
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_commit_and_report_back_in_the_chat() {
+    let app = TestApp::new().await;
+    let tg_id = new_tg_id();
+    let db_user = app.create_telegram_user(tg_id, WALLET_COLLATERAL).await;
+    let mut conn = app.pool.get().await.unwrap();
+    let subaccount = app
+        .trading
+        .primary_subaccount(&db_user, &mut conn)
+        .await
+        .unwrap()
+        .address;
+
+    ConfirmSubaccountDeposit {
+        amount: BigDecimal::from(40),
+    }
+    .process(app.telegram_bot(), app.bot(), callback_query(tg_id))
+    .await
+    .unwrap();
+    app.drain_outbox().await;
+
+    assert_eq!(
+        app.chain
+            .collateral(&subaccount, COLLATERAL_METADATA)
+            .unwrap(),
+        40_000_000
+    );
+
+    ConfirmSubaccountTransfer {
+        from: 0,
+        to: None,
+        amount: BigDecimal::from(15),
+    }
+    .process(app.telegram_bot(), app.bot(), callback_query(tg_id))
+    .await
+    .unwrap();
+    app.drain_outbox().await;
+
+    assert_eq!(
+        app.chain
+            .collateral(&subaccount, COLLATERAL_METADATA)
+            .unwrap(),
+        25_000_000
+    );
+    assert_eq!(
+        app.chain
+            .balance(&db_user.address, COLLATERAL_METADATA)
+            .unwrap(),
+        WALLET_COLLATERAL - 25_000_000
+    );
+
+    // each "Processing…" message is edited with the outcome
+    let outcomes = app
+        .mock_api
+        .calls("editMessageText")
+        .into_iter()
+        .map(|body| body["text"].as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(outcomes.len(), 2, "{:?}", outcomes);
+    assert!(
+        outcomes[0].starts_with("✅ Deposited 40 USDC to your subaccount"),
+        "{}",
+        outcomes[0]
+    );
+    assert!(
+        outcomes[1].starts_with("✅ Withdrew 15 USDC to your wallet"),
+        "{}",
+        outcomes[1]
+    );
+}
+
+#[tokio::test]
+async fn withdrawing_more_than_is_withdrawable_queues_nothing() {
+    let app = TestApp::new().await;
+    let tg_id = new_tg_id();
+    let db_user = app.create_telegram_user(tg_id, WALLET_COLLATERAL).await;
+
+    let err = ConfirmSubaccountTransfer {
+        from: 0,
+        to: None,
+        amount: BigDecimal::from(15),
+    }
+    .process(app.telegram_bot(), app.bot(), callback_query(tg_id))
+    .await
+    .unwrap_err();
+
+    assert!(err.to_string().contains("withdrawable"), "{}", err);
+    assert!(app.outbox_txns_of(&db_user).await.is_empty());
+}
@@ -0,0 +1,116 @@
+//! Stands in for the Telegram Bot API and the Decibel API. Bot API calls are recorded and
+//! answered with a message, account overviews are read off the chain simulator.
+
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    routing::{get, post},
+};
+use pace_api::utils::chain_simulator::ChainSimulator;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+use crate::common::COLLATERAL_METADATA;
+
+/// Unix time of the messages the mock sends, a zero date would make them inaccessible
+const MESSAGE_DATE: i64 = 1_700_000_000;
+
+#[derive(Clone)]
+struct MockState {
+    chain: Arc<ChainSimulator>,
+    calls: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+pub struct MockApi {
+    pub url: String,
+    calls: Arc<Mutex<Vec<(String, Value)>>>,
+}
+
+impl MockApi {
+    /// Listens right away so its url can go into the config, requests are answered once
+    /// `serve` is called with the simulator built from that config
+    pub async fn bind() -> (Self, TcpListener) {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("Failed to bind the mock API");
+        let url = format!(
+            "http://{}",
+            listener.local_addr().expect("Mock API has no address")
+        );
+        let calls = Arc::new(Mutex::new(vec![]));
+        (Self { url, calls }, listener)
+    }
+
+    pub fn serve(&self, listener: TcpListener, chain: Arc<ChainSimulator>) {
+        let router = Router::new()
+            .route("/{bot}/{method}", post(bot_api))
+            .route("/api/v1/account_overviews", get(account_overview))
+            .with_state(MockState {
+                chain,
+                calls: Arc::clone(&self.calls),
+            });
+        tokio::spawn(async move { axum::serve(listener, router).await });
+    }
+
+    /// Bodies of the Bot API calls of `method`, e.g. `editMessageText`, oldest first
+    pub fn calls(&self, method: &str) -> Vec<Value> {
+        self.calls
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(method))
+            .map(|(_, body)| body.clone())
+            .collect()
+    }
+}
+
+/// Message JSON as the Bot API returns it
+pub fn message(chat_id: i64, message_id: i64, text: &str) -> Value {
+    json!({
+        "message_id": message_id,
+        "date": MESSAGE_DATE,
+        "chat": { "id": chat_id, "type": "private", "first_name": "Test" },
+        "from": { "id": 1, "is_bot": true, "first_name": "Test Bot" },
+        "text": text,
+    })
+}
+
+async fn bot_api(
+    State(state): State<MockState>,
+    Path((_bot, method)): Path<(String, String)>,
+    Json(body): Json<Value>,
+) -> Json<Value> {
+    let message_id = {
+        let mut calls = state.calls.lock().unwrap();
+        calls.push((method, body.clone()));
+        calls.len() as i64
+    };
+    let chat_id = body["chat_id"].as_i64().unwrap_or_default();
+    let text = body["text"].as_str().unwrap_or_default();
+    Json(json!({ "ok": true, "result": message(chat_id, message_id, text) }))
+}
+
+#[derive(Deserialize)]
+struct OverviewQuery {
+    user: String,
+}
+
+async fn account_overview(
+    State(state): State<MockState>,
+    Query(query): Query<OverviewQuery>,
+) -> Json<Value> {
+    let collateral = state
+        .chain
+        .collateral(&query.user, COLLATERAL_METADATA)
+        .unwrap_or_default();
+    let balance = format!("{}.{:06}", collateral / 1_000_000, collateral % 1_000_000);
+    Json(json!({
+        "perp_equity_balance": balance,
+        "unrealized_pnl": "0",
+        "total_margin": "0",
+        "usdc_cross_withdrawable_balance": balance,
+    }))
+}
@@ -0,0 +1,449 @@
+//! Runs the HTTP handlers, the bot handlers, `Trading` and the outbox worker against
+//! `ChainSimulator`, `MockApi` and a Postgres database. That is the one `TEST_DATABASE_URL`
+//! points to, or a throwaway server started for the test when it is unset. Migrations are
+//! applied on start and tests take turns, since the outbox worker picks up every open txn of
+//! the database.
+
+#![allow(dead_code)]
+
+pub mod mock_api;
+
+use std::{
+    net::SocketAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::ConnectInfo,
+    http::{Method, Request, StatusCode, header},
+};
+use bigdecimal::BigDecimal;
+use diesel::{Connection, ExpressionMethods, QueryDsl};
+use diesel_async::{
+    AsyncPgConnection, RunQueryDsl, async_connection_wrapper::AsyncConnectionWrapper,
+};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use hmac::{Hmac, Mac};
+use jsonwebtoken::{EncodingKey, Header};
+use pace_api::{
+    cache::{AssetContext, Cache, ICache, Market},
+    config::Config,
+    http_server::{HttpServer, middlewares::authentication::Claims},
+    models::db::{
+        transaction_outbox::OutboxTransaction,
+        users::{CUSTODY_CUSTODIAL, User},
+    },
+    schema::{transaction_outbox, users},
+    telegram_bot::TelegramBot,
+    utils::{
+        api_keys::{API_KEY_HEADER, API_SIGNATURE_HEADER, API_TIMESTAMP_HEADER},
+        chain_client::ChainClient,
+        chain_simulator::ChainSimulator,
+        database_connection::new_db_pool,
+        database_utils::ArcDbPool,
+        push::PushHub,
+        signing_policy::SigningPolicy,
+        trading::Trading,
+    },
+    workers::transaction_outbox::TransactionOutbox,
+};
+use postgresql_embedded::PostgreSQL;
+use serde_json::Value;
+use sha2::Sha256;
+use teloxide::Bot;
+use tokio::sync::{Mutex, MutexGuard};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::common::mock_api::MockApi;
+
+pub const CONTRACT_ADDRESS: &str = "0xdec1be1";
+pub const COLLATERAL_METADATA: &str = "0xc011a7e2a1";
+pub const MARKET_ADDRESS: &str = "0xb7c";
+pub const MARKET_NAME: &str = "BTC/USD";
+/// `ChainId::test()`, the chain id of the simulator
+const CHAIN_ID: u8 = 4;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("src/db_migrations/migrations");
+
+static DATABASE: Mutex<()> = Mutex::const_new(());
+
+pub struct TestApp {
+    pub config: Arc<Config>,
+    pub pool: ArcDbPool,
+    pub chain: Arc<ChainSimulator>,
+    pub trading: Arc<Trading>,
+    pub outbox: TransactionOutbox,
+    pub mock_api: MockApi,
+    aptos_client: Arc<dyn ChainClient>,
+    cache: Arc<Cache>,
+    router: Router,
+    /// Every request comes from its own peer so the per-IP governor stays out of the way
+    next_peer: AtomicU32,
+    _database: MutexGuard<'static, ()>,
+    /// Stopped once the pool above is dropped
+    _postgres: Option<PostgreSQL>,
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        let database = DATABASE.lock().await;
+        let (database_url, postgres) = match std::env::var("TEST_DATABASE_URL") {
+            Ok(database_url) => (database_url, None),
+            Err(_) => {
+                let (database_url, postgres) = start_postgres().await;
+                (database_url, Some(postgres))
+            }
+        };
+        run_migrations(&database_url).await;
+
+        let (mock_api, listener) = MockApi::bind().await;
+        let config = Arc::new(test_config(&database_url, &mock_api.url));
+        let pool = new_db_pool(&database_url, config.db_config.pool_size).await;
+        let chain = Arc::new(
+            ChainSimulator::new(CONTRACT_ADDRESS, COLLATERAL_METADATA, 2)
+                .expect("Failed to create the chain simulator")
+                .with_signing_policy(SigningPolicy::new(&config).expect("Invalid signing policy")),
+        );
+        mock_api.serve(listener, Arc::clone(&chain));
+        let aptos_client: Arc<dyn ChainClient> = chain.clone();
+        let cache = Arc::new(Cache::default());
+        let trading = Arc::new(Trading::new(Arc::clone(&config), Arc::clone(&aptos_client)));
+        let push_hub = Arc::new(PushHub::new());
+
+        let mut conn = pool
+            .get()
+            .await
+            .expect("Failed to connect to the test database");
+        // left over by earlier runs, the worker would try to submit them to this simulator
+        diesel::delete(transaction_outbox::table)
+            .execute(&mut conn)
+            .await
+            .expect("Failed to clear the outbox");
+        diesel::sql_query(format!(
+            "INSERT INTO tokens (name, symbol, address, decimals, is_collateral, chain_id) \
+            VALUES ('USD Coin', 'USDC', '{}', 6, TRUE, {}) ON CONFLICT DO NOTHING",
+            COLLATERAL_METADATA, CHAIN_ID
+        ))
+        .execute(&mut conn)
+        .await
+        .expect("Failed to seed the collateral token");
+        drop(conn);
+
+        cache
+            .set_markets(vec![Market {
+                market_addr: MARKET_ADDRESS.to_string(),
+                market_name: MARKET_NAME.to_string(),
+                sz_decimals: 5,
+                px_decimals: 8,
+                max_leverage: 20,
+                tick_size: 1,
+                min_size: 1,
+                lot_size: 1,
+                max_open_interest: 1_000_000.0,
+            }])
+            .await;
+        let mark_price = BigDecimal::from(50_000);
+        cache
+            .set_asset_contexts(vec![AssetContext {
+                market: MARKET_NAME.to_string(),
+                volume_24h: BigDecimal::from(0),
+                open_interest: BigDecimal::from(0),
+                mark_price: mark_price.clone(),
+                mid_price: mark_price.clone(),
+                oracle_price: mark_price.clone(),
+                previous_day_price: mark_price,
+                price_change_pct_24h: BigDecimal::from(0),
+                price_history: vec![],
+            }])
+            .await;
+
+        let outbox = TransactionOutbox::new(
+            Arc::clone(&pool),
+            Arc::clone(&config),
+            Arc::clone(&aptos_client),
+            Arc::clone(&trading),
+            Arc::clone(&push_hub),
+        );
+        let router = Arc::new(HttpServer::new(
+            Arc::clone(&config),
+            Arc::clone(&pool),
+            Arc::clone(&aptos_client),
+            Arc::clone(&cache),
+            Arc::clone(&trading),
+            push_hub,
+        ))
+        .router();
+
+        Self {
+            config,
+            pool,
+            chain,
+            trading,
+            outbox,
+            mock_api,
+            aptos_client,
+            cache,
+            router,
+            next_peer: AtomicU32::new(1),
+            _database: database,
+            _postgres: postgres,
+        }
+    }
+
+    /// Bot handlers run against this app, with `bot` sending to the mock API
+    pub fn telegram_bot(&self) -> Arc<TelegramBot<Cache>> {
+        Arc::new(TelegramBot::new(
+            Arc::clone(&self.config),
+            Arc::clone(&self.pool),
+            Arc::clone(&self.aptos_client),
+            Arc::clone(&self.cache),
+            Arc::clone(&self.trading),
+        ))
+    }
+
+    pub fn bot(&self) -> Bot {
+        self.config.bot_config.bot()
+    }
+
+    /// Custodial user with a fresh wallet, holding `collateral` base units of the collateral
+    /// token
+    pub async fn create_user(&self, collateral: u64) -> User {
+        self.create_user_with_tg_id(None, collateral).await
+    }
+
+    /// `create_user` for the Telegram user `tg_id`
+    pub async fn create_telegram_user(&self, tg_id: i64, collateral: u64) -> User {
+        self.create_user_with_tg_id(Some(tg_id), collateral).await
+    }
+
+    async fn create_user_with_tg_id(&self, tg_id: Option<i64>, collateral: u64) -> User {
+        let (wallet_id, address, public_key) = self
+            .chain
+            .create_new_wallet("test")
+            .await
+            .expect("Failed to create a wallet");
+        let db_user = User {
+            id: Uuid::new_v4(),
+            tg_id,
+            tg_username: None,
+            connected_wallet: None,
+            address,
+            public_key,
+            wallet_id,
+            slippage: 20,
+            degen_mode: true,
+            custody_mode: CUSTODY_CUSTODIAL.to_string(),
+            pin_hash: None,
+            merged_into: None,
+        };
+        let mut conn = self.pool.get().await.expect("Failed to get a connection");
+        diesel::insert_into(users::table)
+            .values(db_user.clone())
+            .execute(&mut conn)
+            .await
+            .expect("Failed to insert the user");
+        if collateral > 0 {
+            self.chain
+                .fund(&db_user.address, COLLATERAL_METADATA, collateral)
+                .expect("Failed to fund the user");
+        }
+        db_user
+    }
+
+    /// Sends a request authenticated as `db_user`, returning the status and the JSON body
+    pub async fn request(
+        &self,
+        method: Method,
+        uri: &str,
+        db_user: &User,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let builder = Request::builder().method(method).uri(uri).header(
+            header::AUTHORIZATION,
+            format!("Bearer {}", self.jwt(db_user)),
+        );
+        let request = match body {
+            Some(body) => builder
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => builder.body(Body::empty()),
+        }
+        .expect("Invalid request");
+        self.send(request).await
+    }
+
+    /// Sends a GET request signed with the `key_id` API key, whose secret is `secret`
+    pub async fn request_with_api_key(
+        &self,
+        uri: &str,
+        key_id: &str,
+        secret: &str,
+    ) -> (StatusCode, Value) {
+        let timestamp = chrono::Utc::now().timestamp_millis().to_string();
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes())
+            .expect("Invalid API key secret");
+        mac.update(timestamp.as_bytes());
+        mac.update(b"GET");
+        mac.update(uri.as_bytes());
+        let request = Request::builder()
+            .method(Method::GET)
+            .uri(uri)
+            .header(API_KEY_HEADER, key_id)
+            .header(API_TIMESTAMP_HEADER, timestamp)
+            .header(
+                API_SIGNATURE_HEADER,
+                hex::encode(mac.finalize().into_bytes()),
+            )
+            .body(Body::empty())
+            .expect("Invalid request");
+        self.send(request).await
+    }
+
+    async fn send(&self, mut request: Request<Body>) -> (StatusCode, Value) {
+        let peer = self.next_peer.fetch_add(1, Ordering::Relaxed);
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((peer.to_be_bytes(), 1))));
+
+        let response = self
+            .router
+            .clone()
+            .oneshot(request)
+            .await
+            .expect("Router failed");
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read the response body");
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    fn jwt(&self, db_user: &User) -> String {
+        let now = chrono::Utc::now().timestamp() as usize;
+        let claims = Claims {
+            id: db_user.id.to_string(),
+            exp: now + 3600,
+            iat: now,
+        };
+        jsonwebtoken::encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(self.config.jwt_config.secret.as_ref()),
+        )
+        .expect("Failed to sign the JWT")
+    }
+
+    /// Runs the outbox worker until every queued txn committed or failed
+    pub async fn drain_outbox(&self) {
+        for _ in 0..10 {
+            self.outbox
+                .process_batch()
+                .await
+                .expect("Outbox batch failed");
+            let mut conn = self.pool.get().await.expect("Failed to get a connection");
+            let open = OutboxTransaction::get_open(1, &mut conn)
+                .await
+                .expect("Failed to load open outbox txns");
+            if open.is_empty() {
+                return;
+            }
+        }
+        panic!("Outbox txns are still open after 10 batches");
+    }
+
+    pub async fn outbox_txn(&self, id: Uuid) -> OutboxTransaction {
+        let mut conn = self.pool.get().await.expect("Failed to get a connection");
+        transaction_outbox::table
+            .filter(transaction_outbox::id.eq(id))
+            .select(transaction_outbox::all_columns)
+            .first::<OutboxTransaction>(&mut conn)
+            .await
+            .expect("Outbox txn not found")
+    }
+
+    pub async fn outbox_txns_of(&self, db_user: &User) -> Vec<OutboxTransaction> {
+        let mut conn = self.pool.get().await.expect("Failed to get a connection");
+        transaction_outbox::table
+            .filter(transaction_outbox::user_id.eq(db_user.id))
+            .order(transaction_outbox::created_at.asc())
+            .select(transaction_outbox::all_columns)
+            .load::<OutboxTransaction>(&mut conn)
+            .await
+            .expect("Failed to load outbox txns")
+    }
+}
+
+async fn run_migrations(database_url: &str) {
+    let database_url = database_url.to_string();
+    tokio::task::spawn_blocking(move || {
+        let mut conn = AsyncConnectionWrapper::<AsyncPgConnection>::establish(&database_url)
+            .expect("Failed to connect to the test database");
+        conn.run_pending_migrations(MIGRATIONS)
+            .expect("Failed to run migrations");
+    })
+    .await
+    .expect("Migrations panicked");
+}
+
+/// Throwaway Postgres server with an empty database, stopped when dropped
+async fn start_postgres() -> (String, PostgreSQL) {
+    let mut postgres = PostgreSQL::default();
+    postgres.setup().await.expect("Failed to set up Postgres");
+    postgres.start().await.expect("Failed to start Postgres");
+    postgres
+        .create_database("pace_test")
+        .await
+        .expect("Failed to create the test database");
+    (postgres.settings().url("pace_test"), postgres)
+}
+
+fn test_config(database_url: &str, mock_api_url: &str) -> Config {
+    let yaml = format!(
+        r#"
+network: simulator
+networks:
+  simulator:
+    node_url: http://localhost:8080/v1
+    decibel_url: "{mock_api_url}"
+    contract_address: "{CONTRACT_ADDRESS}"
+    explorer_txn_url: "http://localhost/txn/{{hash}}"
+    chain_id: {CHAIN_ID}
+    testnet: true
+terminal_url: http://localhost:3000
+server_config:
+  port: 0
+  allowed_origins: []
+jwt_config:
+  secret: test-jwt-secret
+  expires_in: 1h
+api_key_config:
+  secret: test-api-key-secret
+db_config:
+  url: "{database_url}"
+  pool_size: 4
+signer_config:
+  backend: local
+bot_config:
+  token: "1:test"
+  username: test_bot
+  api_url: "{mock_api_url}"
+admin_config:
+  sponsor_private_keys: []
+stream_config:
+  indexer_grpc: http://localhost:50051
+  starting_version: 0
+  auth_token: test
+  request_name_header: test
+"#
+    );
+    serde_yaml::from_str(&yaml).expect("Invalid test config")
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use aptos_sdk::types::transaction::TransactionPayload;
+use pace_api::{
+    models::db::{transaction_outbox::STATUS_COMMITTED, users::User},
+    utils::{chain_client::SignedTxn, decibel_transaction::deposit_to_subaccount_at},
+};
+
+use crate::common::{COLLATERAL_METADATA, CONTRACT_ADDRESS, TestApp};
+
+/// 100 USDC in base units
+const WALLET_COLLATERAL: u64 = 100_000_000;
+/// 1 USDC in base units
+const DEPOSIT: u64 = 1_000_000;
+
+async fn deposit_payload(app: &TestApp, db_user: &User) -> (String, TransactionPayload) {
+    let mut conn = app.pool.get().await.unwrap();
+    let subaccount = app
+        .trading
+        .primary_subaccount(db_user, &mut conn)
+        .await
+        .unwrap();
+    let payload = deposit_to_subaccount_at(
+        CONTRACT_ADDRESS,
+        &subaccount.address,
+        COLLATERAL_METADATA,
+        DEPOSIT,
+    )
+    .unwrap();
+    (subaccount.address, payload)
+}
+
+async fn sign(app: &TestApp, db_user: &User, payload: TransactionPayload) -> SignedTxn {
+    let mut conn = app.pool.get().await.unwrap();
+    app.trading
+        .sign_txn(db_user, payload, &mut conn)
+        .await
+        .unwrap()
+}
+
+async fn sign_and_enqueue(app: &TestApp, db_user: &User, payload: TransactionPayload) {
+    let txn = sign(app, db_user, payload).await;
+    let mut conn = app.pool.get().await.unwrap();
+    app.trading
+        .enqueue_transaction(
+            db_user,
+            "deposit",
+            "Deposit".to_string(),
+            txn,
+            None,
+            None,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn concurrent_txns_of_one_user_get_distinct_sequence_numbers() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(WALLET_COLLATERAL).await;
+    let (subaccount, payload) = deposit_payload(&app, &db_user).await;
+
+    // every txn is simulated while the others are signed but not submitted yet
+    let submissions = (0..5).map(|_| sign_and_enqueue(&app, &db_user, payload.clone()));
+    futures_util::future::join_all(submissions).await;
+    app.drain_outbox().await;
+
+    let txns = app.outbox_txns_of(&db_user).await;
+    assert_eq!(txns.len(), 5);
+    assert!(txns.iter().all(|txn| txn.status == STATUS_COMMITTED));
+    assert_eq!(app.chain.sequence_number(&db_user.address).unwrap(), 5);
+    assert_eq!(
+        app.chain
+            .collateral(&subaccount, COLLATERAL_METADATA)
+            .unwrap(),
+        5 * DEPOSIT
+    );
+}
+
+#[tokio::test]
+async fn dropped_txn_gives_its_sequence_number_back() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(WALLET_COLLATERAL).await;
+    let (_, payload) = deposit_payload(&app, &db_user).await;
+
+    sign_and_enqueue(&app, &db_user, payload.clone()).await;
+    // signed, then abandoned like a handler that timed out before queueing it
+    let abandoned = sign(&app, &db_user, payload.clone()).await;
+    assert_eq!(abandoned.sequence_number(), 1);
+    drop(abandoned);
+    sign_and_enqueue(&app, &db_user, payload).await;
+    app.drain_outbox().await;
+
+    let txns = app.outbox_txns_of(&db_user).await;
+    assert!(txns.iter().all(|txn| txn.status == STATUS_COMMITTED));
+    assert_eq!(app.chain.sequence_number(&db_user.address).unwrap(), 2);
+}
+
+#[tokio::test]
+async fn rejected_simulation_takes_no_sequence_number() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(DEPOSIT).await;
+    let (subaccount, payload) = deposit_payload(&app, &db_user).await;
+
+    let too_much = deposit_to_subaccount_at(
+        CONTRACT_ADDRESS,
+        &subaccount,
+        COLLATERAL_METADATA,
+        2 * DEPOSIT,
+    )
+    .unwrap();
+    let mut conn = app.pool.get().await.unwrap();
+    let err = app
+        .trading
+        .sign_txn(&db_user, too_much, &mut conn)
+        .await
+        .err()
+        .expect("Deposit above the wallet balance was signed");
+    drop(conn);
+    assert!(
+        format!("{err:#}").contains("Insufficient balance"),
+        "{err:#}"
+    );
+
+    let txn = sign(&app, &db_user, payload).await;
+    assert_eq!(txn.sequence_number(), 0);
+}
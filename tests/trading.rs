@@ -0,0 +1,198 @@
+mod common;
+
+use axum::http::{Method, StatusCode};
+use pace_api::{
+    models::db::transaction_outbox::{STATUS_COMMITTED, STATUS_WAITING},
+    utils::decibel_transaction::{deposit_to_subaccount_at, withdraw_from_subaccount},
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::common::{COLLATERAL_METADATA, CONTRACT_ADDRESS, MARKET_ADDRESS, MARKET_NAME, TestApp};
+
+/// 100 USDC in base units
+const WALLET_COLLATERAL: u64 = 100_000_000;
+
+#[tokio::test]
+async fn limit_order_moves_margin_and_commits_through_the_outbox() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(WALLET_COLLATERAL).await;
+
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/api/v1/orders",
+            &db_user,
+            Some(json!({
+                "market": MARKET_NAME,
+                "is_long": true,
+                "leverage": 2,
+                "amount": "10",
+                "price": "50000",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let id = Uuid::parse_str(body["id"].as_str().unwrap()).unwrap();
+    let subaccount = body["subaccount"].as_str().unwrap().to_string();
+
+    app.drain_outbox().await;
+
+    assert_eq!(app.outbox_txn(id).await.status, STATUS_COMMITTED);
+    assert_eq!(
+        app.chain
+            .collateral(&subaccount, COLLATERAL_METADATA)
+            .unwrap(),
+        10_000_000
+    );
+    assert_eq!(
+        app.chain
+            .balance(&db_user.address, COLLATERAL_METADATA)
+            .unwrap(),
+        WALLET_COLLATERAL - 10_000_000
+    );
+    let position = app
+        .chain
+        .position(&subaccount, MARKET_ADDRESS)
+        .unwrap()
+        .expect("Order did not open a position");
+    assert!(position.size > 0);
+}
+
+#[tokio::test]
+async fn market_order_without_margin_is_rejected_before_queueing() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(0).await;
+
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/api/v1/orders",
+            &db_user,
+            Some(json!({
+                "market": MARKET_NAME,
+                "is_long": false,
+                "leverage": 2,
+                "amount": "10",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", body);
+    assert!(
+        body["msg"]
+            .as_str()
+            .unwrap()
+            .contains("Insufficient margin"),
+        "{}",
+        body
+    );
+    assert!(app.outbox_txns_of(&db_user).await.is_empty());
+}
+
+#[tokio::test]
+async fn sponsored_txns_are_paid_by_a_sponsor() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(WALLET_COLLATERAL).await;
+
+    let (status, body) = app
+        .request(
+            Method::POST,
+            "/api/v1/orders",
+            &db_user,
+            Some(json!({
+                "market": MARKET_NAME,
+                "is_long": true,
+                "leverage": 1,
+                "amount": "5",
+                "price": "49000",
+            })),
+        )
+        .await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    app.drain_outbox().await;
+
+    // the user never held APT, the deposit and the order were both sponsored
+    assert_eq!(app.chain.balance(&db_user.address, "0xa").unwrap(), 0);
+    assert_eq!(app.chain.sequence_number(&db_user.address).unwrap(), 2);
+}
+
+#[tokio::test]
+async fn dependent_txn_is_signed_once_the_first_one_committed() {
+    let app = TestApp::new().await;
+    let db_user = app.create_user(WALLET_COLLATERAL).await;
+    let mut conn = app.pool.get().await.unwrap();
+    let subaccount = app
+        .trading
+        .primary_subaccount(&db_user, &mut conn)
+        .await
+        .unwrap()
+        .address;
+    let deposit = deposit_to_subaccount_at(
+        CONTRACT_ADDRESS,
+        &subaccount,
+        COLLATERAL_METADATA,
+        WALLET_COLLATERAL,
+    )
+    .unwrap();
+    let txn = app
+        .trading
+        .sign_txn(&db_user, deposit.clone(), &mut conn)
+        .await
+        .unwrap();
+    app.trading
+        .enqueue_transaction(
+            &db_user,
+            "deposit",
+            "Deposit".to_string(),
+            txn,
+            None,
+            None,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+    app.drain_outbox().await;
+
+    // the wallet is empty, the deposit back only passes simulation after the withdrawal
+    let withdraw = withdraw_from_subaccount(
+        CONTRACT_ADDRESS,
+        &subaccount,
+        COLLATERAL_METADATA,
+        WALLET_COLLATERAL,
+    )
+    .unwrap();
+    let txn = app
+        .trading
+        .sign_txn(&db_user, withdraw, &mut conn)
+        .await
+        .unwrap();
+    let id = app
+        .trading
+        .enqueue_transaction_then(
+            &db_user,
+            ("withdraw", "Withdraw".to_string(), txn),
+            ("transfer", "Transfer".to_string(), deposit),
+            None,
+            None,
+            &mut conn,
+        )
+        .await
+        .unwrap();
+    drop(conn);
+    assert_eq!(app.outbox_txn(id).await.status, STATUS_WAITING);
+    app.drain_outbox().await;
+
+    assert!(
+        app.outbox_txns_of(&db_user)
+            .await
+            .iter()
+            .all(|txn| txn.status == STATUS_COMMITTED)
+    );
+    assert_eq!(
+        app.chain
+            .collateral(&subaccount, COLLATERAL_METADATA)
+            .unwrap(),
+        WALLET_COLLATERAL
+    );
+    assert_eq!(app.chain.sequence_number(&db_user.address).unwrap(), 3);
+}
@@ -0,0 +1,77 @@
+use pace_api::utils::vm_status::{MoveAbort, explain_vm_status};
+
+#[test]
+fn parses_an_abort_with_its_reason_and_category() {
+    let abort = MoveAbort::parse(
+        "Move abort in 0x1::fungible_asset: EINSUFFICIENT_BALANCE(0x10004): Not enough balance",
+    )
+    .unwrap();
+
+    assert_eq!(abort.module, "0x1::fungible_asset");
+    assert_eq!(abort.module_name(), "fungible_asset");
+    assert_eq!(abort.reason.as_deref(), Some("EINSUFFICIENT_BALANCE"));
+    assert_eq!(abort.code, Some(0x10004));
+    assert_eq!(abort.reason_code(), Some(4));
+}
+
+#[test]
+fn parses_an_abort_without_a_reason() {
+    let abort = MoveAbort::parse("Move abort in 0x1::primary_fungible_store: 0x10004").unwrap();
+
+    assert_eq!(abort.module_name(), "primary_fungible_store");
+    assert_eq!(abort.reason, None);
+    assert_eq!(abort.code, Some(0x10004));
+    assert_eq!(abort.reason_code(), Some(4));
+}
+
+#[test]
+fn other_statuses_are_not_aborts() {
+    assert_eq!(MoveAbort::parse("OUT_OF_GAS"), None);
+}
+
+#[test]
+fn explains_known_aborts() {
+    assert_eq!(
+        explain_vm_status("Move abort in 0x1::fungible_asset: EINSUFFICIENT_BALANCE(0x10004): x"),
+        "Insufficient balance in your wallet"
+    );
+    assert_eq!(
+        explain_vm_status("Move abort in 0x1::primary_fungible_store: 0x10004"),
+        "Insufficient balance in your wallet"
+    );
+    assert_eq!(
+        explain_vm_status(
+            "Move abort in 0xabc::dex_accounts: EINSUFFICIENT_MARGIN(0x10006): Insufficient margin"
+        ),
+        "Insufficient margin in your subaccount for this order"
+    );
+}
+
+#[test]
+fn unknown_aborts_name_their_module() {
+    assert_eq!(
+        explain_vm_status("Move abort in 0xabc::dex_accounts: EUNKNOWN(0x30063): Unknown"),
+        "Transaction rejected by dex_accounts (EUNKNOWN)"
+    );
+    // dex_accounts codes are only known by their reason names
+    assert_eq!(
+        explain_vm_status("Move abort in 0xabc::dex_accounts: 0x10006"),
+        "Transaction rejected by dex_accounts (code 65542)"
+    );
+}
+
+#[test]
+fn explains_vm_errors() {
+    assert_eq!(
+        explain_vm_status("SEQUENCE_NUMBER_TOO_OLD"),
+        "Another transaction from your wallet is in progress, please try again"
+    );
+    assert_eq!(
+        explain_vm_status("INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE"),
+        "Not enough APT to pay for gas"
+    );
+    assert_eq!(
+        explain_vm_status("SOMETHING_ELSE"),
+        "Transaction would fail: SOMETHING_ELSE"
+    );
+}